@@ -0,0 +1,410 @@
+use std::{
+    fmt::{self, Write},
+    time::Duration,
+};
+
+use crate::{
+    board::{cell_name, Board},
+    game::level_name,
+    replay::{ActionKind, Replay},
+    solver::{Deduction, Reason, Solver},
+};
+
+// what the solver makes of a single reveal
+#[derive(Clone, PartialEq, Debug)]
+pub enum Judgement {
+    // the first click of a game is always safe
+    FirstClick,
+    // the cell was proven safe, along with the deduction that proved it
+    Safe(Option<Deduction>),
+    // nothing on the board could be proven safe so the player had to guess
+    ForcedGuess {
+        probability: f64,
+    },
+    // a proven safe cell existed but the player clicked an unproven one instead
+    UnnecessaryRisk {
+        probability: f64,
+        alternative: (usize, usize),
+    },
+    // the cell was proven to be a mine
+    Blunder,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Move {
+    // which of the replay's actions it was
+    pub action: usize,
+    // milliseconds since the first reveal
+    pub time: u64,
+    pub cell: (usize, usize),
+    pub judgement: Judgement,
+    // how many cells the reveal uncovered
+    pub opened: usize,
+    // whether the cell had no mines around it, so it opened everything around it
+    pub opening: bool,
+    pub exploded: bool,
+}
+
+// one reveal in the solver's line through a board
+#[derive(Clone, PartialEq, Debug)]
+pub struct Step {
+    pub cell: (usize, usize),
+    // Safe with the deduction behind it, or ForcedGuess when nothing could be proven
+    pub judgement: Judgement,
+    // the guess turned out to be a mine
+    pub exploded: bool,
+}
+
+impl Step {
+    // a line saying what the step was and why, like "C4 is safe because the 1 at B3 already has all its mines"
+    pub fn describe(&self, board: &Board) -> String {
+        let name = cell_name(self.cell.0, self.cell.1);
+
+        match &self.judgement {
+            Judgement::Safe(Some(deduction)) => {
+                format!("{name} is safe because {}", deduction.why(board))
+            }
+            Judgement::ForcedGuess { probability } => format!(
+                "{name} is a guess with a {:.0}% chance of a mine, {}",
+                probability * 100.0,
+                if self.exploded {
+                    "and it was one"
+                } else {
+                    "and it was safe"
+                },
+            ),
+            _ => format!("{name} is safe"),
+        }
+    }
+}
+
+// how the solver would finish the game from where `board` is now. it reveals everything it can prove safe,
+// and when it can't prove anything it takes the guess least likely to be a mine.
+// a guess that hits a mine counts as a found mine and it carries on, so the line always clears the board
+pub fn solution(board: &Board) -> Vec<Step> {
+    let mut board = board.clone();
+    let mut steps = Vec::new();
+
+    let cleared = |board: &Board| {
+        (0..board.width() * board.height())
+            .map(|i| board.position(i))
+            .all(|(x, y)| board.is_mine(x, y) || board.is_uncovered(x, y))
+    };
+
+    while !cleared(&board) {
+        let solver = Solver::new(&board);
+        let safe: Vec<Step> = solver
+            .safe_cells()
+            .into_iter()
+            .map(|(x, y)| Step {
+                cell: (x, y),
+                judgement: Judgement::Safe(solver.deduction_for(x, y).cloned()),
+                exploded: false,
+            })
+            .collect();
+
+        let next = match safe.is_empty() {
+            false => safe,
+            true => match solver.best_guess() {
+                Some(((x, y), probability)) => vec![Step {
+                    cell: (x, y),
+                    judgement: Judgement::ForcedGuess { probability },
+                    exploded: board.is_mine(x, y),
+                }],
+                None => break,
+            },
+        };
+
+        for step in next {
+            // an earlier reveal's opening might have got to it already
+            if !board.is_uncovered(step.cell.0, step.cell.1) {
+                board.uncover(step.cell.0, step.cell.1);
+                steps.push(step);
+            }
+        }
+    }
+
+    steps
+}
+
+// a replay walked through move by move with the solver looking over the player's shoulder
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub level: u8,
+    pub board: Board,
+    pub moves: Vec<Move>,
+    pub duration: Duration,
+    pub won: bool,
+}
+
+pub fn analyse(replay: &Replay) -> Analysis {
+    let mut board = Board::with_mines(replay.width, replay.height, &replay.mines);
+    let mut moves = Vec::new();
+
+    for (i, action) in replay.actions.iter().enumerate() {
+        let (x, y) = (action.x, action.y);
+
+        if action.kind == ActionKind::Mark {
+            board.toggle_flag(x, y);
+            continue;
+        }
+
+        if board.is_uncovered(x, y) {
+            continue;
+        }
+
+        let judgement = if board.num_uncovered() == 0 {
+            Judgement::FirstClick
+        } else {
+            let solver = Solver::new(&board);
+
+            if solver.is_safe(x, y) {
+                Judgement::Safe(solver.deduction_for(x, y).cloned())
+            } else if solver.is_mine(x, y) {
+                Judgement::Blunder
+            } else {
+                let probability = solver.probability(x, y);
+
+                match solver.safe_cells().first() {
+                    Some(&alternative) => Judgement::UnnecessaryRisk {
+                        probability,
+                        alternative,
+                    },
+                    None => Judgement::ForcedGuess { probability },
+                }
+            }
+        };
+
+        let opening = !board.is_mine(x, y) && board.adjacent_mines(x, y) == 0;
+        let opened = board.uncover(x, y).len();
+
+        moves.push(Move {
+            action: i,
+            time: action.time,
+            cell: (x, y),
+            judgement,
+            opened,
+            opening,
+            exploded: board.is_mine(x, y),
+        });
+    }
+
+    let exploded = moves.iter().any(|action| action.exploded);
+    let won =
+        !exploded && board.num_uncovered() == (board.width() * board.height()) - board.num_mines();
+
+    Analysis {
+        level: replay.level,
+        board,
+        moves,
+        duration: replay.duration(),
+        won,
+    }
+}
+
+impl Analysis {
+    // the reveal that set off the mine the game ended on, if it ended on one
+    pub fn fatal(&self) -> Option<&Move> {
+        self.moves.iter().rev().find(|action| action.exploded)
+    }
+
+    // whether the mine that ended the game could have been avoided, as a sentence
+    pub fn verdict(&self) -> Option<String> {
+        let action = self.fatal()?;
+        let name = cell_name(action.cell.0, action.cell.1);
+
+        let verdict = match action.judgement {
+            Judgement::ForcedGuess { probability } => format!(
+                "{name} was a forced guess: nothing on the board could be proven safe and it had a {:.0}% chance of a mine, that's just bad luck",
+                probability * 100.0,
+            ),
+            Judgement::UnnecessaryRisk { probability, alternative } => format!(
+                "{name} was a blunder: it had a {:.0}% chance of a mine while {} was provably safe",
+                probability * 100.0,
+                cell_name(alternative.0, alternative.1),
+            ),
+            Judgement::Blunder => format!("{name} was a blunder: the numbers around it prove it's a mine"),
+            _ => return None,
+        };
+
+        Some(verdict)
+    }
+
+    // every time a safe cell could have been proven and something riskier got clicked instead, whether it went off or not
+    pub fn missed(&self) -> Vec<String> {
+        let seconds = |time: u64| format!("{:.1}s", time as f64 / 1000.0);
+
+        self.moves
+            .iter()
+            .filter_map(|action| {
+                let name = cell_name(action.cell.0, action.cell.1);
+
+                match action.judgement {
+                    Judgement::UnnecessaryRisk {
+                        probability,
+                        alternative,
+                    } => Some(format!(
+                        "{}: {name} with a {:.0}% chance of a mine, {} was provably safe",
+                        seconds(action.time),
+                        probability * 100.0,
+                        cell_name(alternative.0, alternative.1),
+                    )),
+                    Judgement::Blunder => Some(format!(
+                        "{}: {name}, which was provably a mine",
+                        seconds(action.time)
+                    )),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    // a markdown write-up of the game: where it opened up, how it was solved, where it was guessed and where it went wrong
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        self.write_report(&mut report)
+            .expect("writing to a String can't fail");
+        report
+    }
+
+    fn write_report(&self, report: &mut String) -> fmt::Result {
+        let board = &self.board;
+        let seconds = |time: u64| format!("{:.1}s", time as f64 / 1000.0);
+        let percent = |probability: f64| format!("{:.0}%", probability * 100.0);
+
+        let count = |f: fn(&Judgement) -> bool| {
+            self.moves
+                .iter()
+                .filter(|action| f(&action.judgement))
+                .count()
+        };
+        let safe = count(|judgement| matches!(judgement, Judgement::Safe(_)));
+        let guesses = count(|judgement| matches!(judgement, Judgement::ForcedGuess { .. }));
+        let risks = count(|judgement| matches!(judgement, Judgement::UnnecessaryRisk { .. }));
+        let blunders = count(|judgement| matches!(judgement, Judgement::Blunder));
+        let openings: Vec<&Move> = self.moves.iter().filter(|action| action.opening).collect();
+
+        writeln!(report, "# Minesweeper game report\n")?;
+        writeln!(
+            report,
+            "{}, {}x{} board with {} mines. {} after {}.\n",
+            level_name(self.level),
+            board.width(),
+            board.height(),
+            board.num_mines(),
+            if self.won { "Won" } else { "Lost" },
+            seconds(self.duration.as_millis() as u64),
+        )?;
+
+        writeln!(report, "## Summary\n")?;
+        writeln!(report, "- {} reveals", self.moves.len())?;
+        writeln!(report, "- {safe} proven safe")?;
+        writeln!(report, "- {guesses} forced guesses")?;
+        writeln!(report, "- {} mistakes", risks + blunders)?;
+        writeln!(report, "- {} openings\n", openings.len())?;
+
+        writeln!(report, "## Openings\n")?;
+        for action in &openings {
+            let (x, y) = action.cell;
+            writeln!(
+                report,
+                "- {} at {} opened {} cells",
+                cell_name(x, y),
+                seconds(action.time),
+                action.opened
+            )?;
+        }
+
+        if openings.is_empty() {
+            writeln!(report, "No openings were found.")?;
+        }
+
+        // the deductions that took more than one number to see are the interesting ones
+        writeln!(report, "\n## Key deductions\n")?;
+        let mut key_deductions = 0;
+
+        for action in &self.moves {
+            if let Judgement::Safe(Some(deduction)) = &action.judgement {
+                if deduction.reason == Reason::Subset {
+                    let (x, y) = action.cell;
+                    writeln!(
+                        report,
+                        "- {} at {}: {}",
+                        cell_name(x, y),
+                        seconds(action.time),
+                        deduction.describe(board)
+                    )?;
+                    key_deductions += 1;
+                }
+            }
+        }
+
+        if key_deductions == 0 {
+            writeln!(report, "Every safe cell followed from a single number.")?;
+        }
+
+        writeln!(report, "\n## Forced guesses\n")?;
+        for action in &self.moves {
+            if let Judgement::ForcedGuess { probability } = action.judgement {
+                let (x, y) = action.cell;
+                writeln!(
+                    report,
+                    "- {} at {}: {} chance of a mine, {}",
+                    cell_name(x, y),
+                    seconds(action.time),
+                    percent(probability),
+                    if action.exploded {
+                        "exploded"
+                    } else {
+                        "survived"
+                    },
+                )?;
+            }
+        }
+
+        if guesses == 0 {
+            writeln!(report, "No guesses were needed.")?;
+        }
+
+        writeln!(report, "\n## Mistakes\n")?;
+        for action in &self.moves {
+            let (x, y) = action.cell;
+            let outcome = if action.exploded {
+                "exploded"
+            } else {
+                "got away with it"
+            };
+
+            match action.judgement {
+                Judgement::UnnecessaryRisk {
+                    probability,
+                    alternative,
+                } => {
+                    writeln!(
+                        report,
+                        "- {} at {}: took a {} chance of a mine while {} was proven safe, {outcome}",
+                        cell_name(x, y),
+                        seconds(action.time),
+                        percent(probability),
+                        cell_name(alternative.0, alternative.1),
+                    )?;
+                }
+                Judgement::Blunder => {
+                    writeln!(
+                        report,
+                        "- {} at {}: was proven to be a mine, {outcome}",
+                        cell_name(x, y),
+                        seconds(action.time)
+                    )?;
+                }
+                _ => (),
+            }
+        }
+
+        if risks + blunders == 0 {
+            writeln!(report, "No mistakes.")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{paths, replay::Replay, saves::SavedGame, versioned};
+
+// the game that was being played when the last session was cut off, if one was
+pub fn load() -> io::Result<Option<SavedGame>> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let saved: SavedGame = versioned::from_str(&text)?;
+    saved.replay.check()?;
+    Ok(Some(saved))
+}
+
+// write out the game in progress, `elapsed` into it, so it can be picked back up if the session's cut off before it ends.
+// it's written next to the old one and moved over it, so being cut off part way through a write leaves the old one whole
+pub fn save(replay: &Replay, elapsed: Duration) -> io::Result<()> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let writing = path.with_extension("json.tmp");
+    fs::write(
+        &writing,
+        versioned::to_string(&SavedGame::new(replay, elapsed))?,
+    )?;
+    fs::rename(writing, path)
+}
+
+// the game ended the way it's meant to, so there's nothing to pick back up
+pub fn clear() -> io::Result<()> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("autosave.json"))
+}
@@ -0,0 +1,124 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::Board,
+    generator::{self, Settings},
+    player::{self, Player, Round, Standing},
+};
+
+// how a player got on playing a batch of generated boards, and how long it all took
+#[derive(Debug)]
+pub struct Results {
+    pub games: usize,
+    // games cleared without hitting a mine
+    pub wins: usize,
+    pub generating: Duration,
+    pub playing: Duration,
+}
+
+// play `games` boards with `player`, starting from `seed` and using the next seed for each one after
+pub fn run(settings: Settings, games: usize, seed: u64, player: &mut dyn Player) -> Results {
+    let mut results = Results {
+        games,
+        wins: 0,
+        generating: Duration::ZERO,
+        playing: Duration::ZERO,
+    };
+    let start = settings.start();
+
+    for i in 0..games {
+        if !player.keeps_playing() {
+            results.games = i;
+            break;
+        }
+
+        let started = Instant::now();
+        let mines = generator::layout(settings, seed.wrapping_add(i as u64), start);
+        results.generating += started.elapsed();
+
+        let started = Instant::now();
+        let mut round =
+            Round::with_board(Board::with_mines(settings.width, settings.height, &mines));
+        let standing = player::play(player, &mut round);
+
+        // a board that was stopped part way through doesn't count
+        if standing == Standing::Playing && !player.keeps_playing() {
+            results.games = i;
+            break;
+        }
+
+        results.playing += started.elapsed();
+        results.wins += (standing == Standing::Won) as usize;
+    }
+
+    results
+}
+
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self.games.max(1) as u32;
+
+        writeln!(
+            f,
+            "won {} of {} games ({:.1}%)",
+            self.wins,
+            self.games,
+            self.wins as f64 * 100.0 / games as f64
+        )?;
+        writeln!(
+            f,
+            "generating: {:.0} boards a second, {:.2?} each",
+            self.games as f64 / self.generating.as_secs_f64().max(f64::EPSILON),
+            self.generating / games,
+        )?;
+        writeln!(f, "playing: {:.2?} a game on average", self.playing / games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{Action, BoardView, SolverPlayer};
+
+    // the solver, until it's played `left` games
+    struct Tired {
+        solver: SolverPlayer,
+        left: usize,
+    }
+
+    impl Player for Tired {
+        fn next_action(&mut self, view: &BoardView) -> Action {
+            match self.left {
+                0 => Action::Quit,
+                _ => self.solver.next_action(view),
+            }
+        }
+
+        fn finished(&mut self, _view: &BoardView) {
+            self.left = self.left.saturating_sub(1);
+        }
+
+        fn keeps_playing(&self) -> bool {
+            self.left > 0
+        }
+    }
+
+    #[test]
+    fn a_player_can_stop_early() {
+        let settings = Settings {
+            width: 9,
+            height: 9,
+            mines: 10,
+            placement: Default::default(),
+        };
+        let mut player = Tired {
+            solver: SolverPlayer::default(),
+            left: 3,
+        };
+
+        assert_eq!(run(settings, 100, 1, &mut player).games, 3);
+    }
+}
@@ -0,0 +1,757 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+// everything there is to know about one cell packed into a byte, so even a 1000x1000 board is only a megabyte.
+// the low four bits are how many mines touch it and the four above are whether it's a mine, uncovered, flagged and a wall
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CellState(u8);
+
+impl CellState {
+    const ADJACENT: u8 = 0b0000_1111;
+    const MINE: u8 = 0b0001_0000;
+    const UNCOVERED: u8 = 0b0010_0000;
+    const FLAGGED: u8 = 0b0100_0000;
+    const WALL: u8 = 0b1000_0000;
+
+    pub fn is_mine(self) -> bool {
+        self.0 & Self::MINE != 0
+    }
+
+    pub fn is_uncovered(self) -> bool {
+        self.0 & Self::UNCOVERED != 0
+    }
+
+    pub fn is_flagged(self) -> bool {
+        self.0 & Self::FLAGGED != 0
+    }
+
+    // walls are left uncovered from the start with nothing around them counted, so openings stop at them
+    // and nothing that's looking for covered cells or numbers ever picks them
+    pub fn is_wall(self) -> bool {
+        self.0 & Self::WALL != 0
+    }
+
+    // how many mines touch this cell
+    pub fn adjacent(self) -> usize {
+        (self.0 & Self::ADJACENT) as usize
+    }
+
+    pub fn set_uncovered(&mut self, uncovered: bool) {
+        self.set(Self::UNCOVERED, uncovered);
+    }
+
+    pub fn set_flagged(&mut self, flagged: bool) {
+        self.set(Self::FLAGGED, flagged);
+    }
+
+    fn set(&mut self, bit: u8, on: bool) {
+        match on {
+            true => self.0 |= bit,
+            false => self.0 &= !bit,
+        }
+    }
+}
+
+// the most cells a board can have. game codes and board files can ask for anything up to 65535x65535,
+// which is far more memory than anyone has, so they're turned away past this
+pub const MAX_CELLS: usize = 1000 * 1000;
+
+// a minesweeper board on its own, without anything to do with drawing it or reading input.
+// coordinates are (x, y) with (0, 0) in the bottom left, like the rest of the game
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(into = "BoardFile", try_from = "BoardFile")]
+pub struct Board {
+    width: usize,
+    height: usize,
+    // every cell is stored in one flat list, y * width + x
+    cells: Vec<CellState>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CellState::default(); width * height],
+        }
+    }
+
+    // a board with mines at the given indices (y * width + x)
+    pub fn with_mines(width: usize, height: usize, mines: &[usize]) -> Self {
+        let mut board = Self::new(width, height);
+        board.place_mines(mines);
+        board
+    }
+
+    // add mines at the given indices (y * width + x), leaving everything else about the cells alone.
+    // the cells around each one keep count as they go so nothing has to be counted again later
+    pub fn place_mines(&mut self, mines: &[usize]) {
+        for &i in mines {
+            if self.cells[i].is_mine() {
+                continue;
+            }
+
+            self.cells[i].set(CellState::MINE, true);
+
+            let (x, y) = self.position(i);
+            for (nx, ny) in self.neighbors(x, y) {
+                let i = self.index(nx, ny);
+
+                if !self.cells[i].is_wall() {
+                    self.cells[i].0 += 1;
+                }
+            }
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        (y * self.width) + x
+    }
+
+    pub fn position(&self, i: usize) -> (usize, usize) {
+        (i % self.width, i / self.width)
+    }
+
+    // every (x, y) on the board, a row at a time from the bottom
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i % width, i / width))
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> CellState {
+        self.cells[self.index(x, y)]
+    }
+
+    pub fn cell_mut(&mut self, x: usize, y: usize) -> &mut CellState {
+        let i = self.index(x, y);
+        &mut self.cells[i]
+    }
+
+    // every cell touching (x, y), not including itself
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&step| step != (0, 0))
+            .filter_map(move |(dx, dy)| {
+                let (nx, ny) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                (nx < width && ny < height).then_some((nx, ny))
+            })
+    }
+
+    pub fn is_mine(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_mine()
+    }
+
+    pub fn is_uncovered(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_uncovered()
+    }
+
+    pub fn is_flagged(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_flagged()
+    }
+
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_wall()
+    }
+
+    // turn a cell that isn't a mine into a wall
+    pub fn add_wall(&mut self, x: usize, y: usize) {
+        let cell = self.cell_mut(x, y);
+
+        if !cell.is_mine() {
+            cell.0 = CellState::WALL | CellState::UNCOVERED;
+        }
+    }
+
+    // how many mines touch (x, y)
+    pub fn adjacent_mines(&self, x: usize, y: usize) -> usize {
+        self.cell(x, y).adjacent()
+    }
+
+    pub fn num_mines(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_mine()).count()
+    }
+
+    pub fn num_uncovered(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_uncovered()).count()
+    }
+
+    pub fn num_flagged(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_flagged()).count()
+    }
+
+    // the board's 3BV, the fewest clicks that clear it without flagging: one for every opening and one for every
+    // number that isn't on the edge of an opening. returns how many of those clicks are already done and how many there are
+    pub fn three_bv(&self) -> (usize, usize) {
+        let (in_openings, openings) = self.find_openings();
+        let mut solved = openings.iter().filter(|&&opened| opened).count();
+        let mut total = openings.len();
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            if !in_openings[i] && !cell.is_mine() && !cell.is_wall() {
+                total += 1;
+                solved += cell.is_uncovered() as usize;
+            }
+        }
+
+        (solved, total)
+    }
+
+    // how many openings there are, patches of cells with no mines around them that uncover all at once
+    pub fn openings(&self) -> usize {
+        self.find_openings().1.len()
+    }
+
+    // how many islands there are, groups of touching numbers that aren't on the edge of any opening so they can only be
+    // cleared one cell at a time
+    pub fn islands(&self) -> usize {
+        let (mut counted, _) = self.find_openings();
+        let mut islands = 0;
+
+        for (x, y) in self.positions() {
+            let cell = self.cell(x, y);
+
+            if counted[self.index(x, y)] || cell.is_mine() || cell.is_wall() {
+                continue;
+            }
+
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                let i = self.index(x, y);
+
+                if !counted[i] && !self.cells[i].is_mine() && !self.cells[i].is_wall() {
+                    counted[i] = true;
+                    stack.extend(self.neighbors(x, y));
+                }
+            }
+
+            islands += 1;
+        }
+
+        islands
+    }
+
+    // every cell that's in an opening or on the edge of one, and whether each opening has been opened yet
+    fn find_openings(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut counted = vec![false; self.cells.len()];
+        let mut openings = Vec::new();
+
+        for (x, y) in self.positions() {
+            let cell = self.cell(x, y);
+
+            if counted[self.index(x, y)] || cell.is_mine() || cell.is_wall() || cell.adjacent() != 0
+            {
+                continue;
+            }
+
+            // take in the whole opening along with the numbers around its edge
+            let mut opened = false;
+            let mut stack = vec![(x, y)];
+
+            while let Some((x, y)) = stack.pop() {
+                let i = self.index(x, y);
+
+                if counted[i] || self.cells[i].is_wall() {
+                    continue;
+                }
+
+                counted[i] = true;
+                opened |= self.cells[i].is_uncovered();
+
+                if self.cells[i].adjacent() == 0 {
+                    stack.extend(self.neighbors(x, y));
+                }
+            }
+
+            openings.push(opened);
+        }
+
+        (counted, openings)
+    }
+
+    // flag a covered cell, or take the flag off if it already has one
+    pub fn toggle_flag(&mut self, x: usize, y: usize) {
+        let cell = self.cell_mut(x, y);
+
+        if !cell.is_uncovered() {
+            cell.set_flagged(!cell.is_flagged());
+        }
+    }
+
+    // uncover a cell, and if there aren't any mines around it everything around it as well.
+    // returns every cell that got uncovered
+    pub fn uncover(&mut self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut uncovered = Vec::new();
+        let mut stack = vec![(x, y)];
+
+        while let Some((x, y)) = stack.pop() {
+            let cell = self.cell_mut(x, y);
+
+            if cell.is_uncovered() {
+                continue;
+            }
+
+            cell.set_uncovered(true);
+            cell.set_flagged(false);
+            uncovered.push((x, y));
+
+            if !cell.is_mine() && cell.adjacent() == 0 {
+                stack.extend(self.neighbors(x, y));
+            }
+        }
+
+        uncovered
+    }
+
+    // the board as a text grid from the top row down, one character a cell:
+    // - covered, * covered mine, o uncovered, x uncovered mine, f flagged, F flagged mine and # a wall.
+    // puzzle boards are written the same way, without x, f and F
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let cell = self.cell(x, y);
+
+                text.push(
+                    match (cell.is_mine(), cell.is_uncovered(), cell.is_flagged()) {
+                        _ if cell.is_wall() => '#',
+                        (false, true, _) => 'o',
+                        (true, true, _) => 'x',
+                        (false, false, true) => 'f',
+                        (true, false, true) => 'F',
+                        (false, false, false) => '-',
+                        (true, false, false) => '*',
+                    },
+                );
+            }
+
+            text.push('\n');
+        }
+
+        text
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(ErrorKind::InvalidData, message);
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().map(|row| row.chars().count()).unwrap_or(0);
+
+        if width == 0 || rows.iter().any(|row| row.chars().count() != width) {
+            return Err(invalid("every row of the board has to be the same length, and there has to be at least one".to_string()));
+        }
+
+        let height = rows.len();
+        let cells: Vec<((usize, usize), char)> = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(x, cell)| ((x, height - 1 - row), cell))
+            })
+            .collect();
+
+        if let Some((_, cell)) = cells.iter().find(|(_, cell)| !"-*oxfF#".contains(*cell)) {
+            return Err(invalid(format!(
+                "{cell:?} isn't a cell, use - * o x f F or #"
+            )));
+        }
+
+        let mines: Vec<usize> = cells
+            .iter()
+            .filter(|(_, cell)| "*xF".contains(*cell))
+            .map(|&((x, y), _)| y * width + x)
+            .collect();
+        let mut board = Self::with_mines(width, height, &mines);
+
+        for &((x, y), cell) in &cells {
+            board.cell_mut(x, y).set_uncovered("ox".contains(cell));
+            board.cell_mut(x, y).set_flagged("fF".contains(cell));
+
+            if cell == '#' {
+                board.add_wall(x, y);
+            }
+        }
+
+        Ok(board)
+    }
+
+    // read a board written by save(), JSON if the file ends in .json and a text grid otherwise
+    #[cfg(feature = "std")]
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+
+        match is_json(path) {
+            true => serde_json::from_str(&text)
+                .map_err(|error| io::Error::new(ErrorKind::InvalidData, error)),
+            false => Self::from_text(&text),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = match is_json(path) {
+            true => serde_json::to_string_pretty(self)?,
+            false => self.to_text(),
+        };
+
+        fs::write(path, text)
+    }
+}
+
+#[cfg(feature = "std")]
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+// how a board is written as JSON: its size and the indices (y * width + x) of the cells that are mines,
+// uncovered and flagged, the same way replays list their mines
+#[derive(Serialize, Deserialize)]
+struct BoardFile {
+    width: usize,
+    height: usize,
+    mines: Vec<usize>,
+    #[serde(default)]
+    uncovered: Vec<usize>,
+    #[serde(default)]
+    flagged: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    walls: Vec<usize>,
+}
+
+impl From<Board> for BoardFile {
+    fn from(board: Board) -> Self {
+        let indices = |keep: fn(CellState) -> bool| {
+            (0..board.cells.len())
+                .filter(|&i| keep(board.cells[i]))
+                .collect()
+        };
+
+        Self {
+            width: board.width,
+            height: board.height,
+            mines: indices(CellState::is_mine),
+            uncovered: indices(|cell| cell.is_uncovered() && !cell.is_wall()),
+            flagged: indices(CellState::is_flagged),
+            walls: indices(CellState::is_wall),
+        }
+    }
+}
+
+impl TryFrom<BoardFile> for Board {
+    type Error = String;
+
+    fn try_from(file: BoardFile) -> Result<Self, Self::Error> {
+        let size = file
+            .width
+            .checked_mul(file.height)
+            .filter(|&size| size <= MAX_CELLS)
+            .ok_or_else(|| {
+                format!(
+                    "a {}x{} board is too big, it can have at most {MAX_CELLS} cells",
+                    file.width, file.height
+                )
+            })?;
+
+        if size == 0 {
+            return Err("the board has to be at least 1x1".to_string());
+        }
+
+        if let Some(i) = file
+            .mines
+            .iter()
+            .chain(&file.uncovered)
+            .chain(&file.flagged)
+            .chain(&file.walls)
+            .find(|&&i| i >= size)
+        {
+            return Err(format!(
+                "cell {i} is off the {}x{} board",
+                file.width, file.height
+            ));
+        }
+
+        let mut board = Self::with_mines(file.width, file.height, &file.mines);
+        file.uncovered
+            .iter()
+            .for_each(|&i| board.cells[i].set_uncovered(true));
+        file.flagged
+            .iter()
+            .for_each(|&i| board.cells[i].set_flagged(true));
+
+        for &i in &file.walls {
+            let (x, y) = board.position(i);
+            board.add_wall(x, y);
+        }
+
+        Ok(board)
+    }
+}
+
+// the letter above a column: A-Z, then a-z for really wide boards
+pub fn column_label(x: usize) -> char {
+    match (x % 52) as u8 {
+        x @ 0..=25 => (b'A' + x) as char,
+        x => (b'a' + x - 26) as char,
+    }
+}
+
+// a cell's name like a chess square, "C4" is the third column and fourth row from the bottom
+pub fn cell_name(x: usize, y: usize) -> String {
+    format!("{}{}", column_label(x), y + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(cells: impl Iterator<Item = (usize, usize)>) -> Vec<(usize, usize)> {
+        let mut cells: Vec<_> = cells.collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn middle_has_eight_neighbors() {
+        let board = Board::new(3, 3);
+
+        assert_eq!(
+            sorted(board.neighbors(1, 1)),
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn corners_have_three_neighbors() {
+        let board = Board::new(4, 3);
+
+        assert_eq!(sorted(board.neighbors(0, 0)), vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(sorted(board.neighbors(3, 0)), vec![(2, 0), (2, 1), (3, 1)]);
+        assert_eq!(sorted(board.neighbors(0, 2)), vec![(0, 1), (1, 1), (1, 2)]);
+        assert_eq!(sorted(board.neighbors(3, 2)), vec![(2, 1), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn edges_have_five_neighbors() {
+        let board = Board::new(4, 3);
+
+        assert_eq!(
+            sorted(board.neighbors(1, 0)),
+            vec![(0, 0), (0, 1), (1, 1), (2, 0), (2, 1)]
+        );
+        assert_eq!(
+            sorted(board.neighbors(0, 1)),
+            vec![(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)]
+        );
+        assert_eq!(board.neighbors(3, 1).count(), 5);
+        assert_eq!(board.neighbors(2, 2).count(), 5);
+    }
+
+    #[test]
+    fn thin_boards() {
+        assert_eq!(Board::new(1, 1).neighbors(0, 0).count(), 0);
+        assert_eq!(
+            sorted(Board::new(1, 3).neighbors(0, 1)),
+            vec![(0, 0), (0, 2)]
+        );
+        assert_eq!(sorted(Board::new(3, 1).neighbors(0, 0)), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn adjacent_mines_counts_neighbors() {
+        // mines in the bottom left and top right corners of a 3x3 board
+        let board = Board::with_mines(3, 3, &[0, 8]);
+
+        assert_eq!(board.adjacent_mines(1, 1), 2);
+        assert_eq!(board.adjacent_mines(0, 1), 1);
+        assert_eq!(board.adjacent_mines(2, 0), 0);
+    }
+
+    #[test]
+    fn cells_keep_their_bits_apart() {
+        let mut board = Board::with_mines(3, 3, &[0, 1, 2, 3, 5, 6, 7, 8]);
+        let cell = board.cell_mut(1, 1);
+
+        cell.set_flagged(true);
+        assert_eq!(
+            (
+                cell.is_mine(),
+                cell.is_uncovered(),
+                cell.is_flagged(),
+                cell.adjacent()
+            ),
+            (false, false, true, 8)
+        );
+
+        cell.set_flagged(false);
+        cell.set_uncovered(true);
+        assert_eq!(
+            (
+                cell.is_mine(),
+                cell.is_uncovered(),
+                cell.is_flagged(),
+                cell.adjacent()
+            ),
+            (false, true, false, 8)
+        );
+
+        assert!(board.is_mine(0, 0));
+        assert_eq!(board.adjacent_mines(0, 0), 2);
+        assert_eq!(core::mem::size_of::<CellState>(), 1);
+    }
+
+    #[test]
+    fn three_bv_counts_openings_and_lone_numbers() {
+        // one mine in the middle of a 5x1 strip: an opening either side, each taking the number next to the mine with it
+        let mut board = Board::with_mines(5, 1, &[2]);
+        assert_eq!(board.three_bv(), (0, 2));
+
+        board.uncover(0, 0);
+        assert_eq!(board.three_bv(), (1, 2));
+
+        // mines down both sides of a 3x3 board leave a column of numbers with no opening, every one its own click
+        let board = Board::with_mines(3, 3, &[0, 3, 6, 2, 5, 8]);
+        assert_eq!(board.three_bv(), (0, 3));
+        assert_eq!((board.openings(), board.islands()), (0, 1));
+
+        let board = Board::with_mines(5, 1, &[2]);
+        assert_eq!((board.openings(), board.islands()), (2, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn boards_come_back_the_same() {
+        let mut board = Board::with_mines(4, 3, &[0, 6, 11]);
+        board.uncover(3, 0);
+        board.toggle_flag(0, 0);
+        board.toggle_flag(1, 2);
+        board.cell_mut(3, 2).set_uncovered(true);
+
+        assert_eq!(board.to_text(), "-f-x\n--*-\nF--o\n");
+        assert_eq!(Board::from_text(&board.to_text()).unwrap(), board);
+
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+
+        assert!(Board::from_text("--\n-").is_err());
+        assert!(
+            serde_json::from_str::<Board>(r#"{"width": 2, "height": 2, "mines": [4]}"#).is_err()
+        );
+    }
+
+    // an opening stops at a wall and the cells behind it stay covered, and a wall never gets a number
+    #[test]
+    #[cfg(feature = "std")]
+    fn walls_stop_openings() {
+        let mut board = Board::from_text("--#-*\n--#--\n--#--").unwrap();
+        assert_eq!(board.adjacent_mines(2, 1), 0);
+        assert_eq!(board.adjacent_mines(3, 1), 1);
+
+        board.uncover(0, 0);
+        assert_eq!(board.num_uncovered(), 9);
+        assert!(!board.is_uncovered(3, 0));
+        assert_eq!(board.to_text(), "oo#-*\noo#--\noo#--\n");
+        assert_eq!(board.three_bv(), (1, 3));
+    }
+
+    // a file asking for more cells than anyone has memory for is turned away before anything is allocated
+    #[test]
+    fn huge_boards_are_refused() {
+        let file = |width, height| BoardFile {
+            width,
+            height,
+            mines: Vec::new(),
+            uncovered: Vec::new(),
+            flagged: Vec::new(),
+            walls: Vec::new(),
+        };
+
+        assert!(Board::try_from(file(1000, 1000)).is_ok());
+        assert!(Board::try_from(file(1001, 1000)).is_err());
+        assert!(Board::try_from(file(usize::MAX, 2)).is_err());
+    }
+
+    // placing the same mine twice shouldn't count it twice
+    #[test]
+    fn repeated_mines_count_once() {
+        let board = Board::with_mines(2, 2, &[0, 0]);
+
+        assert_eq!(board.num_mines(), 1);
+        assert_eq!(board.adjacent_mines(1, 1), 1);
+    }
+
+    // a rough benchmark, run it with `cargo test --release -- --ignored`. it fails with the timings if a
+    // 1000x1000 board takes more than a second to make and open up
+    #[test]
+    #[ignore]
+    #[cfg(feature = "std")]
+    fn large_board() {
+        use std::time::{Duration, Instant};
+
+        use crate::generator::{self, Settings};
+
+        let settings = Settings {
+            width: 1000,
+            height: 1000,
+            mines: 50_000,
+            placement: Default::default(),
+        };
+        let (x, y) = settings.start();
+
+        let started = Instant::now();
+        let mines = generator::generate(settings, 0);
+        let generated = started.elapsed();
+
+        let started = Instant::now();
+        let mut board = Board::with_mines(settings.width, settings.height, &mines);
+        let placed = started.elapsed();
+
+        let started = Instant::now();
+        let opened = board.uncover(x, y).len();
+        let revealed = started.elapsed();
+
+        assert!(opened > 1);
+        assert!(
+            generated + placed + revealed < Duration::from_secs(1),
+            "generate: {generated:?}, place: {placed:?}, reveal {opened} cells: {revealed:?}"
+        );
+    }
+}
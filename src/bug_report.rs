@@ -0,0 +1,181 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crossterm::terminal;
+use serde::Serialize;
+use tar::{Builder, Header};
+
+use crate::{
+    autosave,
+    replay::Replay,
+    terminal::{crash_log, Multiplexer},
+    theme,
+};
+
+// what's left in place of anything in the config that could say who someone is
+const REDACTED: &str = "<redacted>";
+
+// everything worth knowing about where the game's running, the first thing in the archive
+#[derive(Debug, Serialize)]
+struct BugReport {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    terminal: TerminalInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct TerminalInfo {
+    term: Option<String>,
+    colorterm: Option<String>,
+    term_program: Option<String>,
+    multiplexer: Option<String>,
+    // columns and rows, if we're in a terminal at all
+    size: Option<(u16, u16)>,
+    plain: bool,
+}
+
+// write a tar archive to `path` with everything worth attaching to an issue: the report on the terminal, the config
+// from `config_path` if there is one, the last crash, the autosave and the last game that was finished. the files go in
+// as they are on disk so broken ones can be looked at too, with the home directory and the online section taken out
+pub fn write(path: &str, config_path: Option<&Path>) -> io::Result<()> {
+    let report = BugReport {
+        version: env!("CARGO_PKG_VERSION"),
+        os: env::consts::OS,
+        arch: env::consts::ARCH,
+        terminal: TerminalInfo {
+            term: env::var("TERM").ok(),
+            colorterm: env::var("COLORTERM").ok(),
+            term_program: env::var("TERM_PROGRAM").ok(),
+            multiplexer: Multiplexer::detect().map(|multiplexer| format!("{multiplexer:?}")),
+            size: terminal::size().ok(),
+            plain: theme::plain_terminal(),
+        },
+    };
+
+    let mut archive = Builder::new(File::create(path)?);
+    add(
+        &mut archive,
+        "report.json",
+        &serde_json::to_string_pretty(&report)?,
+    )?;
+
+    if let Some(config_path) = config_path {
+        add(
+            &mut archive,
+            "config.yaml",
+            &redact(&sanitize(&fs::read_to_string(config_path)?)),
+        )?;
+    }
+
+    let files = [
+        ("crash.log", crash_log()),
+        ("autosave.json", autosave::path()),
+        ("last.json", Replay::last_path()),
+    ];
+
+    for (name, file) in files {
+        if let Some(text) = read(file)? {
+            add(&mut archive, name, &sanitize(&text))?;
+        }
+    }
+
+    archive.into_inner()?;
+    Ok(())
+}
+
+fn add(archive: &mut Builder<File>, name: &str, text: &str) -> io::Result<()> {
+    let mut header = Header::new_gnu();
+    header.set_size(text.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+    );
+    archive.append_data(&mut header, name, text.as_bytes())
+}
+
+// a file from the data directory, or None if it isn't there. one that's been mangled still goes in as best it can
+fn read(path: Option<PathBuf>) -> io::Result<Option<String>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+// people's home directories tend to have their name in them, which doesn't need to end up in a public issue
+fn sanitize(text: &str) -> String {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+// the online section says which server someone uses and what name they go by there, so everything in it but whether
+// submitting's on is taken out. it's done on the text rather than the parsed config so one that doesn't parse, which
+// might be what's being reported, still has it taken out
+fn redact(config: &str) -> String {
+    let mut online = false;
+    let mut redacted = String::new();
+
+    for line in config.lines() {
+        let trimmed = line.trim_start();
+        let indented = trimmed.len() < line.len();
+
+        // any other top level key ends the online section, comments and blank lines don't
+        if !indented && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            online = line.starts_with("online:");
+        }
+
+        match trimmed.split_once(':') {
+            // written all on one line, like `online: { url: ... }`
+            Some(("online", value)) if online && !indented && !value.trim().is_empty() => {
+                redacted.push_str(&format!("online: {REDACTED}"));
+            }
+            Some((key, value))
+                if online && indented && key.trim() != "submit" && !value.trim().is_empty() =>
+            {
+                redacted.push_str(&format!(
+                    "{}{}: {REDACTED}",
+                    &line[..line.len() - trimmed.len()],
+                    key.trim_end()
+                ));
+            }
+            _ => redacted.push_str(line),
+        }
+
+        redacted.push('\n');
+    }
+
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_online_section_is_taken_out() {
+        let config = "theme: dark\nonline:\n  # where results go\n  url: https://scores.example.com\n  submit: true\n  name: someone\nwrap_cursor: true\n";
+        assert_eq!(
+            redact(config),
+            "theme: dark\nonline:\n  # where results go\n  url: <redacted>\n  submit: true\n  name: <redacted>\nwrap_cursor: true\n"
+        );
+
+        assert_eq!(
+            redact("online: { url: https://scores.example.com, name: someone }\n"),
+            "online: <redacted>\n"
+        );
+    }
+}
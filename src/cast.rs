@@ -0,0 +1,314 @@
+use std::{fmt::Write, time::Duration};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::{Color, Modifier},
+    text::Spans,
+    widgets::{Paragraph, StatefulWidget, Widget, Wrap},
+};
+use serde_json::json;
+
+use crate::{
+    board::{column_label, Board},
+    config::Config,
+    game::level_name,
+    replay::{ActionKind, Replay},
+    status::{Status, StatusPosition},
+    ui::{self, MinesweeperState, MinesweeperWidget},
+};
+
+// how often the clock on the status bar moves, the same as in the game
+const TICK: Duration = Duration::from_millis(100);
+
+// the narrowest a cast gets, so the status bar fits beside small boards
+const MIN_WIDTH: u16 = 80;
+
+// a replay as an asciinema v2 cast: a line of JSON about the terminal, then a line for every frame with when it was
+// drawn and what was written to the terminal to draw it. the frames are the ones the player saw, in the theme from
+// the config
+pub fn export(replay: &Replay, config: &Config) -> String {
+    let mut recorder = Recorder::new(replay, config);
+
+    let header = json!({
+        "version": 2,
+        "width": recorder.area.width,
+        "height": recorder.area.height,
+        "title": format!("Minesweeper - {}", recorder.difficulty),
+        "env": { "TERM": "xterm-256color" },
+    });
+    let mut cast = format!("{header}\n");
+
+    recorder.frame(Duration::ZERO, &mut cast);
+
+    for action in &replay.actions {
+        let time = Duration::from_millis(action.time);
+
+        // the clock keeps going between moves
+        while recorder.drawn + TICK < time {
+            recorder.frame(recorder.drawn + TICK, &mut cast);
+        }
+
+        recorder.selection = (action.x, action.y);
+
+        match action.kind {
+            ActionKind::Reveal => {
+                recorder.board.uncover(action.x, action.y);
+            }
+            ActionKind::Mark => recorder.board.toggle_flag(action.x, action.y),
+        }
+
+        recorder.frame(time, &mut cast);
+    }
+
+    // and the end screen, with the whole board shown
+    let board = &recorder.board;
+    let ending = match board
+        .positions()
+        .any(|(x, y)| board.is_mine(x, y) && board.is_uncovered(x, y))
+    {
+        true => "You lost!",
+        false
+            if board
+                .positions()
+                .all(|(x, y)| board.is_mine(x, y) || board.is_uncovered(x, y)) =>
+        {
+            "You won!"
+        }
+        false => "You gave up!",
+    };
+
+    recorder.show_everything = true;
+    recorder.footer = vec![Spans::from(ui::styled(ending, &config.theme.overlay))];
+    recorder.frame(replay.duration(), &mut cast);
+
+    cast
+}
+
+// plays the replay back into a screen that's only kept in memory
+struct Recorder<'a> {
+    config: &'a Config,
+    board: Board,
+    selection: (usize, usize),
+    show_everything: bool,
+    footer: Vec<Spans<'static>>,
+    difficulty: &'static str,
+    // the screen as of the last frame, the next one only writes what's changed
+    screen: Buffer,
+    area: Rect,
+    board_row: u16,
+    status_row: u16,
+    footer_row: u16,
+    // when the last frame was drawn
+    drawn: Duration,
+    // whether anything's been drawn yet
+    started: bool,
+}
+
+impl<'a> Recorder<'a> {
+    fn new(replay: &Replay, config: &'a Config) -> Self {
+        let board = Board::with_mines(replay.width, replay.height, &replay.mines);
+        let widget = MinesweeperWidget::new(&board, &config.theme);
+
+        // laid out like the game does it, without the safe area since there's no notch to keep clear of
+        let board_row = match config.status_bar {
+            StatusPosition::Top => 1,
+            StatusPosition::Bottom => 0,
+        };
+        let below_board = board_row + widget.rows();
+        let status_row = match config.status_bar {
+            StatusPosition::Top => 0,
+            StatusPosition::Bottom => below_board,
+        };
+        let area = Rect::new(0, 0, widget.columns().max(MIN_WIDTH), below_board + 2);
+
+        Self {
+            config,
+            selection: (replay.width / 2, replay.height / 2),
+            show_everything: false,
+            footer: Vec::new(),
+            difficulty: match replay.daily {
+                Some(_) => "Daily",
+                None => level_name(replay.level),
+            },
+            screen: Buffer::empty(area),
+            area,
+            board_row,
+            status_row,
+            footer_row: below_board + 1,
+            drawn: Duration::ZERO,
+            started: false,
+            board,
+        }
+    }
+
+    // draw the screen as it is at `time` and add what it took to the cast
+    fn frame(&mut self, time: Duration, cast: &mut String) {
+        let theme = &self.config.theme;
+        let mut next = Buffer::empty(self.area);
+
+        let status = Status {
+            difficulty: self.difficulty,
+            elapsed: time,
+            time_limit: None,
+            lives: None,
+            rating: None,
+            pace: None,
+            precise: false,
+            mines: self.board.num_mines(),
+            flags: self.board.num_flagged(),
+            opponent: None,
+            cursor: match theme.board.labels {
+                true => format!("{}{}", column_label(self.selection.0), self.selection.1 + 1),
+                false => format!("{},{}", self.selection.0 + 1, self.selection.1 + 1),
+            },
+        };
+
+        let widget =
+            MinesweeperWidget::new(&self.board, theme).show_everything(self.show_everything);
+        let mut state = MinesweeperState::new(self.selection);
+
+        StatefulWidget::render(
+            widget,
+            ui::below(self.area, self.board_row),
+            &mut next,
+            &mut state,
+        );
+        Paragraph::new(status.line(theme)).render(ui::below(self.area, self.status_row), &mut next);
+        Paragraph::new(self.footer.clone())
+            .wrap(Wrap { trim: false })
+            .render(ui::below(self.area, self.footer_row), &mut next);
+
+        // the first frame starts from a clear screen
+        let mut output = match self.started {
+            true => String::new(),
+            false => "\x1b[2J".to_string(),
+        };
+
+        let mut at = None;
+        let mut style = None;
+
+        for (x, y, cell) in self.screen.diff(&next) {
+            if at != Some((x, y)) {
+                let _ = write!(output, "\x1b[{};{}H", y + 1, x + 1);
+            }
+
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                output.push_str(&sgr(cell));
+                style = Some(cell_style);
+            }
+
+            output.push_str(&cell.symbol);
+            at = Some((x + 1, y));
+        }
+
+        output.push_str("\x1b[0m");
+
+        // the terminal cursor sits on the selected cell, like it does in the game
+        match state.cursor() {
+            Some((column, row)) => {
+                let _ = write!(output, "\x1b[{};{}H\x1b[?25h", row + 1, column + 1);
+            }
+            None => output.push_str("\x1b[?25l"),
+        }
+
+        let _ = writeln!(cast, "{}", json!([time.as_secs_f64(), "o", output]));
+
+        self.screen = next;
+        self.drawn = time;
+        self.started = true;
+    }
+}
+
+// the escape code that sets up a cell's colors and attributes, from scratch
+fn sgr(cell: &Cell) -> String {
+    let mut codes = vec!["0".to_string()];
+
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+
+    for (modifier, code) in modifiers {
+        if cell.modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+
+    codes.extend(color(cell.fg, 30));
+    codes.extend(color(cell.bg, 40));
+
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+// a color as it's given in an escape code, `base` is 30 for the foreground and 40 for the background
+fn color(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Indexed(value) => format!("{};5;{value}", base + 8),
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+    };
+
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::Action;
+
+    #[test]
+    fn every_move_gets_a_frame() {
+        // a mine in the corner, everything else opens up from the other one
+        let mut replay = Replay::new(1, 3, 3);
+        replay.mines = vec![8];
+        replay.actions.push(Action {
+            time: 250,
+            kind: ActionKind::Reveal,
+            x: 0,
+            y: 0,
+            uncovered: 8,
+        });
+
+        let cast = export(&replay, &Config::default());
+        let lines: Vec<serde_json::Value> = cast
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], MIN_WIDTH);
+
+        // the first frame, two clock ticks, the move and the end screen
+        let times: Vec<f64> = lines[1..]
+            .iter()
+            .map(|event| event[0].as_f64().unwrap())
+            .collect();
+        assert_eq!(times, [0.0, 0.1, 0.2, 0.25, 0.25]);
+        assert!(lines[5][2].as_str().unwrap().contains("You won!"));
+    }
+}
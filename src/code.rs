@@ -0,0 +1,237 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    board::{Board, MAX_CELLS},
+    generator::{self, Placement, Settings},
+    replay::{ActionKind, Replay},
+};
+
+// the version byte every code starts with, so the layout can change later without old codes turning into other boards
+const VERSION: u8 = 1;
+
+// URL-safe base64, so codes can go in links and chat messages without anything getting mangled
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// everything it takes to play the same board again: the seed its layout came from, the size of the board
+// and where the first click was, since that's what the layout got shifted under
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GameCode {
+    pub seed: u64,
+    pub settings: Settings,
+    pub start: (usize, usize),
+}
+
+impl GameCode {
+    // the code for a game that's been played, if its board was generated
+    pub fn from_replay(replay: &Replay) -> Option<Self> {
+        let start = replay
+            .actions
+            .iter()
+            .find(|action| action.kind == ActionKind::Reveal)?;
+
+        Some(Self {
+            seed: replay.seed?,
+            settings: Settings {
+                width: replay.width,
+                height: replay.height,
+                mines: replay.mines.len(),
+                placement: replay.placement,
+            },
+            start: (start.x, start.y),
+        })
+    }
+
+    // where the mines go, the same as they did in the game the code came from
+    pub fn mines(&self) -> Vec<usize> {
+        generator::layout(self.settings, self.seed, self.start)
+    }
+}
+
+impl fmt::Display for GameCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Settings {
+            width,
+            height,
+            mines,
+            placement,
+        } = self.settings;
+        let mut bytes = vec![VERSION];
+
+        bytes.extend(self.seed.to_le_bytes());
+        for value in [width, height, self.start.0, self.start.1] {
+            bytes.extend((value as u16).to_le_bytes());
+        }
+        bytes.extend((mines as u32).to_le_bytes());
+        // uniform boards leave the placement off, so their codes are the same as before there was a choice
+        if placement != Placement::Uniform {
+            bytes.push(placement as u8);
+        }
+
+        f.write_str(&encode(&bytes))
+    }
+}
+
+impl FromStr for GameCode {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let bytes = decode(code.trim()).ok_or_else(|| format!("{code} isn't a game code"))?;
+
+        if bytes.first() != Some(&VERSION) || !(21..=22).contains(&bytes.len()) {
+            return Err(format!(
+                "{code} isn't a game code, or it's from a different version of minesweeper"
+            ));
+        }
+
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+        let seed = u64::from_le_bytes(bytes[1..9].try_into().unwrap_or_default());
+        let (width, height, start) = (u16_at(9), u16_at(11), (u16_at(13), u16_at(15)));
+        let mines = u32::from_le_bytes(bytes[17..21].try_into().unwrap_or_default()) as usize;
+        let placement = match bytes.get(21) {
+            None => Placement::Uniform,
+            Some(1) => Placement::Clustered,
+            Some(2) => Placement::Border,
+            Some(_) => {
+                return Err(format!(
+                    "{code} isn't a game code, or it's from a different version of minesweeper"
+                ))
+            }
+        };
+
+        // a code can ask for up to 65535x65535, so anything that wouldn't fit in memory is turned away first
+        if width * height > MAX_CELLS {
+            return Err(format!(
+                "{code} asks for a {width}x{height} board, which is bigger than minesweeper can play"
+            ));
+        }
+
+        // the mines have to fit around the safe area the first click opens up
+        let room = (width * height).saturating_sub(
+            Board::new(width, height)
+                .neighbors(start.0, start.1)
+                .count()
+                + 1,
+        );
+        if width == 0 || height == 0 || start.0 >= width || start.1 >= height || mines > room {
+            return Err(format!(
+                "{code} isn't a game code, the board in it doesn't make sense"
+            ));
+        }
+
+        Ok(Self {
+            seed,
+            settings: Settings {
+                width,
+                height,
+                mines,
+                placement,
+            },
+            start,
+        })
+    }
+}
+
+// base64 without the padding on the end
+fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - i * 8)
+        });
+
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(group >> (18 - i * 6)) as usize & 0x3f] as char);
+        }
+    }
+
+    text
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    let values = text
+        .bytes()
+        .map(|char| {
+            ALPHABET
+                .iter()
+                .position(|&letter| letter == char)
+                .map(|value| value as u32)
+        })
+        .collect::<Option<Vec<u32>>>()?;
+
+    // a group of one character would only be 6 bits, not a whole byte
+    if values.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+
+    for chunk in values.chunks(4) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, &value)| group | value << (18 - i * 6));
+        bytes.extend((0..chunk.len() - 1).map(|i| (group >> (16 - i * 8)) as u8));
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_come_back_the_same() {
+        let code = GameCode {
+            seed: 0x0123_4567_89ab_cdef,
+            settings: Settings {
+                width: 16,
+                height: 16,
+                mines: 40,
+                placement: Placement::Uniform,
+            },
+            start: (3, 12),
+        };
+
+        let text = code.to_string();
+        assert_eq!(text.len(), 28);
+        assert_eq!(text.parse::<GameCode>().unwrap(), code);
+        assert_eq!(code.mines(), text.parse::<GameCode>().unwrap().mines());
+
+        let clustered = GameCode {
+            settings: Settings {
+                placement: Placement::Clustered,
+                ..code.settings
+            },
+            ..code
+        };
+        assert_eq!(
+            clustered.to_string().parse::<GameCode>().unwrap(),
+            clustered
+        );
+
+        for bytes in [&b""[..], b"a", b"ab", b"abc", b"abcd"] {
+            assert_eq!(decode(&encode(bytes)).unwrap(), bytes);
+        }
+
+        assert!("not a code!".parse::<GameCode>().is_err());
+        assert!(encode(&[2; 21]).parse::<GameCode>().is_err());
+    }
+
+    #[test]
+    fn huge_boards_are_refused() {
+        let code = |width: u16, height: u16| {
+            let mut bytes = vec![VERSION];
+            bytes.extend(0u64.to_le_bytes());
+            bytes.extend(width.to_le_bytes());
+            bytes.extend(height.to_le_bytes());
+            bytes.extend([0; 4]);
+            bytes.extend(10u32.to_le_bytes());
+            encode(&bytes)
+        };
+
+        assert!(code(1000, 1000).parse::<GameCode>().is_ok());
+        assert!(code(u16::MAX, u16::MAX).parse::<GameCode>().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::env;
+
+use crossterm::style::Color;
+use serde::{de::IntoDeserializer, Deserialize, Deserializer};
+
+// how many colors the terminal can show
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSupport {
+    // any 24-bit color
+    TrueColor,
+    // the 256 color xterm palette
+    Ansi256,
+    // just the 16 named colors, which the terminal's own palette decides the look of
+    Ansi16,
+}
+
+impl ColorSupport {
+    // going by what the terminal tells programs about itself. COLORTERM is the only way to find out about truecolor
+    // and plenty of terminals that have it don't set it, which is what the colors setting in the config is for
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    // the closest color to `color` that the terminal can show
+    pub fn downgrade(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(ansi256((r, g, b))),
+            (ColorSupport::Ansi16, Color::Rgb { r, g, b }) => ansi16((r, g, b)),
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) => match value {
+                0..=15 => NAMED[value as usize].0,
+                _ => ansi16(rgb(value)),
+            },
+            (_, color) => color,
+        }
+    }
+}
+
+// the 16 named colors in palette order, with what xterm shows them as
+const NAMED: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// the levels each channel of the 6x6x6 color cube in the 256 color palette goes through
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+// what a color in the 256 color palette looks like
+fn rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => NAMED[value as usize].1,
+        16..=231 => {
+            let value = value - 16;
+            (
+                CUBE[value as usize / 36],
+                CUBE[value as usize / 6 % 6],
+                CUBE[value as usize % 6],
+            )
+        }
+        _ => {
+            let grey = 8 + (value - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+// the closest of the color cube and the grey ramp, the first 16 are left out since they depend on the terminal's palette
+fn ansi256(color: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&value| distance(color, rgb(value)))
+        .unwrap_or(16)
+}
+
+fn ansi16(color: (u8, u8, u8)) -> Color {
+    NAMED
+        .iter()
+        .min_by_key(|(_, named)| distance(color, *named))
+        .map(|(named, _)| *named)
+        .unwrap_or(Color::White)
+}
+
+// a color as crossterm names it (red, ansi_(n), rgb_(r,g,b)) or as a hex code like '#ff8800'
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+
+    let hex = match name.strip_prefix('#') {
+        Some(hex) => hex,
+        None => return Color::deserialize(name.as_str().into_deserializer()).map(Some),
+    };
+
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Some(Color::Rgb { r, g, b })),
+        _ => Err(serde::de::Error::custom(format!(
+            "{name} isn't a color, hex colors are written like #ff8800"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downgrades_to_the_closest_color() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 135,
+            b: 0,
+        };
+        assert_eq!(ColorSupport::TrueColor.downgrade(orange), orange);
+        assert_eq!(
+            ColorSupport::Ansi256.downgrade(orange),
+            Color::AnsiValue(208)
+        );
+        assert_eq!(ColorSupport::Ansi16.downgrade(orange), Color::DarkYellow);
+
+        let grey = Color::Rgb {
+            r: 128,
+            g: 128,
+            b: 130,
+        };
+        assert_eq!(ColorSupport::Ansi256.downgrade(grey), Color::AnsiValue(244));
+        assert_eq!(
+            ColorSupport::Ansi16.downgrade(Color::AnsiValue(9)),
+            Color::Red
+        );
+        assert_eq!(
+            ColorSupport::Ansi16.downgrade(Color::AnsiValue(21)),
+            Color::DarkBlue
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use std::{fmt, fs, io, path::Path};
+
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::{
+    color::ColorSupport,
+    status::StatusPosition,
+    terminal::Multiplexer,
+    theme::{Preset, Theme},
+};
+
+// user settings, loaded from a YAML file passed with -c
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // which keys move the cursor around the board
+    pub movement: Movement,
+    // moving off one edge of the board comes back in on the opposite edge
+    pub wrap_cursor: bool,
+    // whether the status bar goes above or below the board
+    pub status_bar: StatusPosition,
+    // colors and characters used to draw the game
+    pub theme: Theme,
+    // how many colors the terminal can show, worked out from the environment if unset
+    pub colors: Option<ColorSupport>,
+    // only act on distinct presses of the reveal key, not the repeats sent while it's held down
+    pub ignore_key_repeat: bool,
+    // reveal presses closer together than this many milliseconds count as the key being held
+    pub key_repeat_delay: u64,
+    // print a summary of every game played to stdout after leaving the game
+    pub exit_summary: bool,
+    // show how the session went before leaving, once more than one game's been played
+    pub session_summary: bool,
+    // rows to keep clear for prompts and status lines drawn over the terminal, worked out from the environment if unset
+    pub safe_area: Option<SafeArea>,
+    // show the level in the terminal window's title
+    pub set_title: bool,
+    // spread big openings out from the cursor over a few frames instead of showing them all at once
+    pub animate_reveals: bool,
+    // flash the mine that went off and plant the flags and throw confetti when you win
+    pub animate_endings: bool,
+    // stop the clock while the terminal's in the background
+    pub pause_on_focus_loss: bool,
+    // cover the board up while the clock's stopped, so it can't be studied from another window
+    pub hide_board_when_paused: bool,
+    // ask before quitting a game that's been started, so a stray q doesn't throw it away
+    pub confirm_quit: bool,
+    // flag every covered cell once all that's left covered are mines
+    pub auto_flag: bool,
+    // only allow as many flags as there are mines
+    pub strict_flags: bool,
+    // no flagging at all, for no-flag (NF) runs that keep their own stats and best times
+    pub no_flags: bool,
+    // how much the game plays for you, assisted games keep their own best times
+    pub assist: Assist,
+    // race the best run from the last time a board was played, when it's played again from its game code
+    pub ghost: bool,
+    // the online leaderboard for the daily challenge
+    pub online: Online,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            movement: Movement::default(),
+            wrap_cursor: false,
+            status_bar: StatusPosition::default(),
+            theme: Theme::default(),
+            colors: None,
+            ignore_key_repeat: false,
+            key_repeat_delay: 500,
+            exit_summary: false,
+            session_summary: true,
+            safe_area: None,
+            set_title: true,
+            animate_reveals: true,
+            animate_endings: true,
+            pause_on_focus_loss: true,
+            hide_board_when_paused: false,
+            confirm_quit: true,
+            auto_flag: false,
+            strict_flags: false,
+            no_flags: false,
+            assist: Assist::default(),
+            ghost: false,
+            online: Online::default(),
+        }
+    }
+}
+
+// the most space there can be between cells, any more and even a beginner board won't fit a terminal
+const MAX_GAP: usize = 8;
+
+// every setting with its default value and what it does, written out by `minesweeper init-config`
+pub const TEMPLATE: &str = include_str!("default_config.yaml");
+
+impl Config {
+    // `preset` picks the theme to start from instead of the one named in the file, like --theme does
+    pub fn load(path: &Path, preset: Option<Preset>) -> Result<Self, ConfigError> {
+        Self::parse(&fs::read_to_string(path).map_err(ConfigError::Io)?, preset)
+    }
+
+    // the default settings on top of a built-in theme, for when there's no config file to read
+    pub fn with_preset(preset: Option<Preset>) -> Self {
+        Self::parse("{}", preset).expect("the built-in themes all parse")
+    }
+
+    pub fn parse(text: &str, preset: Option<Preset>) -> Result<Self, ConfigError> {
+        let mut config: Value = serde_yaml::from_str(text).map_err(ConfigError::Parse)?;
+
+        // the theme starts out as its preset and everything else under theme is changed on top of that
+        if let Some(config) = config.as_mapping_mut() {
+            let key = Value::from("theme");
+            let mut theme = config
+                .remove(&key)
+                .unwrap_or_else(|| Value::Mapping(Default::default()));
+
+            let named = match theme
+                .as_mapping_mut()
+                .and_then(|theme| theme.remove(&Value::from("preset")))
+            {
+                Some(preset) => serde_yaml::from_value(preset).map_err(|error| {
+                    ConfigError::invalid(text, "theme.preset", error.to_string())
+                })?,
+                None => Preset::default(),
+            };
+
+            let mut base: Value =
+                serde_yaml::from_str(preset.unwrap_or(named).yaml()).map_err(ConfigError::Parse)?;
+            merge(&mut base, theme);
+            config.insert(key, base);
+        }
+
+        // going through serde_path_to_error means we can say which setting was wrong, not just what was wrong with it
+        let config: Self = serde_path_to_error::deserialize(config).map_err(|error| {
+            ConfigError::invalid(text, &error.path().to_string(), error.inner().to_string())
+        })?;
+
+        config.validate(text)?;
+        Ok(config)
+    }
+
+    // catch settings that parse fine but can't be drawn
+    fn validate(&self, text: &str) -> Result<(), ConfigError> {
+        let board = &self.theme.board;
+
+        if board.gap_x > MAX_GAP {
+            return Err(ConfigError::invalid(
+                text,
+                "theme.board.gap_x",
+                format!("{} is too wide, it can be up to {MAX_GAP}", board.gap_x),
+            ));
+        }
+
+        if board.gap_y > MAX_GAP {
+            return Err(ConfigError::invalid(
+                text,
+                "theme.board.gap_y",
+                format!("{} is too tall, it can be up to {MAX_GAP}", board.gap_y),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn color_support(&self) -> ColorSupport {
+        self.colors.unwrap_or_else(ColorSupport::detect)
+    }
+
+    // the rows we can't draw in. tmux and zellij like to put status lines, popups and floating panes along the bottom
+    // of a pane, so we stay out of the last row there unless told otherwise
+    pub fn safe_area(&self) -> SafeArea {
+        self.safe_area
+            .unwrap_or_else(|| match Multiplexer::detect() {
+                Some(_) => SafeArea { top: 0, bottom: 1 },
+                None => SafeArea::default(),
+            })
+    }
+}
+
+// rows at the top and bottom of the terminal that nothing gets drawn in
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SafeArea {
+    pub top: u16,
+    pub bottom: u16,
+}
+
+// where daily challenge results go online. nothing's sent or fetched without a url
+#[derive(Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Online {
+    pub url: Option<String>,
+    // send daily wins that check out against their replay
+    pub submit: bool,
+    // the name results go up under, the username if it's not set
+    pub name: Option<String>,
+}
+
+// lay `overrides` on top of `base`, mappings are merged key by key and anything else is replaced
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Mapping(base), Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+// cells the game opens by itself after every move
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Assist {
+    #[default]
+    Off,
+    // open around any number that already has as many flags around it as it shows
+    Chord,
+    // open every cell the solver can prove is safe
+    Solver,
+}
+
+impl Assist {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assist::Off => "off",
+            Assist::Chord => "chord",
+            Assist::Solver => "solver",
+        }
+    }
+}
+
+// the arrow keys always work, the other schemes are layered on top of them
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Movement {
+    // wasd and hjkl are both enabled
+    #[default]
+    Auto,
+    // only the arrow keys
+    Arrows,
+    // arrow keys and wasd
+    Wasd,
+    // arrow keys and hjkl
+    Vim,
+}
+
+impl Movement {
+    // the keys that move the cursor, the way the help screen lists them
+    pub fn keys(&self) -> &'static str {
+        match self {
+            Movement::Auto => "arrow keys/wasd/hjkl",
+            Movement::Arrows => "arrow keys",
+            Movement::Wasd => "arrow keys/wasd",
+            Movement::Vim => "arrow keys/hjkl",
+        }
+    }
+
+    // returns the (x, y) step a key moves the cursor by, y goes up the board
+    pub fn direction(&self, code: KeyCode) -> Option<(isize, isize)> {
+        let wasd = matches!(self, Movement::Auto | Movement::Wasd);
+        let vim = matches!(self, Movement::Auto | Movement::Vim);
+
+        match code {
+            KeyCode::Up => Some((0, 1)),
+            KeyCode::Down => Some((0, -1)),
+            KeyCode::Left => Some((-1, 0)),
+            KeyCode::Right => Some((1, 0)),
+            KeyCode::Char(char) => match char {
+                'w' if wasd => Some((0, 1)),
+                's' if wasd => Some((0, -1)),
+                'a' if wasd => Some((-1, 0)),
+                'd' if wasd => Some((1, 0)),
+                'k' if vim => Some((0, 1)),
+                'j' if vim => Some((0, -1)),
+                'h' if vim => Some((-1, 0)),
+                'l' if vim => Some((1, 0)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    // the file isn't YAML at all
+    Parse(serde_yaml::Error),
+    // a setting has a value it can't have
+    Invalid {
+        field: String,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl ConfigError {
+    fn invalid(text: &str, field: &str, message: String) -> Self {
+        ConfigError::Invalid {
+            field: field.to_string(),
+            line: line_of(text, field),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "couldn't read config file: {error}"),
+            ConfigError::Parse(error) => write!(f, "couldn't parse config file: {error}"),
+            ConfigError::Invalid {
+                field,
+                line: Some(line),
+                message,
+            } => write!(f, "{field} on line {line}: {message}"),
+            ConfigError::Invalid {
+                field,
+                line: None,
+                message,
+            } => write!(f, "{field}: {message}"),
+        }
+    }
+}
+
+// the line a setting like theme.board.mine is on, found by looking for each key in turn after the one before it.
+// this doesn't understand YAML, but it only has to be good enough to point someone at the right line
+fn line_of(text: &str, field: &str) -> Option<usize> {
+    let mut line = 0;
+
+    // list indices like numbers[3] don't have a key of their own, so they're dropped
+    for key in field
+        .split('.')
+        .map(|key| key.split('[').next().unwrap_or(key))
+    {
+        let key = format!("{key}:");
+        let has_key = |text: &str| {
+            !text.trim_start().starts_with('#')
+                && text
+                    .split(|c: char| c.is_whitespace() || "{,-".contains(c))
+                    .any(|token| token == key)
+        };
+
+        line += text.lines().skip(line).position(has_key)?;
+    }
+
+    Some(line + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Config::parse(text, None).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn errors_name_the_field_and_line() {
+        let text = "movement: vim\ntheme:\n  board:\n    gap_x: 1\n    mine: ab\n";
+        assert!(error(text).starts_with("theme.board.mine on line 5: "));
+
+        let text =
+            "theme:\n  board:\n    numbers:\n      - { fg: red }\n      - { fg: not_a_color }\n";
+        assert!(error(text).starts_with("theme.board.numbers[1].fg on line 4: "));
+
+        assert_eq!(
+            error("theme: { board: { gap_x: 40 } }"),
+            "theme.board.gap_x on line 1: 40 is too wide, it can be up to 8"
+        );
+    }
+
+    // the template is meant to spell out the defaults, so it shouldn't change anything
+    #[test]
+    fn template_matches_defaults() {
+        let template = Config::parse(TEMPLATE, None).unwrap();
+        assert_eq!(format!("{template:?}"), format!("{:?}", Config::default()));
+    }
+
+    // presets are only parsed when they're picked, so make sure they all can be
+    #[test]
+    fn presets_parse() {
+        for preset in [
+            Preset::Default,
+            Preset::Classic,
+            Preset::Dark,
+            Preset::Light,
+            Preset::RetroDos,
+            Preset::Colorblind,
+            Preset::HighContrast,
+        ] {
+            if let Err(error) = Config::parse("{}", Some(preset)) {
+                panic!("{preset:?}: {error}");
+            }
+        }
+
+        assert_eq!("retro-dos".parse::<Preset>().unwrap(), Preset::RetroDos);
+        assert_eq!(
+            "high_contrast".parse::<Preset>().unwrap(),
+            Preset::HighContrast
+        );
+    }
+
+    // settings in the file go on top of --theme, the same as they go on top of the file's own preset
+    #[test]
+    fn theme_flag_replaces_the_files_preset() {
+        let config = Config::parse(
+            "theme: { preset: colorblind, board: { mine: 'M' } }",
+            Some(Preset::Classic),
+        )
+        .unwrap();
+        assert_eq!(config.theme.board.mine, 'M');
+        assert_eq!(config.theme.board.marked, 'F');
+        assert_eq!(config.theme.board.covered, ' ');
+    }
+}
@@ -0,0 +1,596 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{Event, KeyCode},
+    Result,
+};
+use ratatui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Paragraph, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::cell_name,
+    code::GameCode,
+    config::Config,
+    event::GameEvent,
+    input::{self, Events},
+    net,
+    player::{Action, Round},
+    terminal::set_title,
+    ui::{self, MinesweeperState, MinesweeperWidget, Screen},
+};
+
+// the most players one board takes, the host included
+pub const MAX_PLAYERS: usize = 4;
+
+// everyone's cursor, the host's first
+const COLORS: [Color; MAX_PLAYERS] = [
+    Color::LightCyan,
+    Color::LightMagenta,
+    Color::LightYellow,
+    Color::LightGreen,
+];
+
+// how long to wait for a key before catching up with everyone else
+const TICK: Duration = Duration::from_millis(50);
+
+// a move on the shared board. a flag says which way it goes, so two players flagging the same cell at once leave it flagged
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[serde(tag = "move", rename_all = "lowercase")]
+enum Move {
+    Reveal { x: usize, y: usize },
+    Flag { x: usize, y: usize, on: bool },
+    Chord { x: usize, y: usize },
+}
+
+// what goes over the connections, one JSON object a line. guests send the host their moves and cursor, and the host
+// sends everyone every move in the order it made them, so all the boards go through the same moves
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+    // the host to each guest once everyone's joined: which player they are and the board's game code
+    Welcome {
+        player: usize,
+        players: usize,
+        code: String,
+    },
+    // a guest asking for a move
+    Move {
+        action: Move,
+    },
+    // a move the host's made
+    Made {
+        player: usize,
+        action: Move,
+    },
+    Cursor {
+        player: usize,
+        x: usize,
+        y: usize,
+    },
+    Left {
+        player: usize,
+    },
+}
+
+// everyone playing and how to reach them, set up before the game starts. the host is player 0 and has a connection to
+// each guest, a guest only has one to the host
+#[derive(Debug)]
+pub struct Connection {
+    me: usize,
+    code: GameCode,
+    // by player, None for ourselves, whoever we're not connected to and anyone who's left
+    peers: Vec<Option<TcpStream>>,
+    // every message that comes in with who it came from, and None when they've gone
+    incoming: Receiver<(usize, Option<Message>)>,
+}
+
+// wait until `players` - 1 guests have joined on `port`, then send them all the board
+pub fn host(port: u16, players: usize, code: GameCode) -> io::Result<Connection> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let (sender, incoming) = mpsc::channel();
+    let mut peers = vec![None];
+
+    while peers.len() < players.clamp(2, MAX_PLAYERS) {
+        let (stream, _) = listener.accept()?;
+        listen(peers.len(), &stream, sender.clone())?;
+        peers.push(Some(stream));
+    }
+
+    let mut connection = Connection {
+        me: 0,
+        code,
+        peers,
+        incoming,
+    };
+    let players = connection.peers.len();
+
+    for player in 1..players {
+        connection.send(
+            player,
+            &Message::Welcome {
+                player,
+                players,
+                code: code.to_string(),
+            },
+        );
+    }
+
+    Ok(connection)
+}
+
+// join someone hosting a co-op game and wait for everyone else to turn up
+pub fn join(address: &str) -> io::Result<Connection> {
+    let stream = net::connect(address)?;
+    let (sender, incoming) = mpsc::channel();
+    listen(0, &stream, sender)?;
+
+    let (me, players, code) = match incoming.recv() {
+        Ok((
+            _,
+            Some(Message::Welcome {
+                player,
+                players,
+                code,
+            }),
+        )) if player < players && players <= MAX_PLAYERS => (
+            player,
+            players,
+            code.parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+        ),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the host didn't send a co-op board",
+            ))
+        }
+    };
+
+    let mut peers: Vec<Option<TcpStream>> = (0..players).map(|_| None).collect();
+    peers[0] = Some(stream);
+
+    Ok(Connection {
+        me,
+        code,
+        peers,
+        incoming,
+    })
+}
+
+// pass on every line `player` sends on its own thread, so the game never waits on the network
+fn listen(
+    player: usize,
+    stream: &TcpStream,
+    sender: Sender<(usize, Option<Message>)>,
+) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+
+    thread::spawn(move || {
+        for line in net::lines(reader, net::MAX_LINE) {
+            let message = match serde_json::from_str(&line).ok() {
+                Some(message) => message,
+                None => break,
+            };
+
+            if sender.send((player, Some(message))).is_err() {
+                return;
+            }
+        }
+
+        let _ = sender.send((player, None));
+    });
+
+    Ok(())
+}
+
+impl Connection {
+    // a connection that's gone is dropped, its reading thread says they've left
+    fn send(&mut self, player: usize, message: &Message) {
+        let mut line = serde_json::to_string(message).unwrap_or_default();
+        line.push('\n');
+
+        if let Some(stream) = &mut self.peers[player] {
+            if stream.write_all(line.as_bytes()).is_err() {
+                self.peers[player] = None;
+            }
+        }
+    }
+
+    // send to everyone we're connected to but `except`
+    fn broadcast(&mut self, message: &Message, except: Option<usize>) {
+        for player in 0..self.peers.len() {
+            if Some(player) != except {
+                self.send(player, message);
+            }
+        }
+    }
+
+    fn is_host(&self) -> bool {
+        self.me == 0
+    }
+}
+
+// the reading threads have their own handles on the connections, so they have to be shut down for anyone to see us go
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for stream in self.peers.iter().flatten() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// everyone playing one board together, each with their own cursor. the host decides the order moves happen in:
+// they're made as they get to it, and one that doesn't make sense any more by then, like revealing a cell someone's
+// just flagged, is dropped. hitting a mine loses it for everyone, and clearing the board wins it for everyone
+#[derive(Debug)]
+pub struct Coop {
+    screen: Screen,
+    config: Config,
+    events: Events,
+    connection: Connection,
+    round: Round,
+    // everyone's cursor, None once they've left
+    cursors: Vec<Option<(usize, usize)>>,
+    started: Option<Instant>,
+    // how long the game took and who made the move that ended it, once it's over
+    ended: Option<(Duration, usize)>,
+    // what just happened, under the board
+    message: String,
+    // a number typed before a direction, to move that many cells like in a game
+    count: Option<usize>,
+}
+
+impl Coop {
+    pub fn new(connection: Connection, config: Config, events: Events) -> Result<Self> {
+        let mut screen = Screen::new()?;
+        let code = connection.code;
+
+        if config.set_title {
+            set_title(screen.out(), "Minesweeper - Co-op")?;
+        }
+
+        Ok(Self {
+            screen,
+            config,
+            events,
+            cursors: vec![Some(code.start); connection.peers.len()],
+            message: "everyone's here. space reveals, m flags and q quits".to_string(),
+            connection,
+            round: Round::new(code.settings, code.seed),
+            started: None,
+            ended: None,
+            count: None,
+        })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.draw()?;
+
+        loop {
+            // catch up with everyone else between keys, and keep the clock going
+            if !self.events.poll(TICK)? {
+                let mut changed = self.started.is_some() && self.ended.is_none();
+
+                while let Ok((player, message)) = self.connection.incoming.try_recv() {
+                    self.receive(player, message);
+                    changed = true;
+                }
+
+                if changed {
+                    self.draw()?;
+                }
+
+                continue;
+            }
+
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                Event::Resize(..) => {
+                    self.draw()?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if input::is_interrupt(&key) || key.code == KeyCode::Char('q') {
+                return Ok(());
+            }
+
+            let (x, y) = self.selection();
+            let board = self.round.board();
+            let size = (board.width(), board.height());
+
+            // a count only applies to the move straight after it
+            let count = self.count.take();
+            if let KeyCode::Char(digit @ '0'..='9') = key.code {
+                let digit = digit as usize - '0' as usize;
+                self.count = Some((count.unwrap_or(0) * 10 + digit).min(size.0.max(size.1)));
+                continue;
+            }
+
+            // the cursor moves the same way it does in a game
+            if let Some((x, y)) = input::moved(&key, (x, y), size, &self.config, count) {
+                let me = self.connection.me;
+
+                self.cursors[me] = Some((x, y));
+                self.connection
+                    .broadcast(&Message::Cursor { player: me, x, y }, None);
+                self.draw()?;
+                continue;
+            }
+
+            if self.ended.is_some() {
+                continue;
+            }
+
+            let board = self.round.board();
+            let action = match key.code {
+                KeyCode::Char(' ') | KeyCode::Enter if board.is_uncovered(x, y) => {
+                    Move::Chord { x, y }
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => Move::Reveal { x, y },
+                KeyCode::Char('m') => Move::Flag {
+                    x,
+                    y,
+                    on: !board.is_flagged(x, y),
+                },
+                _ => continue,
+            };
+
+            // the host makes its own moves straight away, a guest's go through the host like everyone else's
+            match self.connection.is_host() {
+                true => self.make(0, action),
+                false => self.connection.send(0, &Message::Move { action }),
+            }
+
+            self.draw()?;
+        }
+    }
+
+    fn selection(&self) -> (usize, usize) {
+        self.cursors[self.connection.me].unwrap_or(self.connection.code.start)
+    }
+
+    fn receive(&mut self, player: usize, message: Option<Message>) {
+        let host = self.connection.is_host();
+
+        match message {
+            Some(Message::Move { action }) if host => self.make(player, action),
+            // a cursor that's off the board is dropped before anything tries to draw it
+            Some(Message::Cursor { x, y, .. }) if !self.is_on_board(x, y) => {}
+            // the host knows who a cursor's from by which connection it came in on
+            Some(Message::Cursor { x, y, .. }) if host => {
+                self.cursors[player] = Some((x, y));
+                self.connection
+                    .broadcast(&Message::Cursor { player, x, y }, Some(player));
+            }
+            Some(Message::Made { player, action }) if !host => {
+                self.apply(player, action);
+            }
+            Some(Message::Cursor { player, x, y }) if !host && player < self.cursors.len() => {
+                self.cursors[player] = Some((x, y))
+            }
+            Some(Message::Left { player }) if !host && player < self.cursors.len() => {
+                self.leave(player)
+            }
+            None => {
+                self.leave(player);
+
+                if host {
+                    self.connection.broadcast(&Message::Left { player }, None);
+                } else if self.ended.is_none() {
+                    self.message = "the host's left, so the game can't go on. q quits".to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_on_board(&self, x: usize, y: usize) -> bool {
+        let board = self.round.board();
+        x < board.width() && y < board.height()
+    }
+
+    fn leave(&mut self, player: usize) {
+        self.cursors[player] = None;
+        self.connection.peers[player] = None;
+        self.message = format!("player {} left", player + 1);
+    }
+
+    // the host making a move, if it still makes sense, and telling everyone about it
+    fn make(&mut self, player: usize, action: Move) {
+        let board = self.round.board();
+        let (Move::Reveal { x, y } | Move::Flag { x, y, .. } | Move::Chord { x, y }) = action;
+
+        if x >= board.width() || y >= board.height() {
+            return;
+        }
+
+        let still = match action {
+            Move::Reveal { .. } => !board.is_uncovered(x, y) && !board.is_flagged(x, y),
+            Move::Flag { on, .. } => !board.is_uncovered(x, y) && board.is_flagged(x, y) != on,
+            Move::Chord { .. } => board.is_uncovered(x, y),
+        };
+
+        if still && self.apply(player, action) {
+            self.connection
+                .broadcast(&Message::Made { player, action }, None);
+        }
+    }
+
+    // a move everyone makes in the same order, returns whether it could be made
+    fn apply(&mut self, player: usize, action: Move) -> bool {
+        let action = match action {
+            Move::Reveal { x, y } => Action::Reveal(x, y),
+            Move::Flag { x, y, .. } => Action::Flag(x, y),
+            Move::Chord { x, y } => Action::Chord(x, y),
+        };
+
+        let events = match self.round.apply(action) {
+            Ok(events) => events,
+            Err(_) => return false,
+        };
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        for event in events {
+            let message = match event {
+                GameEvent::Won => format!(
+                    "You all cleared it in {:.1}s! q quits",
+                    started.elapsed().as_secs_f64()
+                ),
+                GameEvent::Exploded { cell } => format!(
+                    "{} hit a mine at {}, you all lost! q quits",
+                    self.name(player),
+                    cell_name(cell.0, cell.1)
+                ),
+                _ => continue,
+            };
+
+            self.ended = Some((started.elapsed(), player));
+            self.message = message;
+        }
+
+        true
+    }
+
+    fn name(&self, player: usize) -> String {
+        match player == self.connection.me {
+            true => "you".to_string(),
+            false => format!("player {}", player + 1),
+        }
+    }
+
+    // the board with everyone else's cursor in their color, who's who and what's just happened under it
+    fn draw(&mut self) -> Result<()> {
+        let theme = &self.config.theme;
+        let board = self.round.board();
+        let over = self.ended.is_some();
+        let exploded: Vec<_> = board
+            .positions()
+            .filter(|&(x, y)| board.is_mine(x, y) && board.is_uncovered(x, y))
+            .collect();
+        let widget = MinesweeperWidget::new(board, theme)
+            .show_everything(over)
+            .exploded(&exploded);
+
+        let mut others = HashMap::new();
+        for (player, cursor) in self.cursors.iter().enumerate() {
+            if let Some((x, y)) = cursor.filter(|_| player != self.connection.me) {
+                let mut glyph = ui::span(theme.board.glyph(widget.tile(x, y)));
+                glyph.style = glyph
+                    .style
+                    .patch(Style::default().bg(COLORS[player]).fg(Color::Black));
+                others.insert((x, y), glyph);
+            }
+        }
+
+        let elapsed = match (self.ended, self.started) {
+            (Some((took, _)), _) => took,
+            (None, Some(started)) => started.elapsed(),
+            (None, None) => Duration::ZERO,
+        };
+        let mut status = vec![ui::styled(
+            format!(
+                "Co-op | {:.1}s | {} mines left |",
+                elapsed.as_secs_f64(),
+                self.connection.code.settings.mines as isize - board.num_flagged() as isize
+            ),
+            &theme.status_bar,
+        )];
+        for (player, cursor) in self.cursors.iter().enumerate() {
+            if cursor.is_some() {
+                status.push(Span::raw(" "));
+                status.push(Span::styled(
+                    self.name(player),
+                    Style::default().fg(COLORS[player]),
+                ));
+            }
+        }
+
+        let message = Spans::from(ui::styled(self.message.clone(), &theme.overlay));
+        let widget = widget.drawn_over(&others);
+        let rows = widget.rows();
+        let top = self.config.safe_area().top;
+        let mut state = MinesweeperState::new(self.selection());
+
+        self.screen.draw(|frame| {
+            let area = frame.size();
+
+            frame.render_stateful_widget(widget, ui::below(area, top), &mut state);
+            frame.render_widget(
+                Paragraph::new(vec![Spans::from(status), message]).wrap(Wrap { trim: false }),
+                ui::below(area, top + rows),
+            );
+
+            if let Some((column, row)) = state
+                .cursor()
+                .filter(|&(column, row)| !over && column < area.right() && row < area.bottom())
+            {
+                frame.set_cursor(column, row);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_go_through_the_host() {
+        let port = TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let code: GameCode = "AaVkStfEugSsCQAJAAQABAAKAAAA".parse().unwrap();
+
+        let hosting = thread::spawn(move || host(port, 2, code).unwrap());
+        let mut guest = (0..50)
+            .find_map(|_| {
+                thread::sleep(Duration::from_millis(20));
+                join(&format!("127.0.0.1:{port}")).ok()
+            })
+            .unwrap();
+        let mut host = hosting.join().unwrap();
+        assert_eq!((guest.me, guest.code), (1, code));
+
+        let action = Move::Flag {
+            x: 1,
+            y: 2,
+            on: true,
+        };
+        guest.send(0, &Message::Move { action });
+        assert_eq!(
+            host.incoming.recv().unwrap(),
+            (1, Some(Message::Move { action }))
+        );
+
+        host.broadcast(&Message::Made { player: 1, action }, None);
+        assert_eq!(
+            guest.incoming.recv().unwrap(),
+            (0, Some(Message::Made { player: 1, action }))
+        );
+
+        // a line that never ends is cut off instead of being held on to
+        let stream = guest.peers[0].as_mut().unwrap();
+        stream
+            .write_all(&vec![b'x'; net::MAX_LINE as usize + 1])
+            .unwrap();
+        assert_eq!(host.incoming.recv().unwrap(), (1, None));
+
+        drop(host);
+        assert_eq!(guest.incoming.recv().unwrap(), (0, None));
+    }
+}
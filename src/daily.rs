@@ -0,0 +1,56 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{code::GameCode, game::Game};
+
+// everyone plays the daily challenge on intermediate
+pub const LEVEL: u8 = 2;
+
+// the day it is in UTC, as days since 1970-01-01, so the challenge changes at the same moment for everyone
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+// the board for `day`. the seed only depends on the day and it opens up in the middle, so it's the same board for everyone
+pub fn code(day: u64) -> GameCode {
+    let settings = Game::level_settings(LEVEL);
+
+    GameCode {
+        // spread the days out so one day's seed isn't just the last one plus one
+        seed: day.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ 0x6d69_6e65_7377_6565,
+        settings,
+        start: settings.start(),
+    }
+}
+
+// the day as a date like 2026-10-17
+pub fn date(day: u64) -> String {
+    // Howard Hinnant's civil_from_days, counting in 400 year eras from 0000-03-01 so leap days come at the end of each year
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_become_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_743), "2026-10-17");
+        assert_ne!(code(20_743).seed, code(20_744).seed);
+    }
+}
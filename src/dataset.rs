@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+use crate::{
+    board::Board,
+    code::GameCode,
+    generator::{self, Settings},
+};
+
+// a generated board along with everything needed to make it again. written as JSON it's also a board file,
+// so it can be played with --board
+#[derive(Serialize)]
+pub struct Generated {
+    pub seed: u64,
+    pub code: String,
+    // the first click the layout was generated around, the safe area is around it
+    pub start: (usize, usize),
+    pub three_bv: usize,
+    #[serde(flatten)]
+    pub board: Board,
+}
+
+impl Generated {
+    // the board for `seed`, opened up from the middle like every other generated layout
+    pub fn new(settings: Settings, seed: u64) -> Self {
+        let start = settings.start();
+        let board = Board::with_mines(
+            settings.width,
+            settings.height,
+            &generator::layout(settings, seed, start),
+        );
+
+        Self {
+            seed,
+            code: GameCode {
+                seed,
+                settings,
+                start,
+            }
+            .to_string(),
+            start,
+            three_bv: board.three_bv().1,
+            board,
+        }
+    }
+
+    // the board as a text grid with a line about it on top, for reading in a terminal
+    pub fn text(&self) -> String {
+        format!(
+            "seed {}, code {}, 3BV {}\n{}",
+            self.seed,
+            self.code,
+            self.three_bv,
+            self.board.to_text()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn generated_boards_load_as_board_files() {
+        let settings = Game::level_settings(2);
+        let generated = Generated::new(settings, 42);
+        let board: Board =
+            serde_json::from_str(&serde_json::to_string(&generated).unwrap()).unwrap();
+
+        assert_eq!(board.to_text(), generated.board.to_text());
+        assert_eq!(
+            generated.code.parse::<GameCode>().unwrap().mines(),
+            generator::layout(settings, 42, (8, 8))
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    io::{stdout, Stdout, Write},
+    mem,
+    path::PathBuf,
+};
+
+use crossterm::{
+    cursor::{MoveTo, Show},
+    event::{Event, KeyCode},
+    style::Print,
+    terminal::{Clear, ClearType},
+    ExecutableCommand, QueueableCommand, Result,
+};
+
+use crate::{
+    board::Board,
+    config::Config,
+    input::{self, Events},
+    practice::Practice,
+    puzzle::{Pack, Puzzle},
+    terminal::set_title,
+    theme::Tile,
+};
+
+// the biggest board the editor makes, so every row fits on a terminal
+pub const MAX_SIZE: usize = 40;
+
+// making a board by hand: putting mines down and choosing which cells start out uncovered,
+// then trying it out or adding it to a puzzle pack
+#[derive(Debug)]
+pub struct Editor {
+    out: Stdout,
+    config: Config,
+    events: Events,
+    // the pack the board gets saved to
+    path: PathBuf,
+    // the board from the top row down, in the puzzle format: * is a mine, - a covered cell, o an uncovered one and # a wall
+    rows: Vec<Vec<char>>,
+    // the cell the cursor is on, from the top left
+    selection: (usize, usize),
+    // what just happened, under the board
+    message: String,
+}
+
+impl Editor {
+    pub fn new(
+        path: PathBuf,
+        width: usize,
+        height: usize,
+        config: Config,
+        events: Events,
+    ) -> Result<Self> {
+        let mut out = stdout();
+
+        if config.set_title {
+            set_title(&mut out, "Minesweeper - Editor")?;
+        }
+
+        Ok(Self {
+            out,
+            config,
+            events,
+            path,
+            rows: vec![vec!['-'; width]; height],
+            selection: (width / 2, height / 2),
+            message: String::new(),
+        })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.out.execute(Clear(ClearType::All))?.execute(Show)?;
+        self.draw()?;
+
+        loop {
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                Event::Resize(..) => {
+                    self.out.execute(Clear(ClearType::All))?;
+                    self.draw()?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            // the rows go from the top down, so up is a step back through them
+            if let Some((dx, dy)) = self.config.movement.direction(key.code) {
+                let (x, y) = self.selection;
+                self.selection = (
+                    x.saturating_add_signed(dx).min(self.rows[0].len() - 1),
+                    y.saturating_add_signed(-dy).min(self.rows.len() - 1),
+                );
+                self.draw()?;
+                continue;
+            }
+
+            let (x, y) = self.selection;
+            let cell = &mut self.rows[y][x];
+            self.message.clear();
+
+            match key.code {
+                KeyCode::Char('m') => *cell = if *cell == '*' { '-' } else { '*' },
+                KeyCode::Char(' ') | KeyCode::Enter => *cell = if *cell == 'o' { '-' } else { 'o' },
+                // # like in the puzzle format, w would be up with wasd movement
+                KeyCode::Char('#') => *cell = if *cell == '#' { '-' } else { '#' },
+                KeyCode::Char('c') => self.rows.iter_mut().for_each(|row| row.fill('-')),
+                KeyCode::Char('p') => self.play()?,
+                KeyCode::Char('e') => self.save(),
+                // ctrl+c quits the same way q does
+                _ if input::is_interrupt(&key) || key.code == KeyCode::Char('q') => return Ok(()),
+                _ => continue,
+            }
+
+            self.draw()?;
+        }
+    }
+
+    fn puzzle(&self, name: String) -> Puzzle {
+        Puzzle {
+            name,
+            hint: String::new(),
+            board: self.rows.iter().map(|row| row.iter().collect()).collect(),
+        }
+    }
+
+    // try the board out. it's played the same way puzzles are, with the config and events lent to it until it's done
+    fn play(&mut self) -> Result<()> {
+        let events = mem::replace(&mut self.events, Events::terminal());
+        let mut practice = Practice::single(
+            self.puzzle("your board".to_string()),
+            self.config.clone(),
+            events,
+        )?;
+
+        while practice.run()? {}
+
+        self.events = practice.into_events();
+        self.out.execute(Clear(ClearType::All))?;
+        self.message = "back in the editor".to_string();
+
+        Ok(())
+    }
+
+    // add the board to the end of the pack, starting a new pack named after the file if there isn't one yet
+    fn save(&mut self) {
+        let pack = match self.path.exists() {
+            true => Pack::load(&self.path),
+            false => Ok(Pack {
+                name: self
+                    .path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                puzzles: Vec::new(),
+            }),
+        };
+
+        self.message = match pack {
+            Ok(mut pack) => {
+                // numbered after the puzzles already there, skipping any numbers that are taken
+                let name = (pack.puzzles.len() + 1..)
+                    .map(|number| format!("puzzle {number}"))
+                    .find(|name| pack.puzzles.iter().all(|puzzle| &puzzle.name != name))
+                    .unwrap_or_default();
+                pack.puzzles.push(self.puzzle(name.clone()));
+
+                match pack.save(&self.path) {
+                    Ok(()) => format!("added {name} to {}", self.path.display()),
+                    Err(error) => format!("couldn't save to {}: {error}", self.path.display()),
+                }
+            }
+            Err(error) => format!("couldn't read {}: {error}", self.path.display()),
+        };
+    }
+
+    // mines are always shown and uncovered cells show the number they'll have
+    fn tile(&self, board: &Board, x: usize, y: usize) -> Tile {
+        if board.is_mine(x, y) {
+            Tile::Mine
+        } else if board.is_wall(x, y) {
+            Tile::Wall
+        } else if !board.is_uncovered(x, y) {
+            Tile::Covered
+        } else {
+            match board.adjacent_mines(x, y) {
+                0 => Tile::Empty,
+                num => Tile::Number(num),
+            }
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let theme = &self.config.theme;
+        let style = &theme.board;
+        let (pitch_x, pitch_y) = (style.gap_x + style.cell_width(), style.gap_y + 1);
+        let top = self.config.safe_area().top;
+        let board = self.puzzle(String::new()).board(None);
+        let height = self.rows.len();
+
+        self.out
+            .queue(MoveTo(0, top))?
+            .queue(Clear(ClearType::FromCursorDown))?
+            .queue(Print(theme.status_bar.apply(
+                "Editor | m mine, space uncovered, # wall, c clear, p play, e add to pack, q quit",
+            )))?;
+
+        for row in 0..height {
+            let y = height - 1 - row;
+            let line: String = (0..self.rows[0].len())
+                .map(|x| format!("{}{}", style.glyph(self.tile(&board, x, y)), style.gap()))
+                .collect();
+
+            self.out
+                .queue(MoveTo(0, top + 2 + (row * pitch_y) as u16))?
+                .queue(Print(line))?;
+        }
+
+        let message_row = top + 2 + (((height - 1) * pitch_y) + 2) as u16;
+        self.out
+            .queue(MoveTo(0, message_row))?
+            .queue(Print(theme.menu.apply(&self.message)))?;
+
+        let (x, row) = self.selection;
+        self.out.queue(MoveTo(
+            (x * pitch_x) as u16,
+            top + 2 + (row * pitch_y) as u16,
+        ))?;
+
+        self.out.flush()?;
+        Ok(())
+    }
+}
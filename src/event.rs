@@ -0,0 +1,26 @@
+// something that happened in a game. Round::apply() says what each move did and what to do about it is up to
+// whatever's listening: the co-op and desktop frontends stop their clocks on the game ending, and in the terminal
+// game the replay that stats and bests are kept from picks these up as they happen, the screen and the sounds once
+// the key press that caused them has been dealt with
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    // a covered cell was uncovered, along with every cell that opened up because of it (the cell itself included)
+    CellRevealed {
+        cell: (usize, usize),
+        opened: Vec<(usize, usize)>,
+        // whether the assist opened it rather than the player
+        assisted: bool,
+    },
+    // a flag went on, or came off
+    Flagged {
+        cell: (usize, usize),
+        flagged: bool,
+    },
+    // a mine went off
+    Exploded {
+        cell: (usize, usize),
+    },
+    Won,
+    // the clock's moved on
+    TimerTick,
+}
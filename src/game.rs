@@ -0,0 +1,3788 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{self, stdout},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor::MoveToNextLine,
+    event::{Event, KeyCode},
+    style::{Print, StyledContent, Stylize},
+    terminal, ExecutableCommand, Result,
+};
+use ratatui::{
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Paragraph, Wrap},
+};
+
+use rand::{prelude::SliceRandom, Rng};
+#[cfg(feature = "tokio")]
+use {crossterm::event::EventStream, futures_util::StreamExt, tokio::time::MissedTickBehavior};
+
+use crate::{
+    analysis::{self, Judgement},
+    autosave,
+    board::{cell_name, column_label, Board, CellState},
+    code::GameCode,
+    config::{Assist, Config},
+    daily,
+    event::GameEvent,
+    generator::{self, BoardCache, Placement, Settings, Wanted},
+    ghost::{self, Ghost},
+    history::History,
+    input::{self, Events},
+    leaderboard::{self, Leaderboard},
+    net::Opponent,
+    online, paths,
+    player::{self, Round, Standing},
+    race::Race,
+    rating::{Band, Rating},
+    replay::{self, Action, ActionKind, Replay, Transform},
+    saves::{self, SavedGame, Slots},
+    session::{Outcome, Session},
+    share::{self, Ending},
+    solver::Solver,
+    spectate::{self, Frame, Spectators},
+    stats::Stats,
+    status::{Status, StatusPosition},
+    terminal::set_title,
+    theme::{self, Sound, Tile},
+    ui::{self, MinesweeperState, MinesweeperWidget, Screen},
+};
+
+const MENU: &str = r#"Welcome to Minesweeper
+Copyright 2022 Grant Handy
+
+Controls:
+    q/ctrl+c - quit
+    r - restart
+    arrow keys/wasd/hjkl - navigate board
+    shift + direction - move five cells
+    number + direction - move that many cells
+    page up/page down/home/end - jump to the edge
+    enter/space - uncover cell
+    m - mark cell
+    g - give up and see the solution
+    v - save the game to a slot
+    ? - help while playing
+
+1. Beginner - 9 * 9 Board and 10 Mines
+2. Intermediate - 16 * 16 Board and 40 Mines
+3. Advanced - 24 * 24 Board and 99 Mines
+
+l - load a saved game
+r - history of past games
+s - lifetime statistics
+h - high scores
+o - today's daily challenge rankings online
+p - switch player profile"#;
+
+// how often the game wakes up without any input to keep the timer moving, more often for a speedrun's millisecond clock
+const TICK: Duration = Duration::from_millis(100);
+const SPEEDRUN_TICK: Duration = Duration::from_millis(20);
+
+// openings are revealed outward from the click over at most this many frames
+const CASCADE_FRAMES: usize = 8;
+const CASCADE_FRAME_TIME: Duration = Duration::from_millis(25);
+
+// how the end of a game plays out: the mine that went off flashes, every flag goes in within FLAG_PLANTING_TIME
+// and the confetti gets thrown for a few frames
+const FLASH_TIME: Duration = Duration::from_millis(80);
+const FLAG_PLANTING_TIME: Duration = Duration::from_millis(400);
+const CONFETTI_FRAMES: usize = 6;
+const CONFETTI_FRAME_TIME: Duration = Duration::from_millis(70);
+
+// uncover everything at the beginning
+// use this for debugging
+const SHOW_EVERYTHING: bool = false;
+
+// columns between the board and the panel beside it
+const PANEL_GAP: u16 = 3;
+
+// how fast a replay can be watched, - and + go through them
+const SPEEDS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+
+// the name shown for each preset
+pub fn level_name(level: u8) -> &'static str {
+    match level {
+        1 => "Beginner",
+        2 => "Intermediate",
+        3 => "Advanced",
+        _ => "Custom",
+    }
+}
+
+// what a game was played as: its level, or the day for a daily challenge
+fn difficulty(replay: &Replay) -> String {
+    match replay.daily {
+        Some(day) => format!("Daily {}", daily::date(day)),
+        None => level_name(replay.level).to_string(),
+    }
+}
+
+enum Input {
+    // Space / Enter
+    Select,
+    // Returns the next cursor direction from the movement keys
+    Direction((usize, usize)),
+    // q
+    Quit,
+    // m
+    Mark,
+    // ?
+    Help,
+    // r
+    Restart,
+    // g
+    GiveUp,
+    // 0-9, a digit of how many cells the next move goes
+    Count(usize),
+    // v
+    Save,
+}
+
+// what was picked from the level menu
+enum Choice {
+    Level(u8),
+    Saved(SavedGame),
+    // a game from the history to watch, or whose board to play again
+    Watch(Replay),
+    Retry(GameCode),
+}
+
+// where the terminal's cursor goes
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Cursor {
+    // on the selected cell
+    Selection,
+    // after whatever's being typed under the board
+    At(u16, u16),
+    Hidden,
+}
+
+// boards one after another on the same clock, until a mine goes off
+#[derive(Copy, Clone, Debug)]
+struct Marathon {
+    // the level the first board is on
+    level: u8,
+    // move up a level every RAMP_EVERY boards
+    ramp: bool,
+    // boards cleared so far
+    boards: usize,
+    // the first reveal on the first board
+    started: Option<Instant>,
+}
+
+// what kind of game is being played. they can't be mixed, so each one only carries what it needs
+#[derive(Debug)]
+enum Mode {
+    // one board after another on your own
+    Solo,
+    // the daily challenge for this day
+    Daily(u64),
+    Marathon(Marathon),
+    // two players taking turns at one keyboard
+    Race(Race),
+    // racing someone over the network
+    Online(Opponent),
+    // a game picked from the history to watch instead of playing, see take_watching()
+    Watching(Replay),
+}
+
+impl Mode {
+    // whether someone else is playing the same board, here or over the network
+    fn is_race(&self) -> bool {
+        matches!(self, Self::Race(_) | Self::Online(_))
+    }
+
+    fn daily(&self) -> Option<u64> {
+        match *self {
+            Self::Daily(day) => Some(day),
+            _ => None,
+        }
+    }
+
+    fn marathon(&self) -> Option<Marathon> {
+        match *self {
+            Self::Marathon(marathon) => Some(marathon),
+            _ => None,
+        }
+    }
+}
+
+// how many boards a ramping marathon stays on each level for
+const RAMP_EVERY: usize = 3;
+
+// how long to keep generating boards looking for one with the rating that was asked for
+const REROLL_FOR: Duration = Duration::from_secs(2);
+
+// how often a game in progress gets written out, in case the session's cut off before it ends
+const AUTOSAVE_EVERY: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct Game {
+    // everything gets drawn through this, a frame at a time
+    screen: Screen,
+    // the board and the rules for playing on it: where the mines are and what the player's done to each cell
+    round: Round,
+    // number of mines on the board
+    num_mines: usize,
+    // width of the board
+    width: usize,
+    // height of the board
+    height: usize,
+    // coordinates of where our cursor is at the moment
+    selection: (usize, usize),
+    // if the board has been touched
+    is_touched: bool,
+    // if we should show everything
+    show_everything: bool,
+    // user settings
+    config: Config,
+    // which preset we're playing
+    level: u8,
+    // when the first cell was uncovered
+    started: Option<Instant>,
+    // everything that's happened in the current game
+    replay: Replay,
+    // the fastest win on this level, used to show how we're pacing against it
+    best: Option<Replay>,
+    // how many cells the next move goes, typed as digits before it
+    count: Option<usize>,
+    // when the reveal key last came in, to spot it being held down
+    last_select: Option<Instant>,
+    // every reveal and flag the player's made this game, wasted ones included
+    clicks: usize,
+    // boards generated in the background so new games start instantly
+    cache: BoardCache,
+    // where key presses come from, usually just the terminal
+    events: Events,
+    // every game played so far, for the summary printed on exit
+    session: Session,
+    // the cells the solver would click to finish the game, highlighted after giving up
+    solution: Vec<(usize, usize)>,
+    // what's said under the board: a notice, a question or how the game ended
+    footer: Vec<Spans<'static>>,
+    // whether the footer is a notice that goes away with the next key press
+    notice: bool,
+    // what's shown beside the board, like how the solver rates each move of a replay being watched
+    panel: Vec<Spans<'static>>,
+    cursor: Cursor,
+    // cells an animation is drawing as something else for now
+    drawn_over: HashMap<(usize, usize), Span<'static>>,
+    // the best run from the last time this board was played, racing alongside
+    ghost: Option<Ghost>,
+    // a board loaded from a file that every game is played on instead of a generated one
+    fixed: Option<Board>,
+    // the game code every game's board comes from instead of a random seed
+    code: Option<GameCode>,
+    // what kind of game it is: on your own, the daily challenge, a marathon or a race
+    mode: Mode,
+    // whoever's watching, if the game's taking spectators
+    spectators: Option<Spectators>,
+    // how many mines it takes to lose, 1 unless playing with lives
+    lives: u8,
+    // the mines that have gone off without ending the game, they stay flagged
+    hit: Vec<(usize, usize)>,
+    // results shared from the end screen, printed again after quitting so they can still be copied out of the terminal
+    shared: Vec<String>,
+    // how the mines get spread over the board
+    placement: Placement,
+    // how hard this game's board is, once it's been worked out
+    rating: Option<Rating>,
+    // the rating being worked out in the background
+    rating_job: Option<Receiver<Rating>>,
+    // how sending the last win to the online leaderboard went, said on the end screen
+    submitted: Option<String>,
+    // the win being sent to the online leaderboard in the background
+    submit_job: Option<Receiver<String>>,
+    // boards get generated again until one comes out in this band
+    wanted_rating: Option<Band>,
+    // what's happened since the screen last caught up, see show_events()
+    happened: Vec<GameEvent>,
+    // a game that was cut off, put back on the board when the first game starts
+    resume: Option<SavedGame>,
+    // when the game in progress was last autosaved
+    autosaved: Option<Instant>,
+    // the clock's shown to the millisecond with the splits beside the board, see set_speedrun()
+    speedrun: bool,
+    // how long there is to clear the board in a timed challenge
+    time_limit: Option<Duration>,
+    // when the clock was stopped for the terminal going into the background, see pause()
+    paused: Option<Instant>,
+}
+
+impl Game {
+    pub fn new(level: Option<&str>, mut config: Config, mut events: Events) -> Result<Self> {
+        let mut screen = Screen::new()?;
+
+        let level = match level {
+            Some(level) => level
+                .parse::<u8>()
+                .ok()
+                .filter(|level| (1..=3).contains(level))
+                .unwrap_or(1),
+            None => match Self::choose_level(&mut screen, &mut events, &mut config)? {
+                Choice::Level(level) => level,
+                Choice::Saved(saved) => return Self::from_saved(saved, config, events),
+                Choice::Watch(replay) => {
+                    let mut game = Self::setup(
+                        screen,
+                        replay.level,
+                        replay.width,
+                        replay.height,
+                        replay.mines.len(),
+                        config,
+                        events,
+                    )?;
+                    game.mode = Mode::Watching(replay);
+                    return Ok(game);
+                }
+                Choice::Retry(code) => return Self::with_code(code, config, events),
+            },
+        };
+
+        let Settings {
+            width,
+            height,
+            mines,
+            ..
+        } = Self::level_settings(level);
+        let mut game = Self::setup(screen, level, width, height, mines, config, events)?;
+        game.best = Replay::load_best(level, game.config.assist, game.config.no_flags)?;
+        game.cache.prepare(game.settings());
+
+        Ok(game)
+    }
+
+    // a new game, unless the last session was cut off part way through one and the player would rather pick it back up
+    pub fn recover(level: Option<&str>, config: Config, mut events: Events) -> Result<Self> {
+        // a crash is when the autosave's most likely to be broken, so one that can't be read is cleared away rather
+        // than stopping every game after it
+        let saved = match autosave::load() {
+            Ok(saved) => saved,
+            Err(error) => {
+                autosave::clear()?;
+                Self::lost_autosave(&mut Screen::new()?, &mut events, &config, &error)?;
+                None
+            }
+        };
+
+        match saved {
+            Some(saved)
+                if Self::offer_autosave(&mut Screen::new()?, &mut events, &config, &saved)? =>
+            {
+                Self::from_saved(saved, config, events)
+            }
+            Some(_) => {
+                autosave::clear()?;
+                Self::new(level, config, events)
+            }
+            None => Self::new(level, config, events),
+        }
+    }
+
+    // carry on a game that was saved part way through, from a slot or the autosave
+    fn from_saved(saved: SavedGame, config: Config, events: Events) -> Result<Self> {
+        // it's played the way it was started, whatever the config says now
+        let mut config = config;
+        config.assist = saved.replay.assist;
+        config.no_flags = saved.replay.no_flags;
+
+        let replay = &saved.replay;
+        let mut game = match replay.daily {
+            Some(day) => Self::daily(day, config, events)?,
+            None => {
+                let mut game = Self::setup(
+                    Screen::new()?,
+                    replay.level,
+                    replay.width,
+                    replay.height,
+                    replay.mines.len(),
+                    config,
+                    events,
+                )?;
+                if replay.level != 0 {
+                    game.best = Replay::load_best(replay.level, replay.assist, replay.no_flags)?;
+                }
+                game.placement = replay.placement;
+                game.cache.prepare(game.settings());
+                game
+            }
+        };
+
+        // a daily game's board comes from its day, so a saved one that isn't that size starts over instead
+        let fits = (game.width, game.height) == (replay.width, replay.height);
+        game.resume = Some(saved).filter(|_| fits);
+        Ok(game)
+    }
+
+    // play the board from a game code, every game. it's on whichever level it's the size of, or a custom game if it isn't one
+    pub fn with_code(code: GameCode, config: Config, events: Events) -> Result<Self> {
+        let level = (1..=3)
+            .find(|&level| Self::level_settings(level) == code.settings)
+            .unwrap_or(0);
+        let Settings {
+            width,
+            height,
+            mines,
+            ..
+        } = code.settings;
+
+        let mut game = Self::setup(Screen::new()?, level, width, height, mines, config, events)?;
+        if level != 0 {
+            game.best = Replay::load_best(level, game.config.assist, game.config.no_flags)?;
+        }
+        game.code = Some(code);
+        game.placement = code.settings.placement;
+
+        Ok(game)
+    }
+
+    // play the daily challenge for `day`. it has its own stats and stays off the level's best times and high scores
+    pub fn daily(day: u64, config: Config, events: Events) -> Result<Self> {
+        let mut game = Self::with_code(daily::code(day), config, events)?;
+        game.mode = Mode::Daily(day);
+        game.best = None;
+
+        if game.config.set_title {
+            set_title(
+                game.screen.out(),
+                &format!("Minesweeper - Daily {}", daily::date(day)),
+            )?;
+        }
+
+        Ok(game)
+    }
+
+    // play a marathon starting on `level`, getting harder as it goes if `ramp` is set. each board is timed as part of
+    // the whole run, so marathon boards stay out of the level's stats, best times and high scores
+    pub fn marathon(
+        level: Option<&str>,
+        ramp: bool,
+        config: Config,
+        events: Events,
+    ) -> Result<Self> {
+        let mut game = Self::new(level, config, events)?;
+        game.best = None;
+        game.mode = Mode::Marathon(Marathon {
+            level: game.level,
+            ramp,
+            boards: 0,
+            started: None,
+        });
+
+        Ok(game)
+    }
+
+    // two players taking turns on the same boards at one keyboard, see Race. a turn on someone else's board isn't a
+    // fair game, so races stay out of the stats, best times and high scores
+    pub fn race(
+        level: Option<&str>,
+        players: [String; 2],
+        handicaps: [Duration; 2],
+        config: Config,
+        events: Events,
+    ) -> Result<Self> {
+        let mut game = Self::new(level, config, events)?;
+        game.best = None;
+        game.mode = Mode::Race(Race::new(players, handicaps));
+
+        Ok(game)
+    }
+
+    // race someone over the network on the game code's board, see net. you both get one go at it, so there's no
+    // starting over
+    pub fn set_opponent(&mut self, opponent: Opponent) {
+        self.mode = Mode::Online(opponent);
+    }
+
+    // send every frame to whoever connects, see spectate
+    pub fn set_spectators(&mut self, spectators: Spectators) {
+        self.spectators = Some(spectators);
+    }
+
+    // time the game to the millisecond and split it at SPLITS, next to when the best run on the level got to each one
+    pub fn set_speedrun(&mut self) {
+        self.speedrun = true;
+    }
+
+    // a timed challenge: the board has to be cleared within `limit` or it's lost. these are counted on their own for
+    // each level and limit, and stay off the level's best times and high scores
+    pub fn set_time_limit(&mut self, limit: Duration) {
+        self.time_limit = Some(limit);
+        self.best = None;
+    }
+
+    // the casual variant: each mine that goes off costs a life and gets flagged, and the game's only lost on the last one.
+    // games with more than one life stay out of the stats, best times and high scores
+    pub fn set_lives(&mut self, lives: u8) {
+        self.lives = lives.max(1);
+    }
+
+    // spread the mines some other way than uniformly. these boards are a different game, so they stay out of the
+    // level's stats, best times and high scores
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+        self.cache.prepare(self.settings());
+
+        if placement != Placement::Uniform {
+            self.best = None;
+        }
+    }
+
+    // only play boards that rate as `band`. picking boards is a different game, so these stay out of the level's stats,
+    // best times and high scores
+    pub fn set_wanted_rating(&mut self, band: Band) {
+        self.wanted_rating = Some(band);
+        self.best = None;
+
+        // the cache does most of the searching in the background, so the next game rarely has to wait for it
+        self.cache.want(Some(Wanted {
+            name: band.name(),
+            keep: Arc::new(move |board, start| Rating::of(board, start).band() == band),
+        }));
+        self.cache.prepare(self.settings());
+    }
+
+    pub fn level_settings(level: u8) -> Settings {
+        let (width, height, mines) = match level {
+            1 => (9, 9, 10),
+            2 => (16, 16, 40),
+            3 => (24, 24, 99),
+            _ => (9, 9, 10),
+        };
+
+        Settings {
+            width,
+            height,
+            mines,
+            placement: Placement::Uniform,
+        }
+    }
+
+    // play on a board loaded from a file, as it is, every game. these count as custom games
+    pub fn with_board(board: Board, config: Config, events: Events) -> Result<Self> {
+        let (width, height, num_mines) = (board.width(), board.height(), board.num_mines());
+
+        let mut game = Self::setup(Screen::new()?, 0, width, height, num_mines, config, events)?;
+        game.fixed = Some(board);
+
+        Ok(game)
+    }
+
+    fn setup(
+        mut screen: Screen,
+        level: u8,
+        width: usize,
+        height: usize,
+        num_mines: usize,
+        config: Config,
+        events: Events,
+    ) -> Result<Self> {
+        if config.set_title {
+            set_title(
+                screen.out(),
+                &format!("Minesweeper - {}", level_name(level)),
+            )?;
+        }
+
+        // starts at 0!! the board starts at 1.
+        let selection = ((width / 2), (height / 2));
+        let show_everything = SHOW_EVERYTHING;
+        let is_touched = false;
+
+        Ok(Self {
+            screen,
+            round: Round::with_board(Board::new(width, height)),
+            num_mines,
+            width,
+            height,
+            selection,
+            is_touched,
+            show_everything,
+            config,
+            level,
+            started: None,
+            replay: Replay::new(level, width, height),
+            best: None,
+            count: None,
+            last_select: None,
+            clicks: 0,
+            cache: BoardCache::default(),
+            events,
+            session: Session::default(),
+            solution: Vec::new(),
+            footer: Vec::new(),
+            panel: Vec::new(),
+            notice: false,
+            cursor: Cursor::Selection,
+            drawn_over: HashMap::new(),
+            ghost: None,
+            fixed: None,
+            code: None,
+            mode: Mode::Solo,
+            spectators: None,
+            lives: 1,
+            hit: Vec::new(),
+            shared: Vec::new(),
+            placement: Placement::Uniform,
+            rating: None,
+            rating_job: None,
+            submitted: None,
+            submit_job: None,
+            wanted_rating: None,
+            happened: Vec::new(),
+            resume: None,
+            autosaved: None,
+            speedrun: false,
+            time_limit: None,
+            paused: None,
+        })
+    }
+
+    pub fn run(&mut self) -> Result<bool> {
+        self.start()?;
+
+        loop {
+            // once the clock's running we wake up every tick to update it, until then there's nothing to do
+            // but wait for a keypress
+            if self.clock().is_some() && !self.events.poll(self.tick_length())? {
+                if let Some(again) = self.tick()? {
+                    return Ok(again);
+                }
+                continue;
+            }
+
+            let event = self.events.read()?;
+
+            if let Some(again) = self.handle(event)? {
+                return Ok(again);
+            }
+        }
+    }
+
+    // run() on tokio: keys come from crossterm's EventStream and the clock ticks on a timer, so nothing has to poll.
+    // it reads the terminal itself, --record-input and --play-input need run()
+    #[cfg(feature = "tokio")]
+    pub async fn run_async(&mut self) -> Result<bool> {
+        self.start()?;
+
+        let mut events = EventStream::new();
+        let mut ticks = tokio::time::interval(self.tick_length());
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(Ok(event)) if input::is_release(&event) => (),
+                    Some(event) => {
+                        if let Some(again) = self.handle(event?)? {
+                            return Ok(again);
+                        }
+                    }
+                    None => return Ok(false),
+                },
+                _ = ticks.tick(), if self.clock().is_some() => {
+                    if let Some(again) = self.tick()? {
+                        return Ok(again);
+                    }
+                }
+            }
+        }
+    }
+
+    // set up a new game: a blank board, the cursor back in the middle and the last game's messages gone
+    fn start(&mut self) -> Result<()> {
+        // a ramping marathon goes up a level every few boards
+        if let Some(marathon) = self.mode.marathon().filter(|marathon| marathon.ramp) {
+            let level = (marathon.level as usize + marathon.boards / RAMP_EVERY).min(3) as u8;
+
+            if level != self.level {
+                let Settings {
+                    width,
+                    height,
+                    mines,
+                    ..
+                } = Self::level_settings(level);
+                (self.level, self.width, self.height, self.num_mines) =
+                    (level, width, height, mines);
+                self.cache.prepare(self.settings());
+            }
+        }
+
+        // both turns of a race's round are on the board its first turn got
+        let settings = self.settings();
+        if let Mode::Race(race) = &mut self.mode {
+            self.code = Some(*race.code.get_or_insert_with(|| GameCode {
+                seed: rand::random(),
+                settings,
+                start: settings.start(),
+            }));
+        }
+
+        self.create_blank_board();
+
+        // reset data from last game. a game code's board opens up where its first click was, so that's where the cursor starts
+        self.selection = match self.code {
+            Some(code) => code.start,
+            None => ((self.width / 2), (self.height / 2)),
+        };
+        self.show_everything = SHOW_EVERYTHING;
+        self.is_touched = false;
+        self.started = None;
+        self.clicks = 0;
+        self.hit.clear();
+        self.rating = None;
+        self.rating_job = None;
+        self.submitted = None;
+        self.submit_job = None;
+        self.replay = Replay::new(self.level, self.width, self.height);
+        self.replay.assist = self.config.assist;
+        self.replay.no_flags = self.config.no_flags;
+        self.replay.daily = self.mode.daily();
+        self.replay.placement = self.placement;
+        self.replay.time_limit = self.time_limit.map(|limit| limit.as_millis() as u64);
+        self.autosaved = None;
+        self.paused = None;
+
+        let resumed = self
+            .resume
+            .take()
+            .map(|saved| self.restore(saved))
+            .is_some();
+
+        // a board that's been played before brings back its best run, unless someone else is playing it too
+        self.ghost = match self.code {
+            Some(code) if self.config.ghost && !self.mode.is_race() => Ghost::load(code)?,
+            _ => None,
+        };
+
+        // clear away the last game's message and show the cursor
+        self.footer.clear();
+        self.notice = false;
+        self.cursor = Cursor::Selection;
+
+        // draw the boards initial state
+        self.draw_board()?;
+
+        if let Some(code) = self.code {
+            let opening = cell_name(code.start.0, code.start.1);
+            let text = match &self.mode {
+                Mode::Race(race) => format!(
+                    "round {}, {}'s turn: the board opens up at {opening}, under the cursor",
+                    race.round(),
+                    race.player()
+                ),
+                _ => format!("this board opens up at {opening}, under the cursor"),
+            };
+            self.notice(&text)?;
+        }
+
+        if let Some(marathon) = self.mode.marathon().filter(|marathon| marathon.boards > 0) {
+            self.notice(&format!(
+                "marathon: {} cleared, here's board {}",
+                marathon.boards,
+                marathon.boards + 1
+            ))?;
+        }
+
+        if resumed {
+            self.notice("picked up where the game was left off, the clock carries on from there")?;
+        }
+
+        Ok(())
+    }
+
+    // put a game that was cut off back on the board, every move played again and the clock where it stopped
+    fn restore(&mut self, saved: SavedGame) {
+        let replay = &saved.replay;
+        self.round = Round::with_board(replay.board());
+        self.replay.seed = replay.seed;
+        self.replay.mines = replay.mines.clone();
+
+        if let Some(first) = replay
+            .actions
+            .iter()
+            .find(|action| action.kind == ActionKind::Reveal)
+        {
+            self.rating_job = Some(Rating::in_background(
+                self.round.board().clone(),
+                (first.x, first.y),
+            ));
+        }
+
+        self.is_touched = true;
+
+        for &action in &replay.actions {
+            self.play_action(action);
+        }
+
+        self.clicks = replay.actions.len();
+        self.started = Instant::now().checked_sub(saved.elapsed());
+    }
+
+    // games that can be picked back up after being cut off: a plain board, not part of a marathon, race or anything
+    // else that couldn't be put back together from its replay
+    fn autosaves(&self) -> bool {
+        matches!(self.mode, Mode::Solo | Mode::Daily(_))
+            && self.fixed.is_none()
+            && (self.code.is_none() || self.mode.daily().is_some())
+            && self.lives == 1
+            && self.time_limit.is_none()
+            && self.reads_terminal()
+    }
+
+    // write the game in progress out every AUTOSAVE_EVERY while its clock's running
+    fn autosave(&mut self) -> Result<()> {
+        let elapsed = match self.elapsed() {
+            Some(elapsed) => elapsed,
+            None => return Ok(()),
+        };
+
+        if self
+            .autosaved
+            .is_none_or(|saved| saved.elapsed() >= AUTOSAVE_EVERY)
+            && self.autosaves()
+        {
+            autosave::save(&self.replay, elapsed)?;
+            self.autosaved = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+
+    // what an event from the terminal does to the game: Some(true) once it's over and another should start,
+    // Some(false) to stop playing and None to carry on
+    fn handle(&mut self, event: Event) -> Result<Option<bool>> {
+        // the clock stops while the terminal's in the background, and anything else that comes in starts it again.
+        // that's all it does, the board might not have been showing when it was pressed
+        match event {
+            Event::FocusLost => return self.pause().map(|_| None),
+            Event::FocusGained => return self.resume().map(|_| None),
+            _ if self.paused.is_some() => return self.resume().map(|_| None),
+            _ => {}
+        }
+
+        self.clear_notice()?;
+
+        // get an Input from the event
+        let input = self.get_input(event);
+
+        // a count only applies to the move straight after it
+        if !matches!(input, Some(Input::Count(_))) {
+            self.count = None;
+        }
+
+        match input {
+            // if we have one...
+            Some(input) => match input {
+                // if it's a new direction update the cursor and wait for the next key.
+                Input::Direction(next_selection) => {
+                    self.selection = next_selection;
+                    self.draw_board()?;
+                    return Ok(None);
+                }
+                // if the user said to mark the cell
+                Input::Mark => {
+                    if self.config.no_flags {
+                        self.notice("flagging is off for this no-flag run")?;
+                        return Ok(None);
+                    }
+
+                    self.clicks += 1;
+
+                    // if it's uncovered already there's nothing to do. no need to redraw and fill up the terminal buffer.
+                    // mines that have gone off keep their flags
+                    if self.get_current_cell().is_uncovered() || self.hit.contains(&self.selection)
+                    {
+                        return Ok(None);
+                    }
+
+                    // with strict flags there's only one flag for each mine
+                    let (x, y) = self.selection;
+                    if self.config.strict_flags
+                        && !self.round.board().is_flagged(x, y)
+                        && self.round.board().num_flagged() >= self.num_mines
+                    {
+                        self.notice(&format!(
+                            "no flags left, there are only {} mines",
+                            self.num_mines
+                        ))?;
+                        return Ok(None);
+                    }
+
+                    // otherwise mark it, or unmark it if it's marked
+                    self.play(player::Action::Flag(x, y), false);
+                    self.assist();
+                }
+                // if the user quit then end the function sending "false" which means don't restart it. Go to the exit message.
+                Input::Quit => {
+                    if self.is_touched
+                        && self.config.confirm_quit
+                        && !self.confirm("quit? (y/n)")?
+                    {
+                        return Ok(None);
+                    }
+
+                    self.abandon()?;
+                    return Ok(Some(false));
+                }
+                Input::Count(digit) => {
+                    self.count = Some(
+                        (self.count.unwrap_or(0) * 10 + digit).min(self.width.max(self.height)),
+                    );
+                    return Ok(None);
+                }
+                // if the user selected the cell...
+                Input::Select => {
+                    // a held down reveal key repeats much faster than anyone presses it, skip those repeats if asked to
+                    if self.config.ignore_key_repeat {
+                        let now = Instant::now();
+                        let last = self.last_select.replace(now);
+
+                        if last.is_some_and(|last| {
+                            now - last < Duration::from_millis(self.config.key_repeat_delay)
+                        }) {
+                            return Ok(None);
+                        }
+                    }
+
+                    self.clicks += 1;
+
+                    // if we haven't touched the board yet populate the board so the user doesn't click on a mine their first try
+                    if !self.is_touched {
+                        self.is_touched = true;
+                        // rerolling for a rating can take a moment, which shouldn't count against the clock
+                        self.populate_board()?;
+                        self.started = Some(Instant::now());
+
+                        if let Mode::Marathon(marathon) = &mut self.mode {
+                            marathon.started = marathon.started.or(self.started);
+                        }
+                    }
+
+                    // if we clicked on an uncovered one there's nothing to do and nothing to redraw
+                    if self.get_current_cell().is_uncovered() {
+                        return Ok(None);
+                    }
+
+                    // uncover the cell
+                    self.reveal(self.selection, false);
+                    self.assist();
+
+                    if self.config.auto_flag && !self.config.no_flags && self.exploded().is_none() {
+                        self.flag_remaining_mines();
+                    }
+                }
+                Input::Help => {
+                    self.show_help()?;
+                    return Ok(None);
+                }
+                Input::Save => {
+                    self.save_to_slot()?;
+                    return Ok(None);
+                }
+                Input::Restart if self.mode.is_race() => {
+                    self.notice("no starting over in a race, g gives up this turn")?;
+                    return Ok(None);
+                }
+                Input::Restart => {
+                    self.abandon()?;
+                    return Ok(Some(true));
+                }
+                // there's nothing to solve before the first click
+                Input::GiveUp if !self.is_touched => return Ok(None),
+                Input::GiveUp => {
+                    if let Mode::Online(opponent) = &mut self.mode {
+                        opponent.report_lost();
+                    }
+
+                    self.finish(Outcome::Lost)?;
+                    self.end_marathon()?;
+
+                    // the solution would give the board away to whoever's next
+                    if matches!(self.mode, Mode::Race(_)) {
+                        return self.end_turn(None).map(Some);
+                    }
+
+                    return self.give_up().map(Some);
+                }
+            },
+            // if the input was not a recognized one then wait for the next input.
+            // the less times we redraw the board the better, we don't want to fill up the terminal buffer.
+            None => return Ok(None),
+        }
+
+        // the rules say when a move's ended the game
+        let exploded = self.happened.iter().find_map(|event| match event {
+            GameEvent::Exploded { cell } => Some(*cell),
+            _ => None,
+        });
+
+        // if we uncovered a mine go to the losing screen.
+        // it might have been opened by the assist rather than the player, so the cursor goes to it
+        if let Some(exploded) = exploded {
+            self.show_events()?;
+            self.selection = exploded;
+
+            // with a life to spare the mine gets flagged and the game goes on
+            if self.lives_left() > 1 {
+                self.survive();
+
+                let lives = self.lives_left();
+                self.draw_board()?;
+                self.notice(&format!(
+                    "boom! {lives} {} left",
+                    if lives == 1 { "life" } else { "lives" }
+                ))?;
+                return Ok(None);
+            }
+
+            if let Mode::Online(opponent) = &mut self.mode {
+                opponent.report_lost();
+            }
+
+            // the board stays hidden in a race, the other player hasn't had their go yet
+            if matches!(self.mode, Mode::Race(_)) {
+                self.finish(Outcome::Lost)?;
+                return self.end_turn(None).map(Some);
+            }
+
+            if self.config.animate_endings {
+                self.animate_explosion()?;
+            }
+
+            self.finish(Outcome::Lost)?;
+
+            if let Some(line) = self.end_marathon()? {
+                return self
+                    .end_screen(
+                        "The marathon's over! press r to start another and q to quit",
+                        &[line, self.result_line()],
+                    )
+                    .map(Some);
+            }
+
+            let message = match self.mode {
+                Mode::Online(_) => "You lost! press q to quit",
+                _ => "You lost! press r to try again and q to quit",
+            };
+
+            return self.end_screen(message, &[self.result_line()]).map(Some);
+        }
+
+        // if we won go to the winning screen
+        if self.happened.contains(&GameEvent::Won) {
+            self.show_events()?;
+
+            // a marathon goes straight on to the next board, the clock's still running
+            if let Mode::Marathon(marathon) = &mut self.mode {
+                marathon.boards += 1;
+                self.finish(Outcome::Won)?;
+                return Ok(Some(true));
+            }
+
+            if let Mode::Online(opponent) = &mut self.mode {
+                opponent.report_won(self.replay.duration());
+            }
+
+            if matches!(self.mode, Mode::Race(_)) {
+                self.finish(Outcome::Won)?;
+                return self.end_turn(Some(self.replay.duration())).map(Some);
+            }
+
+            if self.config.animate_endings {
+                self.animate_victory()?;
+            }
+
+            self.finish(Outcome::Won)?;
+
+            // a new best gets its name put on the leaderboard, assisted and no-flag runs have their own bests and stay off it
+            if self.is_ranked()
+                && self.mode.daily().is_none()
+                && self.time_limit.is_none()
+                && self.save_if_best()?
+                && self.replay.assist == Assist::Off
+                && !self.replay.no_flags
+            {
+                if let Some(name) = self.ask_name()? {
+                    let mut leaderboard = Leaderboard::load()?;
+                    leaderboard.add(self.level, name, self.replay.duration());
+                    leaderboard.save()?;
+                }
+            }
+
+            let message = match self.mode {
+                Mode::Online(_) => "You won! press q to quit",
+                _ => "You won! press r to play again and q to quit",
+            };
+
+            let mut details = vec![self.result_line()];
+            if let Mode::Online(opponent) = &self.mode {
+                details.extend(opponent.handicapped(self.replay.duration()));
+            }
+            self.submit_online();
+
+            return self.end_screen(message, &details).map(Some);
+        }
+
+        self.report_progress();
+
+        // update the board on screen after everything else is done
+        self.show_events()?;
+        self.draw_board()?;
+
+        Ok(None)
+    }
+
+    // play a recorded game back on the board in real time
+    pub fn watch(&mut self, replay: &Replay) -> Result<bool> {
+        self.width = replay.width;
+        self.height = replay.height;
+        self.num_mines = replay.mines.len();
+        self.round = Round::with_board(replay.board());
+
+        self.selection = ((self.width / 2), (self.height / 2));
+        self.show_everything = SHOW_EVERYTHING;
+        self.is_touched = true;
+        self.replay = Replay::new(replay.level, replay.width, replay.height);
+        self.replay.mines = replay.mines.clone();
+        self.replay.assist = replay.assist;
+        self.replay.no_flags = replay.no_flags;
+        self.replay.placement = replay.placement;
+
+        self.notice = false;
+        self.cursor = Cursor::Selection;
+
+        let actions = &replay.actions;
+        let time = |played: usize| {
+            played
+                .checked_sub(1)
+                .map(|i| Duration::from_millis(actions[i].time))
+                .unwrap_or_default()
+        };
+        let exploded = |action: &Action| {
+            action.kind == ActionKind::Reveal
+                && replay
+                    .mines
+                    .contains(&((action.y * replay.width) + action.x))
+        };
+
+        // what the solver makes of each reveal, shown beside the board as it's played
+        let notes: Vec<(usize, String)> = analysis::analyse(replay)
+            .moves
+            .iter()
+            .map(|action| {
+                let note = match action.judgement {
+                    Judgement::FirstClick => "first click".to_string(),
+                    Judgement::Safe(_) => "safe deduction".to_string(),
+                    Judgement::ForcedGuess { probability } => {
+                        format!("forced guess, {:.0}% a mine", probability * 100.0)
+                    }
+                    Judgement::UnnecessaryRisk { probability, .. } => {
+                        format!("unnecessary risk, {:.0}% a mine", probability * 100.0)
+                    }
+                    Judgement::Blunder => "blunder, a known mine".to_string(),
+                };
+                let time = action.time as f64 / 1000.0;
+
+                (
+                    action.action,
+                    format!(
+                        "{time:>6.1}s {:<4} {note}",
+                        cell_name(action.cell.0, action.cell.1)
+                    ),
+                )
+            })
+            .collect();
+
+        // how many of the actions have been played, and where in the replay we are
+        let mut played = 0;
+        let mut at = Duration::ZERO;
+        let mut paused = false;
+        // normal speed, SPEEDS[1]
+        let mut speed = 1;
+        // when `at` was last caught up, the replay's moved on from there since at the speed it's going
+        let mut resumed = Instant::now();
+
+        self.draw_playback(
+            at,
+            paused,
+            SPEEDS[speed],
+            &notes[..notes.partition_point(|&(i, _)| i < played)],
+        )?;
+
+        loop {
+            // play everything that's due, the clock's redrawn as it goes
+            if !paused {
+                at += resumed.elapsed().mul_f64(SPEEDS[speed]);
+                resumed = Instant::now();
+
+                while played < actions.len() && Duration::from_millis(actions[played].time) <= at {
+                    self.play_action(actions[played]);
+                    played += 1;
+                }
+
+                // running off the end finishes it, stepping onto the last action waits there
+                if played == actions.len() {
+                    self.started = Instant::now().checked_sub(replay.duration());
+                    break;
+                }
+
+                self.draw_playback(
+                    at,
+                    paused,
+                    SPEEDS[speed],
+                    &notes[..notes.partition_point(|&(i, _)| i < played)],
+                )?;
+
+                // wait until the next action's due
+                let wait = Duration::from_millis(actions[played].time)
+                    .saturating_sub(at)
+                    .div_f64(SPEEDS[speed]);
+                if !self.events.poll(wait.min(TICK))? {
+                    continue;
+                }
+            }
+
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            // how many actions in to skip ahead to, playing everything on the way
+            let mut target = None;
+            let after =
+                |found: Option<usize>| found.map(|i| played + i + 1).unwrap_or(actions.len());
+
+            match key.code {
+                _ if input::is_interrupt(&key) => return Ok(false),
+                KeyCode::Char('q') => return Ok(false),
+                KeyCode::Char(' ') => paused = !paused,
+                KeyCode::Right => {
+                    paused = true;
+                    target = Some((played + 1).min(actions.len()));
+                }
+                KeyCode::Left => {
+                    paused = true;
+                    played = played.saturating_sub(1);
+                    self.rewind(replay, played);
+                    at = time(played);
+                }
+                KeyCode::Char('n') => {
+                    target = Some(after(
+                        actions[played..]
+                            .iter()
+                            .position(|action| action.kind == ActionKind::Reveal),
+                    ))
+                }
+                KeyCode::Char('e') => {
+                    target = Some(after(actions[played..].iter().position(exploded)))
+                }
+                KeyCode::Char('-') => speed = speed.saturating_sub(1),
+                KeyCode::Char('+' | '=') => speed = (speed + 1).min(SPEEDS.len() - 1),
+                _ => continue,
+            }
+
+            if let Some(target) = target {
+                while played < target {
+                    self.play_action(actions[played]);
+                    played += 1;
+                }
+
+                at = time(played);
+            }
+
+            resumed = Instant::now();
+            self.draw_playback(
+                at,
+                paused,
+                SPEEDS[speed],
+                &notes[..notes.partition_point(|&(i, _)| i < played)],
+            )?;
+        }
+
+        self.panel.clear();
+        self.end_screen("Replay finished! press r to watch again and q to quit", &[])
+    }
+
+    // play one of a replay's actions on the board
+    fn play_action(&mut self, action: Action) {
+        self.selection = (action.x, action.y);
+
+        // recorded moves are played by the same rules, so there's nothing to do with what they did
+        let _ = self.round.apply(match action.kind {
+            ActionKind::Reveal => player::Action::Reveal(action.x, action.y),
+            ActionKind::Mark => player::Action::Flag(action.x, action.y),
+        });
+
+        self.replay.actions.push(action);
+    }
+
+    // go back to how the board was after the first `played` of a replay's actions
+    fn rewind(&mut self, replay: &Replay, played: usize) {
+        self.round = Round::with_board(replay.board());
+        self.replay.actions.clear();
+        self.selection = ((self.width / 2), (self.height / 2));
+
+        for &action in &replay.actions[..played] {
+            self.play_action(action);
+        }
+    }
+
+    // the board part way through a replay with the clock at `at`, the controls under it and the solver's notes on
+    // the moves played so far beside it, as many of the latest as fit
+    fn draw_playback(
+        &mut self,
+        at: Duration,
+        paused: bool,
+        speed: f64,
+        notes: &[(usize, String)],
+    ) -> Result<()> {
+        self.started = Instant::now().checked_sub(at);
+
+        let theme = &self.config.theme;
+        let fit =
+            (MinesweeperWidget::new(self.round.board(), theme).rows() as usize).saturating_sub(1);
+        let latest = notes.len().saturating_sub(1);
+
+        self.panel = vec![Spans::from(ui::styled("Moves", &theme.menu_highlight))];
+        self.panel.extend(
+            notes
+                .iter()
+                .enumerate()
+                .skip(notes.len().saturating_sub(fit))
+                .map(|(n, (_, note))| {
+                    let style = if n == latest {
+                        &theme.menu_highlight
+                    } else {
+                        &theme.menu
+                    };
+                    Spans::from(ui::styled(note, style))
+                }),
+        );
+
+        let state = match paused {
+            true => "paused".to_string(),
+            false => format!("playing at {speed}x"),
+        };
+        self.footer = vec![
+            Spans::from(ui::styled(state, &self.config.theme.overlay)),
+            Spans::from("space pause, left/right step, n next reveal, e next explosion, -/+ speed, q stop watching"),
+        ];
+
+        self.draw_board()
+    }
+
+    // if the replay's board would fit the terminal better turned on its side, ask whether to turn it
+    pub fn offer_transform(&mut self, replay: Replay) -> Result<Replay> {
+        let (columns, rows) = terminal::size()?;
+        let safe_area = self.config.safe_area();
+        let rows = rows.saturating_sub(safe_area.top + safe_area.bottom);
+        let board = &self.config.theme.board;
+
+        let transform = match Transform::suggest(
+            replay.width,
+            replay.height,
+            board.gap_x,
+            board.gap_y,
+            columns,
+            rows,
+        ) {
+            Some(transform) => transform,
+            None => return Ok(replay),
+        };
+
+        let lines = vec![
+            Spans::from(format!(
+                "This {}x{} board doesn't fit your terminal, but would if it was turned.",
+                replay.width, replay.height
+            )),
+            Spans::default(),
+            Spans::from(format!(
+                "t - transpose{}",
+                if transform == Transform::Transpose {
+                    " (suggested)"
+                } else {
+                    ""
+                }
+            )),
+            Spans::from("c - rotate clockwise"),
+            Spans::from("a - rotate anticlockwise"),
+            Spans::from("enter - keep it as it is"),
+        ];
+        self.screen.page(safe_area.top, lines)?;
+
+        loop {
+            if let Event::Key(key) = self.events.read()? {
+                if input::is_interrupt(&key) {
+                    Self::quit();
+                }
+
+                let transform = match key.code {
+                    KeyCode::Char('t') => Transform::Transpose,
+                    KeyCode::Char('c') => Transform::RotateClockwise,
+                    KeyCode::Char('a') => Transform::RotateAnticlockwise,
+                    KeyCode::Enter => return Ok(replay),
+                    _ => continue,
+                };
+
+                return Ok(replay.transformed(transform));
+            }
+        }
+    }
+
+    // end the game and show the whole board, with the cells the solver would have clicked to finish it highlighted
+    // and its line written out underneath
+    fn give_up(&mut self) -> Result<bool> {
+        let board = analysis::analyse(&self.replay).board;
+        let line = analysis::solution(&board);
+
+        let steps: Vec<String> = line
+            .iter()
+            .map(|step| {
+                let name = cell_name(step.cell.0, step.cell.1);
+
+                match step.judgement {
+                    Judgement::ForcedGuess { probability } if step.exploded => {
+                        format!("{name} (guess, {:.0}%, a mine)", probability * 100.0)
+                    }
+                    Judgement::ForcedGuess { probability } => {
+                        format!("{name} (guess, {:.0}%)", probability * 100.0)
+                    }
+                    _ => name,
+                }
+            })
+            .collect();
+
+        // as many cells as fit on each line of the terminal
+        let width = terminal::size()?.0 as usize;
+        let mut lines: Vec<String> = Vec::new();
+
+        for step in steps {
+            match lines.last_mut() {
+                Some(last) if last.len() + step.len() + 2 <= width => {
+                    last.push_str(", ");
+                    last.push_str(&step);
+                }
+                _ => lines.push(step),
+            }
+        }
+
+        let mut details = vec![String::from("The solver would finish with:")];
+        details.extend(lines);
+
+        self.solution = line.iter().map(|step| step.cell).collect();
+        let message = match self.mode {
+            Mode::Online(_) => "You gave up! press q to quit",
+            _ => "You gave up! press r to try again and q to quit",
+        };
+        let restart = self.end_screen(message, &details);
+        self.solution.clear();
+
+        restart
+    }
+
+    // the screen that shows up when you lose or win, with any extra lines of text under the message
+    fn end_screen(&mut self, message: &str, details: &[String]) -> Result<bool> {
+        // hide the cursor
+        self.cursor = Cursor::Hidden;
+
+        // show everything to the user because they've lost
+        // it's nice for them to see how they could've won
+        self.show_everything = true;
+
+        self.draw_end_screen(message, details)?;
+
+        // loop through the events. when racing someone over the network their result can still come in, and so can
+        // how sending the win to the online leaderboard went
+        loop {
+            let online = matches!(self.mode, Mode::Online(_));
+            if (online || self.submit_job.is_some()) && !self.events.poll(TICK)? {
+                let mut changed = match &mut self.mode {
+                    Mode::Online(opponent) => opponent.update(),
+                    _ => false,
+                };
+
+                if let Some(job) = &self.submit_job {
+                    match job.try_recv() {
+                        Ok(submitted) => {
+                            self.submitted = Some(submitted);
+                            self.submit_job = None;
+                            changed = true;
+                        }
+                        Err(TryRecvError::Disconnected) => self.submit_job = None,
+                        Err(TryRecvError::Empty) => {}
+                    }
+                }
+
+                if changed {
+                    self.draw_end_screen(message, details)?;
+                }
+
+                continue;
+            }
+
+            let event = self.events.read()?;
+
+            match event {
+                Event::Key(key) if input::is_interrupt(&key) => return Ok(false),
+                Event::Key(key) => match key.code {
+                    KeyCode::Char(char) => match char {
+                        // return false because we don't want to restart
+                        'q' => return Ok(false),
+                        // return true because we want to restart
+                        'r' if !online => return Ok(true),
+                        's' => {
+                            self.share()?;
+                            self.draw_end_screen(message, details)?;
+                        }
+                        'a' if self.exploded().is_some() => {
+                            self.show_analysis()?;
+                            self.draw_end_screen(message, details)?;
+                        }
+                        #[cfg(feature = "qr")]
+                        'c' if self.replay.seed.is_some() => {
+                            self.show_seed_code()?;
+                            self.draw_end_screen(message, details)?;
+                        }
+                        _ => continue,
+                    },
+                    _ => continue,
+                },
+                _ => continue,
+            }
+        }
+    }
+
+    // the whole board with the message and anything else to say under it
+    fn draw_end_screen(&mut self, message: &str, details: &[String]) -> Result<()> {
+        // the message goes at the bottom of the board
+        self.footer = vec![Spans::from(ui::styled(message, &self.config.theme.overlay))];
+        self.footer
+            .extend(details.iter().map(|line| Spans::from(line.clone())));
+        self.footer.extend(self.submitted.clone().map(Spans::from));
+
+        // a game code's board is the same wherever the first click went, so it's passed on as it is
+        self.footer
+            .push(Spans::from("press s to share your result"));
+
+        if self.exploded().is_some() {
+            self.footer.push(Spans::from(
+                "press a to see whether that mine could have been avoided",
+            ));
+        }
+
+        if let Some(code) = self.code.or_else(|| GameCode::from_replay(&self.replay)) {
+            self.footer.push(Spans::from(format!(
+                "code {code}, play this board again with --code {code}"
+            )));
+        }
+
+        #[cfg(feature = "qr")]
+        if self.replay.seed.is_some() {
+            self.footer
+                .push(Spans::from("press c to show the seed as a QR code"));
+        }
+
+        self.draw_board()
+    }
+
+    // every key as it's set up right now and how flags and chording work, over the board until a key is pressed
+    fn show_help(&mut self) -> Result<()> {
+        let config = &self.config;
+        let mark = match (config.no_flags, config.strict_flags) {
+            (true, _) => "m - nothing, flagging is off for this no-flag run".to_string(),
+            (false, true) => format!("m - flag or unflag a cell, up to {} flags", self.num_mines),
+            (false, false) => "m - flag or unflag a cell".to_string(),
+        };
+        let chording = match config.assist {
+            Assist::Off => "chording is off, turn it on with assist: chord in the config",
+            Assist::Chord => "chording is on: a number with all its flags opens its other cells, even if a flag is wrong",
+            Assist::Solver => "the solver is on: every cell it can prove is safe opens after each move",
+        };
+
+        // each line with whether it's a heading, which gets highlighted
+        let lines = [
+            ("Keys".to_string(), true),
+            (
+                format!("    {} - move the cursor", config.movement.keys()),
+                false,
+            ),
+            (
+                format!(
+                    "    shift + direction - move {} cells",
+                    input::JUMP_DISTANCE
+                ),
+                false,
+            ),
+            (
+                "    number + direction - move that many cells".to_string(),
+                false,
+            ),
+            (
+                "    page up/page down/home/end - jump to the edge".to_string(),
+                false,
+            ),
+            ("    enter/space - uncover the cell".to_string(), false),
+            (format!("    {mark}"), false),
+            ("    r - restart".to_string(), false),
+            ("    g - give up and see the solution".to_string(), false),
+            ("    v - save the game to a slot".to_string(), false),
+            ("    ? - this help".to_string(), false),
+            ("    q/ctrl+c - quit".to_string(), false),
+            (String::new(), false),
+            ("Rules".to_string(), true),
+            (
+                "    a number is how many of the 8 cells around it are mines".to_string(),
+                false,
+            ),
+            (
+                "    uncover every cell that isn't a mine to win".to_string(),
+                false,
+            ),
+            (
+                "    the first cell you uncover is never a mine".to_string(),
+                false,
+            ),
+            (
+                "    flags are reminders, uncovering a flagged cell still opens it".to_string(),
+                false,
+            ),
+            (format!("    {chording}"), false),
+            (String::new(), false),
+            ("press any key to go back".to_string(), false),
+        ];
+
+        let theme = &self.config.theme;
+        let lines = lines
+            .into_iter()
+            .map(|(line, heading)| match heading {
+                true => Spans::from(ui::styled(line, &theme.menu_highlight)),
+                false => Spans::from(ui::styled(line, &theme.menu)),
+            })
+            .collect();
+
+        self.screen.page(self.config.safe_area().top, lines)?;
+
+        while !matches!(self.events.read()?, Event::Key(_)) {}
+
+        self.draw_board()
+    }
+
+    // things that move on their own between keypresses
+    fn tick(&mut self) -> Result<Option<bool>> {
+        // running out of time loses a timed challenge the same as a mine would
+        if self
+            .time_limit
+            .is_some_and(|limit| self.elapsed().is_some_and(|elapsed| elapsed >= limit))
+        {
+            return self.time_up().map(Some);
+        }
+
+        if let Some(rating) = self.rating_job.as_ref().and_then(|job| job.try_recv().ok()) {
+            self.rating = Some(rating);
+            self.rating_job = None;
+        }
+
+        if let Mode::Online(opponent) = &mut self.mode {
+            opponent.update();
+        }
+
+        self.autosave()?;
+
+        self.emit(GameEvent::TimerTick);
+        self.show_events()?;
+
+        Ok(None)
+    }
+
+    // the clock's run down to nothing on a timed challenge
+    fn time_up(&mut self) -> Result<bool> {
+        if let Mode::Online(opponent) = &mut self.mode {
+            opponent.report_lost();
+        }
+
+        self.finish(Outcome::Lost)?;
+
+        let message = match self.mode {
+            Mode::Online(_) => "Time's up! press q to quit",
+            _ => "Time's up! press r to try again and q to quit",
+        };
+
+        self.end_screen(message, &[self.result_line()])
+    }
+
+    // ask a yes or no question under the board, anything other than y (or another ctrl+c) counts as no
+    // say something under the board until the next key press
+    fn notice(&mut self, text: &str) -> Result<()> {
+        self.footer = vec![Spans::from(ui::styled(text, &self.config.theme.overlay))];
+        self.notice = true;
+        self.draw_board()
+    }
+
+    fn clear_notice(&mut self) -> Result<()> {
+        if std::mem::take(&mut self.notice) {
+            self.footer.clear();
+            self.draw_board()?;
+        }
+
+        Ok(())
+    }
+
+    fn confirm(&mut self, question: &str) -> Result<bool> {
+        self.footer = vec![Spans::from(ui::styled(
+            question,
+            &self.config.theme.overlay,
+        ))];
+        self.draw_board()?;
+
+        let answer = loop {
+            if let Event::Key(key) = self.events.read()? {
+                break input::is_interrupt(&key) || matches!(key.code, KeyCode::Char('y' | 'Y'));
+            }
+        };
+
+        self.footer.clear();
+        self.draw_board()?;
+
+        Ok(answer)
+    }
+
+    // the seed as a QR code taking up the whole screen, so it can be scanned off the terminal with a phone
+    #[cfg(feature = "qr")]
+    fn show_seed_code(&mut self) -> Result<()> {
+        let seed = match self.replay.seed {
+            Some(seed) => seed.to_string(),
+            None => return Ok(()),
+        };
+
+        let mut lines: Vec<Spans> = crate::qr::render(&seed)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| Spans::from(line.to_string()))
+            .collect();
+        lines.push(Spans::from(format!(
+            "seed {seed}, press any key to go back"
+        )));
+        self.screen.page(self.config.safe_area().top, lines)?;
+
+        while !matches!(self.events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // what the solver makes of a lost game: whether the last click was a forced guess or a blunder, and every time a
+    // safe cell was there to be found and something riskier got clicked instead
+    fn show_analysis(&mut self) -> Result<()> {
+        let analysis = analysis::analyse(&self.replay);
+        let theme = &self.config.theme;
+        let mut lines = vec![
+            Spans::from(ui::styled("What went wrong", &theme.menu_highlight)),
+            Spans::default(),
+        ];
+
+        let verdict = analysis
+            .verdict()
+            .unwrap_or_else(|| "the mine that went off isn't in the replay".to_string());
+        lines.push(Spans::from(ui::styled(verdict, &theme.menu)));
+        lines.push(Spans::default());
+
+        let missed = analysis.missed();
+        let heading = match missed.len() {
+            0 => "No missed deductions, every risk you took was forced".to_string(),
+            1 => "1 missed deduction, a risk you didn't have to take:".to_string(),
+            n => format!("{n} missed deductions, risks you didn't have to take:"),
+        };
+        lines.push(Spans::from(ui::styled(heading, &theme.menu_highlight)));
+        lines.extend(
+            missed
+                .into_iter()
+                .map(|line| Spans::from(ui::styled(format!("    {line}"), &theme.menu))),
+        );
+
+        lines.push(Spans::default());
+        lines.push(Spans::from(ui::styled(
+            "--report writes all of this up for a replay, press any key to go back",
+            &theme.menu,
+        )));
+        self.screen.page(self.config.safe_area().top, lines)?;
+
+        while !matches!(self.events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // the emoji summary of the game taking up the whole screen, copied to the clipboard if it can be
+    fn share(&mut self) -> Result<()> {
+        let ending = match (self.has_won(), self.exploded()) {
+            (true, _) => Ending::Won,
+            (false, Some(_)) => Ending::Lost,
+            (false, None) => Ending::GaveUp,
+        };
+        let name = match self.mode.daily() {
+            Some(day) => format!("Daily {}", daily::date(day)),
+            None => level_name(self.level).to_string(),
+        };
+        let text = share::summary(self.round.board(), &name, ending, self.replay.duration());
+
+        let copied = match share::copy(&text) {
+            Ok(()) => "copied to the clipboard".to_string(),
+            Err(error) => format!("couldn't copy it: {error}"),
+        };
+
+        let mut lines: Vec<Spans> = text
+            .lines()
+            .map(|line| Spans::from(line.to_string()))
+            .collect();
+        lines.push(Spans::default());
+        lines.push(Spans::from(format!(
+            "{copied}, it'll be printed again when you quit. press any key to go back"
+        )));
+        self.screen.page(self.config.safe_area().top, lines)?;
+        self.shared.push(text);
+
+        while !matches!(self.events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // every result shared this session
+    pub fn shared(&self) -> &[String] {
+        &self.shared
+    }
+
+    fn has_won(&mut self) -> bool {
+        self.round.standing() == Standing::Won
+    }
+
+    // how the game went for the end screen: the time, how much of the board's 3BV got done and how many clicks it took
+    fn result_line(&self) -> String {
+        let (solved, total) = self.round.board().three_bv();
+        let efficiency = (solved * 100).checked_div(self.clicks).unwrap_or(0);
+
+        let places = if self.speedrun { 3 } else { 1 };
+        format!(
+            "time {:.places$}s | 3BV {solved}/{total} | {} clicks | {efficiency}% efficiency",
+            self.replay.duration().as_secs_f64(),
+            self.clicks,
+        )
+    }
+
+    // the mine that's been uncovered, if one has
+    fn exploded(&self) -> Option<(usize, usize)> {
+        self.round.board().positions().find(|&(x, y)| {
+            self.round.board().is_mine(x, y) && self.round.board().is_uncovered(x, y)
+        })
+    }
+
+    fn num_safe_cells(&self) -> usize {
+        (self.width * self.height) - self.num_mines
+    }
+
+    fn num_uncovered_cells(&self) -> usize {
+        self.round.board().num_uncovered()
+    }
+
+    // say that something's happened. the replay hears about it straight away, so the time and the number of cells
+    // uncovered are right for each action, everything else waits for show_events()
+    fn emit(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::CellRevealed { cell, .. } => self.record(ActionKind::Reveal, cell),
+            GameEvent::Flagged { cell, .. } => self.record(ActionKind::Mark, cell),
+            _ => {}
+        }
+
+        self.happened.push(event);
+    }
+
+    // catch the screen and the sounds up with everything that's happened since last time
+    fn show_events(&mut self) -> Result<()> {
+        let happened = std::mem::take(&mut self.happened);
+
+        // a mine or a win gets its own sound instead of the reveal that caused it
+        let ended = happened
+            .iter()
+            .any(|event| matches!(event, GameEvent::Exploded { .. } | GameEvent::Won));
+        let mut opened = Vec::new();
+        let mut revealed = false;
+
+        for event in happened {
+            match event {
+                GameEvent::CellRevealed {
+                    opened: cells,
+                    assisted,
+                    ..
+                } => {
+                    opened.extend(cells);
+                    revealed |= !assisted;
+                }
+                GameEvent::Flagged { .. } => self.ring(Sound::Flag)?,
+                GameEvent::Exploded { .. } => self.ring(Sound::Explosion)?,
+                GameEvent::Won => self.ring(Sound::Win)?,
+                GameEvent::TimerTick => self.draw_board()?,
+            }
+        }
+
+        // everything one key press opened, the assist's cells included, plays back as one opening
+        if self.config.animate_reveals {
+            self.animate_reveal(&opened)?;
+        }
+
+        if revealed && !ended {
+            self.ring(Sound::Reveal)?;
+        }
+
+        Ok(())
+    }
+
+    // add something done at (x, y) to the replay
+    fn record(&mut self, kind: ActionKind, (x, y): (usize, usize)) {
+        let time = self
+            .started
+            .map(|started| started.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+
+        self.replay.actions.push(Action {
+            time,
+            kind,
+            x,
+            y,
+            uncovered: self.num_uncovered_cells(),
+        });
+    }
+
+    // open everything the assist level allows, over and over until it runs out of cells or sets off a mine
+    fn assist(&mut self) {
+        if !self.is_touched {
+            return;
+        }
+
+        loop {
+            let cells = match self.config.assist {
+                Assist::Off => return,
+                Assist::Chord => self.chordable_cells(),
+                Assist::Solver => Solver::new(self.round.board()).safe_cells(),
+            };
+
+            // flags are left alone, even ones the solver knows are wrong
+            let cells: Vec<_> = cells
+                .into_iter()
+                .filter(|&(x, y)| {
+                    !self.round.board().is_uncovered(x, y) && !self.round.board().is_flagged(x, y)
+                })
+                .collect();
+
+            if cells.is_empty() {
+                return;
+            }
+
+            for cell in cells {
+                if self.round.board().is_uncovered(cell.0, cell.1) {
+                    continue;
+                }
+
+                self.reveal(cell, true);
+
+                if self.exploded().is_some() {
+                    return;
+                }
+            }
+        }
+    }
+
+    // the covered cells around every number that already has as many flags around it as it shows
+    fn chordable_cells(&self) -> Vec<(usize, usize)> {
+        let board = self.round.board();
+        let mut cells = Vec::new();
+
+        for (x, y) in board.positions() {
+            let cell = board.cell(x, y);
+
+            if !cell.is_uncovered() || cell.is_mine() || cell.adjacent() == 0 {
+                continue;
+            }
+
+            if board
+                .neighbors(x, y)
+                .filter(|&(x, y)| board.is_flagged(x, y))
+                .count()
+                == cell.adjacent()
+            {
+                cells.extend(board.neighbors(x, y));
+            }
+        }
+
+        cells
+    }
+
+    // ring the terminal bell if the theme wants to hear about this
+    fn ring(&mut self, sound: Sound) -> Result<()> {
+        if self.config.theme.bell.rings(sound) {
+            self.screen.out().execute(Print('\x07'))?;
+        }
+
+        Ok(())
+    }
+
+    // keep a record of a game that's just been won or lost
+    fn finish(&mut self, outcome: Outcome) -> Result<()> {
+        autosave::clear()?;
+
+        if self.is_ranked() {
+            self.replay.save_last()?;
+            Stats::record(&self.replay, outcome)?;
+        }
+
+        // games played by someone at the keyboard, recorded and scripted input would crowd them out
+        if self.reads_terminal() {
+            History::record(&self.replay, outcome)?;
+        }
+
+        self.session.add(&self.replay, outcome);
+
+        // races are everyone's boards, so they don't leave ghosts
+        if let Some(code) = self
+            .code
+            .or_else(|| GameCode::from_replay(&self.replay))
+            .filter(|_| !matches!(self.mode, Mode::Race(_)))
+        {
+            ghost::record(code, &self.replay, outcome == Outcome::Won)?;
+        }
+
+        Ok(())
+    }
+
+    // count a game that was left before it ended, if it was ever started
+    fn abandon(&mut self) -> Result<()> {
+        autosave::clear()?;
+
+        if self.is_touched {
+            self.session.add(&self.replay, Outcome::Abandoned);
+        }
+
+        self.end_marathon()?;
+        Ok(())
+    }
+
+    // whether the game goes in the stats: custom boards aren't the same as any level, marathon boards are timed as
+    // part of the whole run and extra lives make it a different game
+    fn is_ranked(&self) -> bool {
+        self.level != 0
+            && !matches!(self.mode, Mode::Marathon(_) | Mode::Race(_))
+            && self.lives == 1
+            && self.placement == Placement::Uniform
+            && self.wanted_rating.is_none()
+    }
+
+    fn lives_left(&self) -> u8 {
+        self.lives.saturating_sub(self.hit.len() as u8)
+    }
+
+    // cover every mine that's just gone off back up and flag it, so it counts like any other flag for chording
+    fn survive(&mut self) {
+        let exploded: Vec<_> = self
+            .mine_cells()
+            .into_iter()
+            .filter(|&(x, y)| self.round.board().is_uncovered(x, y))
+            .collect();
+
+        for (x, y) in exploded {
+            let cell = self.round.board_mut().cell_mut(x, y);
+            cell.set_uncovered(false);
+            cell.set_flagged(true);
+            self.hit.push((x, y));
+        }
+    }
+
+    // when the clock on the status bar started: the first reveal of the game, or of the whole marathon
+    fn clock(&self) -> Option<Instant> {
+        self.mode
+            .marathon()
+            .and_then(|marathon| marathon.started)
+            .or(self.started)
+    }
+
+    // how long the clock's been running, stopped where it was while the game's paused
+    fn elapsed(&self) -> Option<Duration> {
+        let started = self.clock()?;
+        Some(
+            self.paused
+                .unwrap_or_else(Instant::now)
+                .saturating_duration_since(started),
+        )
+    }
+
+    // stop the clock because the terminal's gone into the background, if the config wants it and it's running. races
+    // keep going, the other player's clock doesn't stop
+    fn pause(&mut self) -> Result<()> {
+        if !self.config.pause_on_focus_loss
+            || self.paused.is_some()
+            || self.clock().is_none()
+            || self.mode.is_race()
+        {
+            return Ok(());
+        }
+
+        self.paused = Some(Instant::now());
+        self.notice("paused while the terminal's in the background, the clock starts again when you come back")
+    }
+
+    // start the clock again from where pause() stopped it
+    fn resume(&mut self) -> Result<()> {
+        let away = match self.paused.take() {
+            Some(paused) => paused.elapsed(),
+            None => return Ok(()),
+        };
+
+        self.started = self.started.map(|started| started + away);
+        if let Mode::Marathon(marathon) = &mut self.mode {
+            marathon.started = marathon.started.map(|started| started + away);
+        }
+
+        self.clear_notice()
+    }
+
+    // put the marathon that's going in the stats and start over, returns how it went if there was one
+    fn end_marathon(&mut self) -> Result<Option<String>> {
+        let marathon = match &mut self.mode {
+            Mode::Marathon(marathon) => marathon,
+            _ => return Ok(None),
+        };
+
+        let started = match marathon.started.take() {
+            Some(started) => started,
+            None => return Ok(None),
+        };
+
+        let (boards, duration) = (std::mem::take(&mut marathon.boards), started.elapsed());
+        Stats::record_marathon(boards, duration, marathon.ramp)?;
+
+        let plural = if boards == 1 { "" } else { "s" };
+        Ok(Some(format!(
+            "marathon: {boards} board{plural} cleared in {:.1}s",
+            duration.as_secs_f64()
+        )))
+    }
+
+    // a race turn's over: say how it went on a page of its own so the board stays hidden, then hand the keyboard over,
+    // with the scores once both players have had a go. returns whether to carry on racing
+    fn end_turn(&mut self, time: Option<Duration>) -> Result<bool> {
+        let race = match &mut self.mode {
+            Mode::Race(race) => race,
+            _ => return Ok(false),
+        };
+
+        let (round, player) = (race.round(), race.player().to_string());
+        let handicap = race.handicaps[race.turn()];
+        let round_over = race.finish(time);
+        let theme = &self.config.theme;
+
+        let mut lines = vec![
+            Spans::from(ui::styled(format!("Round {round}"), &theme.menu_highlight)),
+            Spans::default(),
+            Spans::from(match time {
+                Some(time) if !handicap.is_zero() => format!(
+                    "{player} cleared it in {:.1}s, {:.1}s with their {}s handicap",
+                    time.as_secs_f64(),
+                    time.saturating_add(handicap).as_secs_f64(),
+                    handicap.as_secs()
+                ),
+                Some(time) => format!("{player} cleared it in {:.1}s", time.as_secs_f64()),
+                None => format!("{player} didn't clear it"),
+            }),
+        ];
+
+        if round_over {
+            lines.push(Spans::from(race.last_round()));
+            lines.push(Spans::default());
+            lines.extend(race.scoreboard().into_iter().map(Spans::from));
+            lines.push(Spans::default());
+            lines.push(Spans::from(format!(
+                "press any key for round {}, {} goes first. q ends the race",
+                race.round(),
+                race.player()
+            )));
+        } else {
+            lines.push(Spans::default());
+            lines.push(Spans::from(format!(
+                "hand over to {} and press any key when they're ready. q ends the race",
+                race.player()
+            )));
+        }
+
+        self.screen.page(self.config.safe_area().top, lines)?;
+
+        loop {
+            if let Event::Key(key) = self.events.read()? {
+                return Ok(!input::is_interrupt(&key) && key.code != KeyCode::Char('q'));
+            }
+        }
+    }
+
+    // send a daily challenge win to the online leaderboard if that's turned on, returns how it went. it goes by the same
+    // rules as the stats, and assisted wins stay off it like they do the high scores
+    fn submit_online(&mut self) {
+        let Some(day) = self.mode.daily().filter(|_| {
+            self.config.online.submit && self.is_ranked() && self.replay.assist == Assist::Off
+        }) else {
+            return;
+        };
+
+        // the leaderboard can take a while to answer, so the end screen's up in the meantime and says how it went
+        // once it's done. quitting before then gives up on it
+        let (sender, receiver) = mpsc::channel();
+        let online = self.config.online.clone();
+        let replay = self.replay.clone();
+
+        thread::spawn(move || {
+            let _ = sender.send(match online::submit(&online, &replay, day) {
+                Ok(()) => {
+                    "sent to the online leaderboard, o on the level menu shows today's rankings"
+                        .to_string()
+                }
+                Err(error) => format!("couldn't send it to the online leaderboard: {error}"),
+            });
+        });
+
+        self.submitted = Some("sending it to the online leaderboard...".to_string());
+        self.submit_job = Some(receiver);
+    }
+
+    // let whoever we're racing over the network know how much of the board we've got through
+    fn report_progress(&mut self) {
+        let revealed =
+            (self.round.board().num_uncovered() * 100 / self.num_safe_cells().max(1)) as u8;
+        let flags = self.round.board().num_flagged();
+
+        if let Mode::Online(opponent) = &mut self.mode {
+            opponent.report(revealed, flags);
+        }
+    }
+
+    // the race's scores to print once the terminal is back to normal, if a round was finished
+    pub fn race_standings(&self) -> Option<String> {
+        match &self.mode {
+            Mode::Race(race) if race.is_started() => Some(race.scoreboard().join("\n")),
+            _ => None,
+        }
+    }
+
+    // the session summary to print once the terminal is back to normal, if it's turned on and there's anything to say
+    // how the session went, shown on the way out once more than one game's been played, until a key is pressed
+    pub fn show_session(&mut self) -> Result<()> {
+        // played back input has nothing left to press a key with
+        if !self.config.session_summary || self.session.len() < 2 || !self.reads_terminal() {
+            return Ok(());
+        }
+
+        let theme = &self.config.theme;
+        let session = &self.session;
+        let seconds = |duration: Duration| format!("{:.1}s", duration.as_secs_f64());
+        let total = session.total_time();
+        let total = match total.as_secs() {
+            0..=59 => seconds(total),
+            secs => format!("{}m {:02}s", secs / 60, secs % 60),
+        };
+        let best = match session.best() {
+            Some(best) => format!("{} on {}", seconds(best.duration), level_name(best.level)),
+            None => "-".to_string(),
+        };
+
+        let rows = [
+            ("games played", session.len().to_string()),
+            (
+                "won",
+                format!(
+                    "{} ({}%)",
+                    session.won(),
+                    session.won() * 100 / session.len()
+                ),
+            ),
+            ("best time", best),
+            ("total time", total),
+        ];
+
+        let mut lines = vec![
+            Spans::from(ui::styled("This session", &theme.menu_highlight)),
+            Spans::default(),
+        ];
+        lines.extend(rows.iter().map(|(name, value)| {
+            Spans::from(ui::styled(format!("{name:<14}{value}"), &theme.menu))
+        }));
+        lines.push(Spans::default());
+        lines.push(Spans::from(ui::styled(
+            "press any key to quit",
+            &theme.menu,
+        )));
+
+        self.screen.page(self.config.safe_area().top, lines)?;
+
+        while !matches!(self.events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    pub fn exit_summary(&self) -> Option<String> {
+        match self.config.exit_summary && !self.session.is_empty() {
+            true => Some(self.session.summary()),
+            false => None,
+        }
+    }
+
+    // keep this game's replay around if it's the fastest win on this level, returns whether it was
+    fn save_if_best(&mut self) -> Result<bool> {
+        let is_best = match &self.best {
+            Some(best) => self.replay.duration() < best.duration(),
+            None => true,
+        };
+
+        if is_best {
+            self.replay.save_best()?;
+            self.best = Some(self.replay.clone());
+        }
+
+        Ok(is_best)
+    }
+
+    // ask who set the new best time under the board. enter puts it down and escape leaves it off the leaderboard
+    fn ask_name(&mut self) -> Result<Option<String>> {
+        // a profile's usually named after whoever's playing
+        let mut name = paths::profile()
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_default();
+        name.truncate(leaderboard::NAME_LENGTH);
+
+        self.ask("New best time! your name: ", name, leaderboard::NAME_LENGTH)
+    }
+
+    // have something typed in under the board, starting from `name` and up to `length` characters. escape (or ctrl+c)
+    // gives None
+    fn ask(&mut self, prompt: &str, mut name: String, length: usize) -> Result<Option<String>> {
+        let row = self.footer_row() + 1;
+
+        let name = loop {
+            self.footer = vec![Spans::from(vec![
+                ui::styled(prompt, &self.config.theme.overlay),
+                Span::raw(name.clone()),
+            ])];
+            self.cursor = Cursor::At(
+                Spans::from(prompt).width() as u16 + Span::raw(name.as_str()).width() as u16,
+                row,
+            );
+            self.draw_board()?;
+
+            if let Event::Key(key) = self.events.read()? {
+                match key.code {
+                    _ if input::is_interrupt(&key) => break None,
+                    KeyCode::Esc => break None,
+                    KeyCode::Enter if !name.trim().is_empty() => {
+                        break Some(name.trim().to_string())
+                    }
+                    KeyCode::Backspace => {
+                        name.pop();
+                    }
+                    KeyCode::Char(char) if !char.is_control() && name.chars().count() < length => {
+                        name.push(char)
+                    }
+                    _ => (),
+                }
+            }
+        };
+
+        self.footer.clear();
+        self.cursor = Cursor::Hidden;
+        self.draw_board()?;
+
+        Ok(name)
+    }
+
+    // uncover a cell in play, by the player or the assist
+    fn reveal(&mut self, (x, y): (usize, usize), assisted: bool) {
+        self.play(player::Action::Reveal(x, y), assisted);
+    }
+
+    // make a move by the rules and pass on everything it did. a move the rules won't take, like flagging an
+    // uncovered cell, doesn't do anything
+    fn play(&mut self, action: player::Action, assisted: bool) {
+        for event in self.round.apply(action).unwrap_or_default() {
+            self.emit(match event {
+                GameEvent::CellRevealed { cell, opened, .. } => GameEvent::CellRevealed {
+                    cell,
+                    opened,
+                    assisted,
+                },
+                event => event,
+            });
+        }
+    }
+
+    // once every covered cell has to be a mine, flag the lot
+    fn flag_remaining_mines(&mut self) {
+        let covered = self.covered_cells();
+
+        if covered.len() == self.num_mines {
+            for (x, y) in covered {
+                self.round.board_mut().cell_mut(x, y).set_flagged(true);
+            }
+        }
+    }
+
+    fn covered_cells(&self) -> Vec<(usize, usize)> {
+        self.round
+            .board()
+            .positions()
+            .filter(|&(x, y)| !self.round.board().is_uncovered(x, y))
+            .collect()
+    }
+
+    // play an opening back spreading out from the cursor a ring at a time instead of showing it all at once
+    fn animate_reveal(&mut self, opened: &[(usize, usize)]) -> Result<()> {
+        let (cx, cy) = self.selection;
+        let distance = |&(x, y): &(usize, usize)| x.abs_diff(cx).max(y.abs_diff(cy));
+        let furthest = opened.iter().map(distance).max().unwrap_or(0);
+
+        // a single cell or a tiny opening isn't worth waiting for
+        if furthest < 2 {
+            return Ok(());
+        }
+
+        // big openings take more rings per frame so they don't take any longer
+        let step = furthest.div_ceil(CASCADE_FRAMES);
+
+        for &(x, y) in opened {
+            self.round.board_mut().cell_mut(x, y).set_uncovered(false);
+        }
+
+        let mut reach = 0;
+
+        while reach < furthest {
+            reach = (reach + step).min(furthest);
+
+            for &(x, y) in opened.iter().filter(|cell| distance(cell) <= reach) {
+                self.round.board_mut().cell_mut(x, y).set_uncovered(true);
+            }
+
+            self.draw_board()?;
+            thread::sleep(CASCADE_FRAME_TIME);
+        }
+
+        Ok(())
+    }
+
+    // flash the mine that went off, then set the rest off one ring at a time spreading out from it
+    fn animate_explosion(&mut self) -> Result<()> {
+        self.cursor = Cursor::Hidden;
+        self.draw_board()?;
+
+        let exploded = self.selection;
+        let glyph = self.config.theme.board.glyph(Tile::Mine);
+
+        for flash in 0..6 {
+            match flash % 2 {
+                0 => self.draw_cell(exploded, glyph.clone().reverse())?,
+                _ => self.draw_cell(exploded, glyph.clone())?,
+            };
+
+            thread::sleep(FLASH_TIME);
+        }
+
+        let (cx, cy) = exploded;
+        let distance = |&(x, y): &(usize, usize)| x.abs_diff(cx).max(y.abs_diff(cy));
+        let mines: Vec<(usize, usize)> = self
+            .mine_cells()
+            .into_iter()
+            .filter(|&cell| cell != exploded)
+            .collect();
+        let furthest = mines.iter().map(distance).max().unwrap_or(0);
+        let step = furthest.div_ceil(CASCADE_FRAMES).max(1);
+
+        let mut reach = 0;
+
+        while reach < furthest {
+            reach = (reach + step).min(furthest);
+
+            for &cell in mines
+                .iter()
+                .filter(|cell| (reach - step..=reach).contains(&distance(cell)))
+            {
+                self.drawn_over.insert(cell, ui::span(glyph.clone()));
+            }
+
+            self.draw_board()?;
+            thread::sleep(CASCADE_FRAME_TIME);
+        }
+
+        self.drawn_over.clear();
+
+        Ok(())
+    }
+
+    // plant a flag on every mine, then throw some confetti over the board
+    fn animate_victory(&mut self) -> Result<()> {
+        self.cursor = Cursor::Hidden;
+        self.draw_board()?;
+
+        let unflagged: Vec<(usize, usize)> = self
+            .mine_cells()
+            .into_iter()
+            .filter(|&(x, y)| !self.round.board().is_flagged(x, y))
+            .collect();
+
+        // however many there are, planting them all takes about the same time
+        let plant_time = FLAG_PLANTING_TIME / unflagged.len().max(1) as u32;
+
+        for (x, y) in unflagged {
+            self.round.board_mut().cell_mut(x, y).set_flagged(true);
+            self.draw_board()?;
+            thread::sleep(plant_time);
+        }
+
+        let confetti = self.config.theme.confetti.clone();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..CONFETTI_FRAMES {
+            self.drawn_over.clear();
+
+            for cell in self.round.board().positions() {
+                let pieces = ['*', '+', 'o', '.'];
+
+                if let (true, Some(style)) = (rng.gen_bool(0.2), confetti.choose(&mut rng)) {
+                    self.drawn_over.insert(
+                        cell,
+                        ui::styled(pieces[rng.gen_range(0..pieces.len())], style),
+                    );
+                }
+            }
+
+            self.draw_board()?;
+            thread::sleep(CONFETTI_FRAME_TIME);
+        }
+
+        self.drawn_over.clear();
+
+        Ok(())
+    }
+
+    fn mine_cells(&self) -> Vec<(usize, usize)> {
+        self.round
+            .board()
+            .positions()
+            .filter(|&(x, y)| self.round.board().is_mine(x, y))
+            .collect()
+    }
+
+    // a simple shortcut function that gives us the cell the cursor is at
+    fn get_current_cell(&self) -> CellState {
+        self.round.board().cell(self.selection.0, self.selection.1)
+    }
+
+    // draw a cell as something else until the animation's done with it
+    fn draw_cell<D: Display>(
+        &mut self,
+        cell: (usize, usize),
+        glyph: StyledContent<D>,
+    ) -> Result<()> {
+        self.drawn_over.insert(cell, ui::span(glyph));
+        self.draw_board()
+    }
+
+    // lay mines on the board, making sure there aren't any on or around the cursor so the first click opens something up
+    fn populate_board(&mut self) -> Result<()> {
+        // a loaded board already has its mines
+        if self.fixed.is_some() {
+            return Ok(());
+        }
+
+        let settings = self.settings();
+        let (mut seed, mut mines) = match self.code {
+            Some(code) => (code.seed, code.mines()),
+            None if settings.placement == Placement::Uniform => {
+                let (seed, mines) = self.cache.take(settings);
+                (
+                    seed,
+                    generator::shift(&mines, settings, settings.start(), self.selection),
+                )
+            }
+            None => {
+                let seed = rand::random();
+                (seed, generator::layout(settings, seed, self.selection))
+            }
+        };
+
+        // keep trying new layouts until one rates the way we want, for as long as it's worth waiting
+        if let Some(wanted) = self.wanted_rating.filter(|_| self.code.is_none()) {
+            let started = Instant::now();
+
+            loop {
+                let rating = Rating::of(
+                    &Board::with_mines(self.width, self.height, &mines),
+                    self.selection,
+                );
+                self.rating = Some(rating);
+
+                if rating.band() == wanted {
+                    break;
+                } else if started.elapsed() > REROLL_FOR {
+                    self.notice(&format!(
+                        "couldn't find a {wanted} board in time, this one's {}",
+                        rating.band()
+                    ))?;
+                    break;
+                }
+
+                seed = rand::random();
+                mines = generator::layout(settings, seed, self.selection);
+            }
+        }
+
+        // remember where the mines went so the game can be replayed
+        self.replay.seed = Some(seed);
+        self.replay.mines = mines.clone();
+
+        self.round.place_mines(&mines);
+
+        if self.rating.is_none() {
+            self.rating_job = Some(Rating::in_background(
+                self.round.board().clone(),
+                self.selection,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            width: self.width,
+            height: self.height,
+            mines: self.num_mines,
+            placement: self.placement,
+        }
+    }
+
+    // draw the whole screen from the game's internal state: the board, the status bar and anything said under it.
+    // ratatui only sends the cells that changed since the last frame, and all of it goes out in one flush
+    fn draw_board(&mut self) -> Result<()> {
+        let board_row = self.board_row();
+        let status_row = match self.config.status_bar {
+            StatusPosition::Top => self.config.safe_area().top,
+            StatusPosition::Bottom => self.footer_row(),
+        };
+        let status = self.status().line(&self.config.theme);
+        let footer_row = self.footer_row() + 1;
+        let footer = self.footer.clone();
+
+        // the ghost goes under anything else drawn over the board, and it's gone once the whole board's shown
+        // with the board covered up while the game's paused, there's nothing on it to draw over
+        let hidden = self.paused.is_some() && self.config.hide_board_when_paused;
+        let blank = Board::new(self.width, self.height);
+
+        let elapsed = self.elapsed();
+        let drawn_over = match (&mut self.ghost, elapsed) {
+            _ if hidden => HashMap::new(),
+            (Some(ghost), Some(elapsed)) if !self.show_everything => {
+                ghost.catch_up(elapsed);
+                let mut drawn_over = ghost.overlay(self.round.board(), &self.config.theme);
+                drawn_over.extend(self.drawn_over.clone());
+                drawn_over
+            }
+            _ => self.drawn_over.clone(),
+        };
+
+        let widget = MinesweeperWidget::new(
+            if hidden { &blank } else { self.round.board() },
+            &self.config.theme,
+        )
+        .show_everything(self.show_everything)
+        .exploded(&self.hit)
+        .highlighted(&self.solution)
+        .drawn_over(&drawn_over);
+
+        // spectators get the board as it's drawn, so they see no more than the player
+        if let Some(spectators) = &self.spectators {
+            spectators.send(&Frame {
+                rows: spectate::rows(&widget, (self.width, self.height)),
+                selection: self.selection,
+                status: spectate::plain(&status),
+                footer: footer.iter().map(spectate::plain).collect(),
+            });
+        }
+
+        let mut state = MinesweeperState::new(self.selection);
+        let cursor = if hidden { Cursor::Hidden } else { self.cursor };
+        let panel = match self.speedrun {
+            true => self.splits_panel(),
+            false => self.panel.clone(),
+        };
+        let (panel_left, panel_rows) = (widget.columns() + PANEL_GAP, widget.rows());
+
+        self.screen.draw(|frame| {
+            let area = frame.size();
+
+            if !panel.is_empty() && panel_left < area.right() {
+                let beside =
+                    Rect::new(panel_left, board_row, area.right() - panel_left, panel_rows);
+                frame.render_widget(Paragraph::new(panel), beside.intersection(area));
+            }
+
+            frame.render_stateful_widget(widget, ui::below(area, board_row), &mut state);
+            frame.render_widget(Paragraph::new(status), ui::below(area, status_row));
+            frame.render_widget(
+                Paragraph::new(footer).wrap(Wrap { trim: false }),
+                ui::below(area, footer_row),
+            );
+
+            let cursor = match cursor {
+                Cursor::Selection => state.cursor(),
+                Cursor::At(column, row) => Some((column, row)),
+                Cursor::Hidden => None,
+            };
+
+            if let Some((column, row)) =
+                cursor.filter(|&(column, row)| column < area.right() && row < area.bottom())
+            {
+                frame.set_cursor(column, row);
+            }
+        })
+    }
+
+    // what the status bar should be showing right now
+    fn status(&self) -> Status {
+        // compare the moment we reached our current progress with when the best run got there
+        let last = self
+            .replay
+            .actions
+            .iter()
+            .rev()
+            .find(|action| action.kind == ActionKind::Reveal);
+        let pace = last.and_then(|last| {
+            let best = self.best.as_ref()?.pace_at(last.uncovered)?;
+            Some(last.time as i64 - best.as_millis() as i64)
+        });
+
+        Status {
+            difficulty: match self.mode {
+                Mode::Daily(_) => "Daily",
+                _ => level_name(self.level),
+            },
+            elapsed: self.elapsed().unwrap_or_default(),
+            time_limit: self.time_limit,
+            lives: (self.lives > 1).then(|| self.lives_left()),
+            rating: self.rating.map(|rating| rating.band()),
+            pace,
+            precise: self.speedrun,
+            mines: self.num_mines,
+            flags: self.round.board().num_flagged(),
+            opponent: match &self.mode {
+                Mode::Online(opponent) => Some(opponent.line()),
+                _ => None,
+            },
+            cursor: match self.config.theme.board.labels {
+                true => format!("{}{}", column_label(self.selection.0), self.selection.1 + 1),
+                false => format!("{},{}", self.selection.0 + 1, self.selection.1 + 1),
+            },
+        }
+    }
+
+    // the speedrun's splits so far, each with how far behind (+) or ahead (-) of the best run it was
+    fn splits_panel(&self) -> Vec<Spans<'static>> {
+        let theme = &self.config.theme;
+        let seconds = |time: Option<Duration>| {
+            time.map(|time| format!("{:.3}s", time.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let best = self.best.as_ref().map(Replay::splits).unwrap_or_default();
+
+        let mut lines = vec![Spans::from(ui::styled("Splits", &theme.menu_highlight))];
+
+        for ((name, split), best) in replay::SPLITS.iter().zip(self.replay.splits()).zip(best) {
+            let mut line = vec![ui::styled(
+                format!("{name:<14}{:>10}", seconds(split)),
+                &theme.menu,
+            )];
+
+            if let (Some(split), Some(best)) = (split, best) {
+                let behind = split.as_millis() as i64 - best.as_millis() as i64;
+                let style = if behind > 0 {
+                    &theme.pace_behind
+                } else {
+                    &theme.pace_ahead
+                };
+                line.push(ui::styled(
+                    format!(
+                        "  {}{:.3}",
+                        if behind > 0 { '+' } else { '-' },
+                        behind.abs() as f64 / 1000.0
+                    ),
+                    style,
+                ));
+            }
+
+            lines.push(Spans::from(line));
+        }
+
+        if let Some(best) = &self.best {
+            lines.push(Spans::from(ui::styled(
+                format!("{:<14}{:>10}", "best", seconds(Some(best.duration()))),
+                &theme.menu,
+            )));
+        }
+
+        lines
+    }
+
+    // how often the clock's redrawn while it's running
+    fn tick_length(&self) -> Duration {
+        match self.speedrun {
+            true => SPEEDRUN_TICK,
+            false => TICK,
+        }
+    }
+
+    // the line the board starts on, with its labels and frame
+    fn board_row(&self) -> u16 {
+        let status = match self.config.status_bar {
+            StatusPosition::Top => 1,
+            StatusPosition::Bottom => 0,
+        };
+
+        self.config.safe_area().top + status
+    }
+
+    // the first line below the board
+    fn footer_row(&self) -> u16 {
+        self.board_row() + MinesweeperWidget::new(self.round.board(), &self.config.theme).rows()
+    }
+
+    fn create_blank_board(&mut self) {
+        self.round = Round::with_board(match &self.fixed {
+            Some(board) => board.clone(),
+            None => Board::new(self.width, self.height),
+        });
+    }
+
+    // whether keys come straight from the terminal, so the game can be played on run_async()
+    pub fn reads_terminal(&self) -> bool {
+        self.events.is_terminal()
+    }
+
+    // the board as it is right now, for writing out after the game
+    pub fn board(&self) -> &Board {
+        self.round.board()
+    }
+
+    // the game picked from the history to watch, if that's what the level menu was left with. it's watched like
+    // --replay's is
+    pub fn take_watching(&mut self) -> Option<Replay> {
+        match std::mem::replace(&mut self.mode, Mode::Solo) {
+            Mode::Watching(replay) => Some(replay),
+            mode => {
+                self.mode = mode;
+                None
+            }
+        }
+    }
+
+    fn choose_level(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &mut Config,
+    ) -> Result<Choice> {
+        let mut level = 1;
+        let mut draw = true;
+
+        // loop on every keypress
+        loop {
+            if draw {
+                let theme = &config.theme;
+
+                // draw the menu, the line with our number in bold to show our selection
+                let mut lines: Vec<Spans> = MENU
+                    .lines()
+                    .map(|line| match line.contains(&format!("{}. ", level)) {
+                        true => Spans::from(ui::styled(line, &theme.menu_highlight)),
+                        false => Spans::from(ui::styled(line, &theme.menu)),
+                    })
+                    .collect();
+
+                // whose profile it is, once there's more than the one
+                if let Some(name) = paths::profile()
+                    .or_else(|| (!paths::profiles().is_empty()).then(|| "default".to_string()))
+                {
+                    lines.push(Spans::default());
+                    lines.push(Spans::from(ui::styled(
+                        format!("playing as {name}"),
+                        &theme.menu_highlight,
+                    )));
+                }
+
+                screen.page(config.safe_area().top, lines)?;
+            }
+
+            // get our event
+            // this blocks so the loop doesn't run constantly
+            let event = events.read()?;
+
+            // get our next level from the key event
+            level = match event {
+                Event::Key(key) if input::is_interrupt(&key) => Self::quit(),
+                Event::Key(key) => match key.code {
+                    KeyCode::Up => level - 1,
+                    KeyCode::Down => level + 1,
+                    KeyCode::Enter => break,
+                    KeyCode::Char(char) => match char {
+                        ' ' => break,
+                        '1' => 1,
+                        '2' => 2,
+                        '3' => 3,
+                        's' => {
+                            Self::show_stats(screen, events, config)?;
+                            level
+                        }
+                        'h' => {
+                            Self::show_leaderboard(screen, events, config)?;
+                            level
+                        }
+                        'o' => {
+                            Self::show_online_rankings(screen, events, config)?;
+                            level
+                        }
+                        'p' => {
+                            Self::choose_profile(screen, events, config)?;
+                            level
+                        }
+                        'r' => match Self::choose_history(screen, events, config)? {
+                            Some(choice) => return Ok(choice),
+                            None => level,
+                        },
+                        'l' => {
+                            let mut slots = Slots::load()?;
+
+                            match Self::choose_slot(screen, events, config, &mut slots, false)? {
+                                Some(slot) => {
+                                    return Ok(Choice::Saved(slots.slots.swap_remove(slot).game))
+                                }
+                                None => level,
+                            }
+                        }
+                        'q' => Self::quit(),
+                        _ => {
+                            draw = false;
+                            continue;
+                        }
+                    },
+                    _ => {
+                        draw = false;
+                        continue;
+                    }
+                },
+                _ => {
+                    draw = false;
+                    continue;
+                }
+            };
+
+            // if we try to set level as a level out of bounds set it back in bounds
+            level = level.clamp(1, 3);
+
+            draw = true;
+        }
+
+        // return our level :)
+        Ok(Choice::Level(level))
+    }
+
+    // switch to another player's profile or make a new one. the new profile's config file is loaded in place of the
+    // old one's, and whatever was changed from the command line stays behind with it
+    fn choose_profile(screen: &mut Screen, events: &mut Events, config: &mut Config) -> Result<()> {
+        let mut picked = 0;
+        // the new profile's name, as it's typed
+        let mut typing: Option<String> = None;
+        let mut error: Option<String> = None;
+
+        loop {
+            let theme = &config.theme;
+            let playing = paths::profile();
+
+            // the default profile first, then everyone else's and last a new one
+            let mut profiles = vec![None];
+            profiles.extend(paths::profiles().into_iter().map(Some));
+            picked = picked.min(profiles.len());
+
+            let mut lines = vec![
+                Spans::from(ui::styled("Profiles", &theme.menu_highlight)),
+                Spans::default(),
+            ];
+
+            for (i, profile) in profiles.iter().enumerate() {
+                let name = profile.as_deref().unwrap_or("default");
+                let now = if *profile == playing {
+                    ", playing now"
+                } else {
+                    ""
+                };
+                let (marker, style) = if i == picked {
+                    ('>', &theme.menu_highlight)
+                } else {
+                    (' ', &theme.menu)
+                };
+                lines.push(Spans::from(ui::styled(
+                    format!("{marker} {name}{now}"),
+                    style,
+                )));
+            }
+
+            let (marker, style) = if picked == profiles.len() {
+                ('>', &theme.menu_highlight)
+            } else {
+                (' ', &theme.menu)
+            };
+            let new = match &typing {
+                Some(name) => format!("{marker} new profile: {name}_"),
+                None => format!("{marker} new profile"),
+            };
+            lines.push(Spans::from(ui::styled(new, style)));
+
+            lines.push(Spans::default());
+            if let Some(error) = &error {
+                lines.push(Spans::from(ui::styled(error.clone(), &theme.overlay)));
+            }
+            let help = match typing {
+                Some(_) => "type the new profile's name, enter makes it and escape goes back",
+                None => "up/down picks a profile, enter switches to it and escape goes back",
+            };
+            lines.push(Spans::from(ui::styled(help, &theme.menu)));
+            screen.page(config.safe_area().top, lines)?;
+
+            let key = match events.read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            let switch_to = match (&mut typing, key.code) {
+                _ if input::is_interrupt(&key) => return Ok(()),
+                (Some(_), KeyCode::Esc) => {
+                    typing = None;
+                    None
+                }
+                (Some(name), KeyCode::Enter) => Some(Some(name.clone())),
+                (Some(name), KeyCode::Backspace) => {
+                    name.pop();
+                    None
+                }
+                (Some(name), KeyCode::Char(char))
+                    if name.chars().count() < paths::PROFILE_LENGTH =>
+                {
+                    name.push(char);
+                    None
+                }
+                (Some(_), _) => None,
+                (None, KeyCode::Esc | KeyCode::Char('q')) => return Ok(()),
+                (None, KeyCode::Up) => {
+                    picked = picked.saturating_sub(1);
+                    None
+                }
+                (None, KeyCode::Down) => {
+                    picked += 1;
+                    None
+                }
+                (None, KeyCode::Enter | KeyCode::Char(' ')) if picked == profiles.len() => {
+                    typing = Some(String::new());
+                    None
+                }
+                (None, KeyCode::Enter | KeyCode::Char(' ')) => Some(profiles[picked].clone()),
+                (None, _) => None,
+            };
+
+            if let Some(profile) = switch_to {
+                match Self::switch_profile(profile) {
+                    Ok(loaded) => {
+                        *config = loaded;
+                        return Ok(());
+                    }
+                    Err(message) => error = Some(message),
+                }
+            }
+        }
+    }
+
+    // use `profile`'s files from now on and load its config, the profile stays the same if that fails
+    fn switch_profile(profile: Option<String>) -> std::result::Result<Config, String> {
+        let old = paths::profile();
+        paths::set_profile(profile)?;
+
+        let loaded = match paths::config_file().filter(|path| path.exists()) {
+            Some(path) => Config::load(&path, None)
+                .map_err(|error| format!("config error in {}: {error}", path.display())),
+            None => Ok(Config::with_preset(None)),
+        };
+
+        let mut config = match loaded.and_then(|config| {
+            paths::create_profile()
+                .map(|_| config)
+                .map_err(|error| error.to_string())
+        }) {
+            Ok(config) => config,
+            Err(error) => {
+                paths::set_profile(old)?;
+                return Err(error);
+            }
+        };
+
+        // the same as the config gets at startup
+        let colors = config.color_support();
+        config.theme.downgrade(colors);
+
+        if theme::plain_terminal() {
+            config.theme.make_plain();
+            config.set_title = false;
+        }
+
+        Ok(config)
+    }
+
+    // scroll back through the latest games, newest first. enter watches the one that's picked and p plays its board
+    // again, escape (or ctrl+c) gives None
+    fn choose_history(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &Config,
+    ) -> Result<Option<Choice>> {
+        let theme = &config.theme;
+        let mut games = History::load()?.games;
+        games.reverse();
+
+        let (mut picked, mut top) = (0, 0);
+        let mut error: Option<String> = None;
+
+        loop {
+            let mut lines = vec![
+                Spans::from(ui::styled("History", &theme.menu_highlight)),
+                Spans::default(),
+            ];
+
+            if games.is_empty() {
+                lines.push(Spans::from(ui::styled(
+                    "there aren't any games here yet, they're added as they're won or lost",
+                    &theme.menu,
+                )));
+                lines.push(Spans::default());
+                lines.push(Spans::from(ui::styled(
+                    "press any key to go back",
+                    &theme.menu,
+                )));
+                screen.page(config.safe_area().top, lines)?;
+
+                while !matches!(events.read()?, Event::Key(_)) {}
+                return Ok(None);
+            }
+
+            // as many games as fit under the title and header and over the controls, scrolled to keep the picked one in view
+            let safe_area = config.safe_area();
+            let fit = (terminal::size()?
+                .1
+                .saturating_sub(safe_area.top + safe_area.bottom) as usize)
+                .saturating_sub(7)
+                .max(3);
+            picked = picked.min(games.len() - 1);
+            top = top.clamp(picked.saturating_sub(fit - 1), picked);
+
+            lines.push(Spans::from(ui::styled(
+                format!(
+                    "  {:<12}{:<18}{:<11}{:<7}{:>9}{:>6}  seed",
+                    "date", "difficulty", "board", "result", "time", "3BV"
+                ),
+                &theme.menu_highlight,
+            )));
+
+            for (i, game) in games.iter().enumerate().skip(top).take(fit) {
+                let replay = &game.replay;
+                let result = match game.outcome {
+                    Outcome::Won => "won",
+                    _ => "lost",
+                };
+                let line = format!(
+                    "{} {:<12}{:<18}{:<11}{:<7}{:>8.1}s{:>6}  {}",
+                    if i == picked { '>' } else { ' ' },
+                    daily::date(game.finished / 86_400),
+                    difficulty(replay),
+                    format!("{}x{}/{}", replay.width, replay.height, replay.mines.len()),
+                    result,
+                    replay.duration().as_secs_f64(),
+                    game.three_bv,
+                    replay
+                        .seed
+                        .map(|seed| seed.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+                let style = if i == picked {
+                    &theme.menu_highlight
+                } else {
+                    &theme.menu
+                };
+                lines.push(Spans::from(ui::styled(line, style)));
+            }
+
+            lines.push(Spans::default());
+            lines.push(Spans::from(ui::styled(
+                error.take().unwrap_or_else(|| "up/down picks a game, enter watches it, p plays its board again and escape goes back".to_string()),
+                &theme.menu,
+            )));
+            screen.page(safe_area.top, lines)?;
+
+            let key = match events.read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            match key.code {
+                _ if input::is_interrupt(&key) => return Ok(None),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+                KeyCode::Up => picked = picked.saturating_sub(1),
+                KeyCode::Down => picked += 1,
+                KeyCode::PageUp => picked = picked.saturating_sub(fit),
+                KeyCode::PageDown => picked += fit,
+                KeyCode::Home => picked = 0,
+                KeyCode::End => picked = games.len() - 1,
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    return Ok(Some(Choice::Watch(games.swap_remove(picked).replay)))
+                }
+                KeyCode::Char('p') => {
+                    match GameCode::from_replay(&games[picked].replay) {
+                        Some(code) => return Ok(Some(Choice::Retry(code))),
+                        None => error = Some(
+                            "that board wasn't generated from a seed, so it can't be played again"
+                                .to_string(),
+                        ),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // pick one of the save slots, to load the game in it or to save over it. saving has a new slot at the end to pick
+    // too, which is slots.len(). d deletes the slot that's picked, escape (or ctrl+c) gives None
+    fn choose_slot(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &Config,
+        slots: &mut Slots,
+        saving: bool,
+    ) -> Result<Option<usize>> {
+        let theme = &config.theme;
+        let mut picked = 0;
+
+        loop {
+            let choices = slots.slots.len() + saving as usize;
+            picked = picked.min(choices.saturating_sub(1));
+
+            let title = match saving {
+                true => "Save the game",
+                false => "Saved games",
+            };
+            let mut lines = vec![
+                Spans::from(ui::styled(title, &theme.menu_highlight)),
+                Spans::default(),
+            ];
+
+            if choices == 0 {
+                lines.push(Spans::from(ui::styled(
+                    "there aren't any saved games yet, press v while playing to save one",
+                    &theme.menu,
+                )));
+                lines.push(Spans::default());
+                lines.push(Spans::from(ui::styled(
+                    "press any key to go back",
+                    &theme.menu,
+                )));
+                screen.page(config.safe_area().top, lines)?;
+
+                while !matches!(events.read()?, Event::Key(_)) {}
+                return Ok(None);
+            }
+
+            lines.push(Spans::from(ui::styled(
+                format!(
+                    "  {:<width$}  {:<16}{:>9}{:>7}",
+                    "name",
+                    "difficulty",
+                    "time",
+                    "open",
+                    width = saves::NAME_LENGTH
+                ),
+                &theme.menu_highlight,
+            )));
+
+            for (i, slot) in slots.slots.iter().enumerate() {
+                let game = &slot.game;
+                let line = format!(
+                    "{} {:<width$}  {:<16}{:>8.1}s{:>6}%",
+                    if i == picked { '>' } else { ' ' },
+                    slot.name,
+                    difficulty(&game.replay),
+                    game.elapsed().as_secs_f64(),
+                    game.progress(),
+                    width = saves::NAME_LENGTH,
+                );
+                let style = if i == picked {
+                    &theme.menu_highlight
+                } else {
+                    &theme.menu
+                };
+                lines.push(Spans::from(ui::styled(line, style)));
+            }
+
+            if saving {
+                let (marker, style) = match picked == slots.slots.len() {
+                    true => ('>', &theme.menu_highlight),
+                    false => (' ', &theme.menu),
+                };
+                lines.push(Spans::from(ui::styled(format!("{marker} new slot"), style)));
+            }
+
+            let action = match saving {
+                true => "enter saves to it",
+                false => "enter loads it",
+            };
+            lines.push(Spans::default());
+            lines.push(Spans::from(ui::styled(
+                format!("up/down picks a slot, {action}, d deletes it and escape goes back"),
+                &theme.menu,
+            )));
+            screen.page(config.safe_area().top, lines)?;
+
+            let key = match events.read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            match key.code {
+                _ if input::is_interrupt(&key) => return Ok(None),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+                KeyCode::Up => picked = picked.saturating_sub(1),
+                KeyCode::Down => picked += 1,
+                KeyCode::Enter | KeyCode::Char(' ') => return Ok(Some(picked)),
+                KeyCode::Char('d') if picked < slots.slots.len() => {
+                    slots.slots.remove(picked);
+                    slots.save()?;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // save the game to a slot picked from the save menu, over what's there or to a new one that's named first. the
+    // clock stops until it's back to the game
+    fn save_to_slot(&mut self) -> Result<()> {
+        let elapsed = match self.started {
+            Some(started) if self.autosaves() => started.elapsed(),
+            Some(_) => return self.notice("only a plain game or a daily challenge can be saved"),
+            None => return self.notice("there's nothing to save until a cell's been opened"),
+        };
+
+        let mut slots = Slots::load()?;
+        let name = match Self::choose_slot(
+            &mut self.screen,
+            &mut self.events,
+            &self.config,
+            &mut slots,
+            true,
+        )? {
+            Some(slot) if slot < slots.slots.len() => Some(slots.slots[slot].name.clone()),
+            Some(_) => self.ask("save as: ", String::new(), saves::NAME_LENGTH)?,
+            None => None,
+        };
+
+        self.started = Instant::now().checked_sub(elapsed);
+        self.cursor = Cursor::Selection;
+
+        match name {
+            Some(name) => {
+                slots.put(&name, SavedGame::new(&self.replay, elapsed));
+                slots.save()?;
+                self.notice(&format!(
+                    "saved to {name}, press l in the level menu to carry on from here"
+                ))
+            }
+            None => self.draw_board(),
+        }
+    }
+
+    // ask whether to carry on with the game the last session was cut off in the middle of
+    fn offer_autosave(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &Config,
+        saved: &SavedGame,
+    ) -> Result<bool> {
+        let theme = &config.theme;
+        let replay = &saved.replay;
+        let safe = (replay.width * replay.height).saturating_sub(replay.mines.len());
+        let opened = replay
+            .actions
+            .last()
+            .map(|action| action.uncovered)
+            .unwrap_or(0);
+
+        let lines = vec![
+            Spans::from(ui::styled(
+                "Your last game was cut off part way through",
+                &theme.menu_highlight,
+            )),
+            Spans::default(),
+            Spans::from(ui::styled(
+                format!(
+                    "{}, {opened} of {safe} cells open, {:.1}s in",
+                    difficulty(replay),
+                    saved.elapsed().as_secs_f64()
+                ),
+                &theme.menu,
+            )),
+            Spans::default(),
+            Spans::from(ui::styled(
+                "press y to pick it up where you left off, anything else starts a new game",
+                &theme.menu,
+            )),
+        ];
+
+        screen.page(config.safe_area().top, lines)?;
+
+        loop {
+            if let Event::Key(key) = events.read()? {
+                if input::is_interrupt(&key) {
+                    Self::quit();
+                }
+
+                return Ok(matches!(key.code, KeyCode::Char('y' | 'Y')));
+            }
+        }
+    }
+
+    // say the autosave couldn't be read, until a key is pressed
+    fn lost_autosave(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &Config,
+        error: &io::Error,
+    ) -> Result<()> {
+        let theme = &config.theme;
+        let lines = vec![
+            Spans::from(ui::styled(
+                "Your last game was cut off, but it couldn't be picked back up",
+                &theme.menu_highlight,
+            )),
+            Spans::default(),
+            Spans::from(ui::styled(
+                format!("the autosave couldn't be read ({error}), so it's been cleared away"),
+                &theme.menu,
+            )),
+            Spans::default(),
+            Spans::from(ui::styled("press any key to start a new game", &theme.menu)),
+        ];
+
+        screen.page(config.safe_area().top, lines)?;
+
+        loop {
+            if let Event::Key(key) = events.read()? {
+                if input::is_interrupt(&key) {
+                    Self::quit();
+                }
+
+                return Ok(());
+            }
+        }
+    }
+
+    // every level's lifetime statistics, until a key is pressed
+    fn show_stats(screen: &mut Screen, events: &mut Events, config: &Config) -> Result<()> {
+        let theme = &config.theme;
+        let stats = Stats::load()?;
+        let seconds = |time: Option<Duration>| {
+            time.map(|time| format!("{:.1}s", time.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let (won, lost) = match theme.board.ascii {
+            true => ('+', '-'),
+            false => ('█', '▁'),
+        };
+
+        let mut lines = vec![
+            Spans::from(ui::styled("Lifetime statistics", &theme.menu_highlight)),
+            Spans::default(),
+            Spans::from(ui::styled(
+                format!(
+                    "{:<18}{:>7}{:>6}{:>10}{:>9}{:>9}{:>8}{:>9}  recent",
+                    "", "played", "won", "win rate", "best", "average", "streak", "longest"
+                ),
+                &theme.menu_highlight,
+            )),
+        ];
+
+        // every level, then the no-flag runs on any level they've been played on and the daily challenges if there are any
+        let levels =
+            (1..=3).map(|level| (level_name(level).to_string(), stats.level(level, false)));
+        let no_flags = (1..=3).map(|level| {
+            (
+                format!("{} NF", level_name(level)),
+                stats.level(level, true),
+            )
+        });
+        let timed = stats.time_limits().into_iter().map(|(level, limit)| {
+            (
+                format!("{} {}s", level_name(level), limit / 1000),
+                stats.timed(level, limit),
+            )
+        });
+        let daily = [("Daily".to_string(), stats.daily())];
+        let rows = levels.chain(
+            no_flags
+                .chain(timed)
+                .chain(daily)
+                .filter(|(_, level_stats)| level_stats.played > 0),
+        );
+
+        for (name, level_stats) in rows {
+            let rate = (level_stats.won * 100)
+                .checked_div(level_stats.played)
+                .unwrap_or(0);
+
+            let mut line = vec![ui::styled(
+                format!(
+                    "{:<18}{:>7}{:>6}{:>9}%{:>9}{:>9}{:>8}{:>9}  ",
+                    name,
+                    level_stats.played,
+                    level_stats.won,
+                    rate,
+                    seconds(level_stats.best),
+                    seconds(level_stats.average),
+                    level_stats.streak,
+                    level_stats.longest_streak,
+                ),
+                &theme.menu,
+            )];
+
+            for &result in &level_stats.recent {
+                line.push(match result {
+                    true => ui::styled(won, &theme.pace_ahead),
+                    false => ui::styled(lost, &theme.pace_behind),
+                });
+            }
+
+            lines.push(Spans::from(line));
+        }
+
+        lines.push(Spans::default());
+
+        // the longest marathons, straight and ramping, if there have been any
+        for (ramp, name) in [
+            (false, "longest marathon"),
+            (true, "longest ramping marathon"),
+        ] {
+            if let Some(marathon) = stats.longest_marathon(ramp) {
+                lines.push(Spans::from(ui::styled(
+                    format!(
+                        "{name}: {} boards in {}",
+                        marathon.boards,
+                        seconds(Some(Duration::from_millis(marathon.duration)))
+                    ),
+                    &theme.menu,
+                )));
+            }
+        }
+
+        // one number for every level together, which needs a game played since 3BVs were kept
+        if let Some(skill) = stats.skill() {
+            lines.push(Spans::from(ui::styled(
+                format!(
+                    "skill rating: {:.0} ({:+.0} last game, from {} {})",
+                    skill.rating,
+                    skill.change,
+                    skill.games,
+                    if skill.games == 1 { "game" } else { "games" },
+                ),
+                &theme.menu,
+            )));
+        }
+
+        lines.push(Spans::from(ui::styled("best and average times and the skill rating leave out assisted games, press any key to go back", &theme.menu)));
+        screen.page(config.safe_area().top, lines)?;
+
+        while !matches!(events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // the top times on every level side by side, until a key is pressed
+    fn show_leaderboard(screen: &mut Screen, events: &mut Events, config: &Config) -> Result<()> {
+        let theme = &config.theme;
+        let leaderboard = Leaderboard::load()?;
+        let column = leaderboard::NAME_LENGTH + 12;
+
+        let header: String = (1..=3)
+            .map(|level| format!("{:<column$}", level_name(level)))
+            .collect();
+        let mut lines = vec![
+            Spans::from(ui::styled("High scores", &theme.menu_highlight)),
+            Spans::default(),
+            Spans::from(ui::styled(header, &theme.menu_highlight)),
+        ];
+
+        for place in 0..leaderboard::PLACES {
+            let line: String = (1..=3)
+                .map(|level| match leaderboard.scores(level).get(place) {
+                    Some(score) => {
+                        let entry = format!(
+                            "{:>2}. {} {:.1}s",
+                            place + 1,
+                            score.name,
+                            score.time as f64 / 1000.0
+                        );
+                        format!("{entry:<column$}")
+                    }
+                    None => " ".repeat(column),
+                })
+                .collect();
+
+            lines.push(Spans::from(ui::styled(line.trim_end(), &theme.menu)));
+        }
+
+        lines.push(Spans::default());
+        lines.push(Spans::from(ui::styled(
+            "press any key to go back",
+            &theme.menu,
+        )));
+        screen.page(config.safe_area().top, lines)?;
+
+        while !matches!(events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // today's times on the daily challenge from the online leaderboard, until a key is pressed
+    fn show_online_rankings(
+        screen: &mut Screen,
+        events: &mut Events,
+        config: &Config,
+    ) -> Result<()> {
+        let theme = &config.theme;
+        let day = daily::today();
+        let title = Spans::from(ui::styled(
+            format!("Daily {} online", daily::date(day)),
+            &theme.menu_highlight,
+        ));
+
+        screen.page(
+            config.safe_area().top,
+            vec![
+                title.clone(),
+                Spans::default(),
+                Spans::from(ui::styled("fetching...", &theme.menu)),
+            ],
+        )?;
+
+        let mut lines = vec![title, Spans::default()];
+        let rankings = match online::rankings(&config.online, day) {
+            Ok(rankings) if rankings.is_empty() => {
+                vec!["nobody's cleared today's board yet".to_string()]
+            }
+            Ok(rankings) => rankings
+                .iter()
+                .take(leaderboard::PLACES)
+                .enumerate()
+                .map(|(place, ranking)| {
+                    format!(
+                        "{:>2}. {:<width$} {:.1}s",
+                        place + 1,
+                        ranking.name,
+                        ranking.time as f64 / 1000.0,
+                        width = leaderboard::NAME_LENGTH
+                    )
+                })
+                .collect(),
+            Err(error) => vec![format!("couldn't get the rankings: {error}")],
+        };
+
+        lines.extend(
+            rankings
+                .into_iter()
+                .map(|line| Spans::from(ui::styled(line, &theme.menu))),
+        );
+        lines.push(Spans::default());
+        lines.push(Spans::from(ui::styled(
+            "press any key to go back",
+            &theme.menu,
+        )));
+        screen.page(config.safe_area().top, lines)?;
+
+        while !matches!(events.read()?, Event::Key(_)) {}
+
+        Ok(())
+    }
+
+    // say goodbye, once the terminal's been put back to normal
+    pub fn exit_message() -> Result<()> {
+        stdout()
+            .execute(Print("Thanks for playing!"))?
+            .execute(MoveToNextLine(2))?;
+        Ok(())
+    }
+
+    // leave straight away from outside a game, where there's nothing to clean up but the terminal
+    // the process is ending either way, so a terminal error here isn't worth reporting
+    fn quit() -> ! {
+        let _ = crate::terminal::restore();
+        let _ = Self::exit_message();
+        std::process::exit(0);
+    }
+
+    fn get_input(&self, event: Event) -> Option<Input> {
+        let key = match event {
+            Event::Key(key) => key,
+            _ => return None,
+        };
+
+        // checked before the action keys, which ignore modifiers and would see this as a plain 'c'
+        if input::is_interrupt(&key) {
+            return Some(Input::Quit);
+        }
+
+        // action keys ignore case and modifiers so a latched sticky shift or ctrl doesn't get in the way
+        if let KeyCode::Char(char) = key.code {
+            match char.to_ascii_lowercase() {
+                'q' => return Some(Input::Quit),
+                ' ' => return Some(Input::Select),
+                'm' => return Some(Input::Mark),
+                '?' => return Some(Input::Help),
+                'r' => return Some(Input::Restart),
+                'g' => return Some(Input::GiveUp),
+                'v' => return Some(Input::Save),
+                digit @ '0'..='9' => return Some(Input::Count(digit as usize - '0' as usize)),
+                _ => (),
+            }
+        }
+
+        if key.code == KeyCode::Enter {
+            return Some(Input::Select);
+        }
+
+        // typing a number first moves that many cells without needing a modifier at all
+        input::moved(
+            &key,
+            self.selection,
+            (self.width, self.height),
+            &self.config,
+            self.count,
+        )
+        .map(Input::Direction)
+    }
+}
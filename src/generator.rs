@@ -0,0 +1,446 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+
+// what kind of board to make
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub placement: Placement,
+}
+
+// how the mines get spread over the board
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    // every cell is as likely as any other, like the classic game
+    #[default]
+    Uniform,
+    // mines bunch up in a few clumps, leaving big open areas between them
+    Clustered,
+    // mines crowd towards the edges of the board and thin out in the middle
+    Border,
+}
+
+impl Placement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Placement::Uniform => "uniform",
+            Placement::Clustered => "clustered",
+            Placement::Border => "border",
+        }
+    }
+
+    // how likely each cell (y * width + x) is to get a mine, compared to the others
+    fn weights(&self, settings: Settings, rng: &mut impl Rng) -> Vec<f64> {
+        let (width, height) = (settings.width, settings.height);
+        let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x as f64, y as f64)));
+
+        match self {
+            Placement::Uniform => vec![1.0; width * height],
+            Placement::Clustered => {
+                // about one clump for every 8 mines, each one a blob a few cells across
+                let centers: Vec<(f64, f64)> = (0..(settings.mines / 8).max(1))
+                    .map(|_| {
+                        (
+                            rng.gen_range(0..width) as f64,
+                            rng.gen_range(0..height) as f64,
+                        )
+                    })
+                    .collect();
+
+                cells
+                    .map(|(x, y)| {
+                        // a little weight everywhere so a board can't run out of places to put mines
+                        0.02 + centers
+                            .iter()
+                            .map(|(cx, cy)| exp(-(square(x - cx) + square(y - cy)) / 4.5))
+                            .sum::<f64>()
+                    })
+                    .collect()
+            }
+            Placement::Border => cells
+                .map(|(x, y)| {
+                    let edge = x
+                        .min(y)
+                        .min(width as f64 - 1.0 - x)
+                        .min(height as f64 - 1.0 - y);
+                    1.0 / (1.0 + edge)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [Placement::Uniform, Placement::Clustered, Placement::Border]
+            .into_iter()
+            .find(|placement| placement.name() == name)
+            .ok_or_else(|| "pick uniform, clustered or border".to_string())
+    }
+}
+
+impl Settings {
+    // the cell every layout is generated to start from, boards get shifted to where the player actually clicks
+    pub fn start(&self) -> (usize, usize) {
+        (self.width / 2, self.height / 2)
+    }
+}
+
+// this was the most technical function in the program, it randomly places mines on the board
+// the hardest part was not placing any mines where the user's cursor is, and not adjacent to the cursor either
+// this is done so that the user's first click is not on a bomb or adjacent square so they can have a chance to win each time.
+// layouts are always generated around settings.start(), use shift() to move them under the first click.
+// the same seed always gives the same layout. returns the indices (y * width + x) of the mines
+pub fn generate(settings: Settings, seed: u64) -> Vec<usize> {
+    generate_with(settings, &mut StdRng::seed_from_u64(seed))
+}
+
+// generate() with the randomness coming from `rng`, for when there's no std to seed a StdRng from, like on a
+// microcontroller with a hardware RNG
+pub fn generate_with(settings: Settings, rng: &mut impl Rng) -> Vec<usize> {
+    // Random mine placement indice idea: credit @asuradev99
+    let board = Board::new(settings.width, settings.height);
+    let (x, y) = settings.start();
+
+    // leave out the start and all the spots around it so that we don't click on an adjacent square.
+    let safe: Vec<_> = board.neighbors(x, y).chain([(x, y)]).collect();
+
+    let mut mine_indices: Vec<usize> = (0..settings.width * settings.height)
+        .filter(|&i| !safe.contains(&board.position(i)))
+        .collect();
+
+    // shuffle mine placement using rand
+    mine_indices.shuffle(rng);
+    mine_indices.truncate(settings.mines);
+
+    mine_indices
+}
+
+// the layout for `seed` with the first click at `start`, using whichever placement the settings ask for.
+// uniform layouts are generated around the middle and shifted like they always have been, so old codes and seeds still
+// give the same boards. the others care where the edges are so they can't be shifted, and get generated around `start`
+pub fn layout(settings: Settings, seed: u64, start: (usize, usize)) -> Vec<usize> {
+    layout_with(settings, &mut StdRng::seed_from_u64(seed), start)
+}
+
+// layout() with the randomness coming from `rng`
+pub fn layout_with(settings: Settings, rng: &mut impl Rng, start: (usize, usize)) -> Vec<usize> {
+    if settings.placement == Placement::Uniform {
+        return shift(
+            &generate_with(settings, rng),
+            settings,
+            settings.start(),
+            start,
+        );
+    }
+
+    let board = Board::new(settings.width, settings.height);
+    let safe: Vec<_> = board.neighbors(start.0, start.1).chain([start]).collect();
+    let weights = settings.placement.weights(settings, rng);
+
+    // weighted sampling without replacement: give every cell a random key skewed by its weight and keep the biggest.
+    // ln(u) / weight is the same ordering as u^(1 / weight) without the precision trouble
+    let mut keyed: Vec<(f64, usize)> = (0..settings.width * settings.height)
+        .filter(|&i| !safe.contains(&board.position(i)))
+        .map(|i| (ln(rng.gen::<f64>()) / weights[i], i))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    keyed
+        .into_iter()
+        .take(settings.mines)
+        .map(|(_, i)| i)
+        .collect()
+}
+
+// move a layout so the safe area around `from` ends up around `to`, wrapping around the edges.
+// every layout is equally likely before and after the shift, so it's just as random as generating around `to`
+pub fn shift(
+    mines: &[usize],
+    settings: Settings,
+    from: (usize, usize),
+    to: (usize, usize),
+) -> Vec<usize> {
+    let (width, height) = (settings.width, settings.height);
+    let dx = (to.0 + width - from.0) % width;
+    let dy = (to.1 + height - from.1) % height;
+
+    mines
+        .iter()
+        .map(|i| {
+            let (x, y) = ((i % width + dx) % width, (i / width + dy) % height);
+            (y * width) + x
+        })
+        .collect()
+}
+
+// float functions come from std when it's there, it's what every seed so far has been laid out with,
+// and from libm when it isn't
+#[cfg(feature = "std")]
+fn exp(x: f64) -> f64 {
+    x.exp()
+}
+
+#[cfg(not(feature = "std"))]
+fn exp(x: f64) -> f64 {
+    libm::exp(x)
+}
+
+#[cfg(feature = "std")]
+fn ln(x: f64) -> f64 {
+    x.ln()
+}
+
+#[cfg(not(feature = "std"))]
+fn ln(x: f64) -> f64 {
+    libm::log(x)
+}
+
+fn square(x: f64) -> f64 {
+    x * x
+}
+
+// how long the cache keeps trying for a wanted board before it settles for the last one it made
+#[cfg(feature = "std")]
+const KEEP_TRYING_FOR: Duration = Duration::from_secs(5);
+
+// whether a board with its mines down is worth keeping, starting from the cell it's given
+#[cfg(feature = "std")]
+pub type Keep = Arc<dyn Fn(&Board, (usize, usize)) -> bool + Send + Sync>;
+
+// a kind of board the cache keeps generating until it gets one, like a board of a certain difficulty. the name
+// tells kinds apart, so a board that's being made for one kind is never handed out for another
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct Wanted {
+    pub name: &'static str,
+    pub keep: Keep,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for Wanted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+// generates the next board on a background thread while the current game is played,
+// so starting a new game never has to wait for generation
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct BoardCache {
+    pending: Option<Pending>,
+    wanted: Option<Wanted>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Pending {
+    settings: Settings,
+    wanted: Option<&'static str>,
+    receiver: Receiver<(u64, Vec<usize>)>,
+    // tells the worker its board isn't wanted anymore, it stops trying between boards
+    cancelled: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "std")]
+impl BoardCache {
+    // only hand out boards `wanted` keeps, or any board if it's None. a board already being made for something
+    // else is cancelled the next time one's prepared
+    pub fn want(&mut self, wanted: Option<Wanted>) {
+        self.wanted = wanted;
+    }
+
+    // start generating a board for `settings` in the background.
+    // anything already being generated for different settings is cancelled
+    pub fn prepare(&mut self, settings: Settings) {
+        // only uniform layouts can be made before the first click, the rest depend on where it is
+        if settings.placement != Placement::Uniform {
+            return self.cancel();
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| self.made_for(pending, settings))
+        {
+            return;
+        }
+
+        self.cancel();
+
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        let wanted = self.wanted.clone();
+
+        thread::spawn(move || {
+            let started = Instant::now();
+
+            // one board after another until one's kept, giving up as soon as nobody wants it
+            while !worker_cancelled.load(Ordering::Relaxed) {
+                let seed = rand::thread_rng().gen();
+                let mines = generate(settings, seed);
+
+                let kept = wanted.as_ref().is_none_or(|wanted| {
+                    let board = Board::with_mines(settings.width, settings.height, &mines);
+                    (wanted.keep)(&board, settings.start())
+                });
+
+                if kept || started.elapsed() > KEEP_TRYING_FOR {
+                    let _ = sender.send((seed, mines));
+                    return;
+                }
+            }
+        });
+
+        self.pending = Some(Pending {
+            settings,
+            wanted: self.wanted.as_ref().map(|wanted| wanted.name),
+            receiver,
+            cancelled,
+        });
+    }
+
+    // a board for `settings` and the seed it was made from, taken from the cache if it's ready or generated right now if it isn't.
+    // this is called as the first click goes in, so it never waits on the worker: one that's still looking for a
+    // wanted board could be at it for KEEP_TRYING_FOR. one generated right now might not be what's wanted, and the
+    // next one starts generating straight away
+    pub fn take(&mut self, settings: Settings) -> (u64, Vec<usize>) {
+        let cached = self.pending.take().and_then(|pending| {
+            let ready = match self.made_for(&pending, settings) {
+                true => pending.receiver.try_recv().ok(),
+                false => None,
+            };
+
+            if ready.is_none() {
+                pending.cancel();
+            }
+
+            ready
+        });
+
+        let layout = cached.unwrap_or_else(|| {
+            let seed = rand::thread_rng().gen();
+            (seed, generate(settings, seed))
+        });
+        self.prepare(settings);
+
+        layout
+    }
+
+    fn made_for(&self, pending: &Pending, settings: Settings) -> bool {
+        pending.settings == settings
+            && pending.wanted == self.wanted.as_ref().map(|wanted| wanted.name)
+    }
+
+    fn cancel(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.cancel();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Pending {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_placement_keeps_the_first_click_safe() {
+        for placement in [Placement::Uniform, Placement::Clustered, Placement::Border] {
+            let settings = Settings {
+                width: 16,
+                height: 16,
+                mines: 40,
+                placement,
+            };
+            let mines = layout(settings, 7, (2, 3));
+            let board = Board::with_mines(16, 16, &mines);
+
+            assert_eq!(board.num_mines(), 40);
+            assert!(
+                !board.cell(2, 3).is_mine()
+                    && board
+                        .neighbors(2, 3)
+                        .all(|(x, y)| !board.cell(x, y).is_mine())
+            );
+            assert_eq!(mines, layout(settings, 7, (2, 3)));
+        }
+    }
+
+    // the cache keeps trying until it has a wanted board, stops trying as soon as it's cancelled, and taking a board
+    // never waits for it
+    #[test]
+    #[cfg(feature = "std")]
+    fn the_cache_stops_when_cancelled() {
+        let settings = Settings {
+            width: 16,
+            height: 16,
+            mines: 40,
+            placement: Placement::Uniform,
+        };
+
+        let mut cache = BoardCache::default();
+        cache.want(Some(Wanted {
+            name: "corner",
+            keep: Arc::new(|board, _| board.is_mine(0, 0)),
+        }));
+        cache.prepare(settings);
+        let (_, mines) = cache.pending.as_ref().unwrap().receiver.recv().unwrap();
+        assert!(mines.contains(&0));
+
+        let (tried, tries) = mpsc::channel();
+        cache.want(Some(Wanted {
+            name: "never",
+            keep: Arc::new(move |_, _| {
+                let _ = tried.send(());
+                false
+            }),
+        }));
+        cache.prepare(settings);
+        tries.recv().unwrap();
+
+        // a worker that kept going would send its last board after KEEP_TRYING_FOR, one that stopped hangs up
+        let pending = cache.pending.take().unwrap();
+        pending.cancel();
+        assert!(pending.receiver.recv().is_err());
+
+        // with one that's never going to find what's wanted, the board comes from right here instead of waiting
+        cache.prepare(settings);
+        let started = Instant::now();
+        cache.take(settings);
+        assert!(started.elapsed() < KEEP_TRYING_FOR);
+    }
+}
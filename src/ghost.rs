@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use ratatui::{style::Modifier, text::Span};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::Board,
+    code::GameCode,
+    paths,
+    replay::{ActionKind, Replay},
+    theme::Theme,
+    ui::{self, MinesweeperWidget},
+};
+
+// how many boards' runs are kept, the ones played longest ago go first
+const KEEP: usize = 50;
+
+// the best run on a board, to race against when it's played again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Run {
+    code: String,
+    won: bool,
+    replay: Replay,
+}
+
+impl Run {
+    // a win beats any loss and a faster win beats a slower one, between losses whoever got further is better
+    fn beats(&self, other: &Run) -> bool {
+        match (self.won, other.won) {
+            (true, true) => self.replay.duration() < other.replay.duration(),
+            (won, other_won) if won != other_won => won,
+            _ => uncovered(&self.replay) > uncovered(&other.replay),
+        }
+    }
+}
+
+fn uncovered(replay: &Replay) -> usize {
+    replay
+        .actions
+        .last()
+        .map(|action| action.uncovered)
+        .unwrap_or(0)
+}
+
+// every board's best run, the most recently played last
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Runs {
+    runs: Vec<Run>,
+}
+
+impl Runs {
+    fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut runs: Self = serde_json::from_str(&text)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+        runs.runs.retain(|run| run.replay.check().is_ok());
+        Ok(runs)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("ghosts.json"))
+    }
+}
+
+// keep a finished game on `code`'s board if it's the best one there yet
+pub fn record(code: GameCode, replay: &Replay, won: bool) -> io::Result<()> {
+    let mut runs = Runs::load()?;
+    let run = Run {
+        code: code.to_string(),
+        won,
+        replay: replay.clone(),
+    };
+
+    // the board moves to the back either way, since it's just been played
+    let best = match runs.runs.iter().position(|kept| kept.code == run.code) {
+        Some(i) => {
+            let kept = runs.runs.remove(i);
+            if run.beats(&kept) {
+                run
+            } else {
+                kept
+            }
+        }
+        None => run,
+    };
+
+    runs.runs.push(best);
+
+    if runs.runs.len() > KEEP {
+        runs.runs.drain(..runs.runs.len() - KEEP);
+    }
+
+    runs.save()
+}
+
+// the best run played before on `code`'s board, played back alongside the game as it goes
+#[derive(Clone, Debug)]
+pub struct Ghost {
+    replay: Replay,
+    board: Board,
+    // the next of the replay's actions to play
+    next: usize,
+    // where it did the last thing it did
+    cursor: Option<(usize, usize)>,
+}
+
+impl Ghost {
+    pub fn load(code: GameCode) -> io::Result<Option<Self>> {
+        let code = code.to_string();
+        let runs = Runs::load()?;
+
+        Ok(runs
+            .runs
+            .into_iter()
+            .find(|run| run.code == code)
+            .map(|run| Self::new(run.replay)))
+    }
+
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            board: replay.board(),
+            replay,
+            next: 0,
+            cursor: None,
+        }
+    }
+
+    // play everything it did in the first `elapsed` of its game
+    pub fn catch_up(&mut self, elapsed: Duration) {
+        while let Some(action) = self
+            .replay
+            .actions
+            .get(self.next)
+            .filter(|action| action.time <= elapsed.as_millis() as u64)
+        {
+            match action.kind {
+                ActionKind::Reveal => {
+                    self.board.uncover(action.x, action.y);
+                }
+                ActionKind::Mark => self.board.toggle_flag(action.x, action.y),
+            }
+
+            self.cursor = Some((action.x, action.y));
+            self.next += 1;
+        }
+    }
+
+    // how it looks over `board`: the cells it's opened that are still covered there drawn dimly, and its cursor
+    // the other way round
+    pub fn overlay(&self, board: &Board, theme: &Theme) -> HashMap<(usize, usize), Span<'static>> {
+        let ghost = MinesweeperWidget::new(&self.board, theme);
+        let mut cells: HashMap<_, _> = board
+            .positions()
+            .filter(|&(x, y)| self.board.is_uncovered(x, y) && !board.is_uncovered(x, y))
+            .map(|(x, y)| {
+                let mut glyph = ui::span(theme.board.glyph(ghost.tile(x, y)));
+                glyph.style = glyph.style.add_modifier(Modifier::DIM);
+                ((x, y), glyph)
+            })
+            .collect();
+
+        if let Some(cursor) = self.cursor {
+            let mut glyph = cells.remove(&cursor).unwrap_or_else(|| {
+                ui::span(
+                    theme
+                        .board
+                        .glyph(MinesweeperWidget::new(board, theme).tile(cursor.0, cursor.1)),
+                )
+            });
+            glyph.style = glyph.style.add_modifier(Modifier::DIM | Modifier::REVERSED);
+            cells.insert(cursor, glyph);
+        }
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::Action;
+
+    #[test]
+    fn wins_beat_losses_and_faster_wins_beat_slower_ones() {
+        let run = |won, time, uncovered| {
+            let mut replay = Replay::new(1, 9, 9);
+            replay.actions.push(Action {
+                time,
+                kind: ActionKind::Reveal,
+                x: 0,
+                y: 0,
+                uncovered,
+            });
+            Run {
+                code: String::new(),
+                won,
+                replay,
+            }
+        };
+
+        assert!(run(true, 9000, 71).beats(&run(false, 1000, 70)));
+        assert!(run(true, 9000, 71).beats(&run(true, 9500, 71)));
+        assert!(!run(false, 1000, 20).beats(&run(false, 5000, 30)));
+    }
+
+    #[test]
+    fn the_ghost_keeps_to_its_own_time() {
+        let mut replay = Replay::new(1, 3, 1);
+        replay.mines = vec![1];
+        replay.actions.push(Action {
+            time: 0,
+            kind: ActionKind::Reveal,
+            x: 0,
+            y: 0,
+            uncovered: 1,
+        });
+        replay.actions.push(Action {
+            time: 500,
+            kind: ActionKind::Reveal,
+            x: 2,
+            y: 0,
+            uncovered: 2,
+        });
+
+        let mut ghost = Ghost::new(replay);
+        let board = Board::with_mines(3, 1, &[1]);
+        let theme = Theme::default();
+
+        ghost.catch_up(Duration::from_millis(100));
+        assert_eq!(ghost.overlay(&board, &theme).len(), 1);
+
+        // half a second in it's opened the other end too, and its cursor's over there
+        ghost.catch_up(Duration::from_millis(600));
+        assert_eq!(ghost.cursor, Some((2, 0)));
+        assert_eq!(ghost.overlay(&board, &theme).len(), 2);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{board::Board, generator::Settings};
+
+// play in a desktop window with the mouse instead of the terminal, if this was built with the gui feature.
+// it's the same Round the bots play through, only drawn with egui
+#[cfg(feature = "gui")]
+pub fn run(settings: Settings, board: Option<Board>) -> Result<(), String> {
+    window::run(settings, board)
+}
+
+#[cfg(not(feature = "gui"))]
+pub fn run(_settings: Settings, _board: Option<Board>) -> Result<(), String> {
+    Err("this copy of minesweeper was built without the gui feature".to_string())
+}
+
+#[cfg(feature = "gui")]
+mod window {
+    use std::time::{Duration, Instant};
+
+    use eframe::egui::{self, Button, Color32, RichText};
+
+    use crate::{
+        board::Board,
+        event::GameEvent,
+        generator::Settings,
+        player::{Action, Round, Seen, Standing},
+    };
+
+    // how big each cell's button is, in points
+    const CELL: f32 = 26.0;
+    const SPACING: f32 = 2.0;
+
+    // the number colors from minesweeper on Windows, like the classic theme
+    const NUMBERS: [Color32; 8] = [
+        Color32::from_rgb(0, 0, 255),
+        Color32::from_rgb(0, 128, 0),
+        Color32::from_rgb(255, 0, 0),
+        Color32::from_rgb(0, 0, 128),
+        Color32::from_rgb(128, 0, 0),
+        Color32::from_rgb(0, 128, 128),
+        Color32::from_rgb(0, 0, 0),
+        Color32::from_rgb(128, 128, 128),
+    ];
+
+    pub fn run(settings: Settings, board: Option<Board>) -> Result<(), String> {
+        let (width, height) = match &board {
+            Some(board) => (board.width(), board.height()),
+            None => (settings.width, settings.height),
+        };
+        let size = |cells: usize| cells as f32 * (CELL + SPACING) + 16.0;
+
+        let options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default()
+                .with_inner_size([size(width).max(320.0), size(height) + 40.0]),
+            ..Default::default()
+        };
+
+        eframe::run_native(
+            "Minesweeper",
+            options,
+            Box::new(|_| Ok(Box::new(Window::new(settings, board)))),
+        )
+        .map_err(|error| error.to_string())
+    }
+
+    struct Window {
+        settings: Settings,
+        // the board every game is played on, if one was loaded instead of generating them
+        board: Option<Board>,
+        round: Round,
+        started: Option<Instant>,
+        // how long the game took, once it's over
+        took: Option<Duration>,
+    }
+
+    impl Window {
+        fn new(settings: Settings, board: Option<Board>) -> Self {
+            let round = Self::round(settings, &board);
+
+            Self {
+                settings,
+                board,
+                round,
+                started: None,
+                took: None,
+            }
+        }
+
+        fn round(settings: Settings, board: &Option<Board>) -> Round {
+            match board {
+                Some(board) => Round::with_board(board.clone()),
+                None => Round::new(settings, rand::random()),
+            }
+        }
+
+        fn restart(&mut self) {
+            *self = Self::new(self.settings, self.board.take());
+        }
+
+        // moves the game won't take, like chording a number without all its flags, just don't do anything
+        fn play(&mut self, action: Action) {
+            let events = match self.round.apply(action) {
+                Ok(events) => events,
+                Err(_) => return,
+            };
+
+            let started = *self.started.get_or_insert_with(Instant::now);
+
+            if events
+                .iter()
+                .any(|event| matches!(event, GameEvent::Won | GameEvent::Exploded { .. }))
+            {
+                self.took = Some(started.elapsed());
+            }
+        }
+
+        fn elapsed(&self) -> Duration {
+            match (self.took, self.started) {
+                (Some(took), _) => took,
+                (None, Some(started)) => started.elapsed(),
+                (None, None) => Duration::ZERO,
+            }
+        }
+    }
+
+    // what goes on a cell's button and what color it is
+    fn face(seen: Seen) -> (RichText, Color32) {
+        let covered = Color32::from_gray(150);
+        let uncovered = Color32::from_gray(210);
+
+        match seen {
+            Seen::Covered => (RichText::new(""), covered),
+            Seen::Flagged => (RichText::new("⚑").color(Color32::RED), covered),
+            Seen::Wall => (RichText::new(""), Color32::from_gray(60)),
+            Seen::Number(0) => (RichText::new(""), uncovered),
+            Seen::Number(num) => (
+                RichText::new(num.to_string())
+                    .color(NUMBERS[num - 1])
+                    .strong(),
+                uncovered,
+            ),
+            Seen::Mine => (RichText::new("✹").color(Color32::BLACK), uncovered),
+            Seen::Exploded => (RichText::new("✹").color(Color32::BLACK), Color32::RED),
+        }
+    }
+
+    impl eframe::App for Window {
+        fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+            let view = self.round.view();
+            let standing = view.standing();
+            let mines_left = view.mines() as isize - view.flags() as isize;
+
+            let mut restart = false;
+            egui::TopBottomPanel::top("status").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    restart = ui.button("New game").clicked();
+                    ui.label(format!("{mines_left} mines left"));
+                    ui.label(format!("{:.1}s", self.elapsed().as_secs_f32()));
+
+                    match standing {
+                        Standing::Won => ui.label(RichText::new("You won!").strong()),
+                        Standing::Lost => ui.label(RichText::new("You lost!").strong()),
+                        Standing::Playing => ui.label("click to reveal, right click to flag"),
+                    };
+                });
+            });
+
+            // left click reveals, or opens up around a number that has all its flags, and right click flags
+            let mut action = None;
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::Grid::new("board")
+                    .spacing([SPACING, SPACING])
+                    .show(ui, |ui| {
+                        for y in (0..view.height()).rev() {
+                            for x in 0..view.width() {
+                                let seen = view.seen(x, y);
+                                let (text, fill) = face(seen);
+                                let response =
+                                    ui.add_sized([CELL, CELL], Button::new(text).fill(fill));
+
+                                if response.clicked() {
+                                    action = Some(match seen {
+                                        Seen::Number(_) => Action::Chord(x, y),
+                                        _ => Action::Reveal(x, y),
+                                    });
+                                } else if response.secondary_clicked() {
+                                    action = Some(Action::Flag(x, y));
+                                }
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            if restart {
+                self.restart();
+            } else if let Some(action) = action {
+                self.play(action);
+            }
+
+            // keep the clock going
+            if self.started.is_some() && self.took.is_none() {
+                ctx.request_repaint_after(Duration::from_millis(100));
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::Board,
+    paths,
+    replay::Replay,
+    session::Outcome,
+    versioned::{self, Versioned},
+};
+
+// how many of the latest games are kept, older ones drop off the end
+pub const KEPT: usize = 100;
+
+// one game that was won or lost, with everything it takes to watch it again or play its board again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub replay: Replay,
+    pub outcome: Outcome,
+    pub three_bv: usize,
+    // seconds since the unix epoch when the game ended
+    pub finished: u64,
+}
+
+// the latest games played, oldest first
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub games: Vec<Entry>,
+}
+
+impl Versioned for History {
+    const VERSION: u32 = 1;
+}
+
+impl History {
+    pub fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut history: Self = versioned::from_str(&text)?;
+        history.games.retain(|entry| entry.replay.check().is_ok());
+        Ok(history)
+    }
+
+    // add a game that's just ended to the history file
+    pub fn record(replay: &Replay, outcome: Outcome) -> io::Result<()> {
+        let mut history = Self::load()?;
+        history.add(Entry {
+            replay: replay.clone(),
+            outcome,
+            three_bv: Board::with_mines(replay.width, replay.height, &replay.mines)
+                .three_bv()
+                .1,
+            finished: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or(0),
+        });
+
+        history.save()
+    }
+
+    // add a game, letting the oldest go once there are more than KEPT
+    pub fn add(&mut self, entry: Entry) {
+        self.games.push(entry);
+        self.games.drain(..self.games.len().saturating_sub(KEPT));
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, versioned::to_string(self)?)
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("history.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_latest_games_are_kept() {
+        let mut history = History::default();
+
+        for finished in 0..KEPT as u64 + 5 {
+            history.add(Entry {
+                replay: Replay::new(1, 9, 9),
+                outcome: Outcome::Won,
+                three_bv: 10,
+                finished,
+            });
+        }
+
+        assert_eq!(history.games.len(), KEPT);
+        assert_eq!(history.games[0].finished, 5);
+        assert_eq!(
+            history.games.last().map(|game| game.finished),
+            Some(KEPT as u64 + 4)
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, io};
+
+use crossterm::{
+    event::{Event, KeyCode},
+    Result,
+};
+use ratatui::{
+    text::Spans,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::{
+    board::Board,
+    config::Config,
+    event::GameEvent,
+    input::{self, Events},
+    player::{Action, BoardView, Player, Seen, Standing},
+    terminal::set_title,
+    theme::Tile,
+    ui::{self, MinesweeperState, MinesweeperWidget, Screen},
+};
+
+// someone at the terminal, played through player::play() the same way the solver and bots are. the cursor moves the
+// way it does in a game, space reveals (or chords a number), m flags and q stops. bench --player human plays with it,
+// so a person can be measured on the same boards as the other players
+#[derive(Debug)]
+pub struct Human {
+    screen: Screen,
+    config: Config,
+    events: Events,
+    selection: (usize, usize),
+    // a number typed before a direction, to move that many cells like in a game
+    count: Option<usize>,
+    // whether a board's being played, so the next one starts with the cursor back in the middle
+    playing: bool,
+    // how many boards have been started
+    boards: usize,
+    // what just happened, under the board
+    message: String,
+    // q was pressed, so there are no more boards to play
+    stopped: bool,
+    // what went wrong with the terminal, if anything did. it stops the playing too
+    error: Option<io::Error>,
+}
+
+impl Human {
+    pub fn new(config: Config, events: Events) -> Result<Self> {
+        let mut screen = Screen::new()?;
+
+        if config.set_title {
+            set_title(screen.out(), "Minesweeper - Bench")?;
+        }
+
+        Ok(Self {
+            screen,
+            config,
+            events,
+            selection: (0, 0),
+            count: None,
+            playing: false,
+            boards: 0,
+            message: String::new(),
+            stopped: false,
+            error: None,
+        })
+    }
+
+    // what stopped the playing part way, if the terminal did
+    pub fn error(self) -> Option<io::Error> {
+        self.error
+    }
+
+    // wait for the next move on the board
+    fn read(&mut self, view: &BoardView) -> Result<Action> {
+        loop {
+            self.draw(view)?;
+
+            // anything else, like the terminal being resized, just gets the board drawn again
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            if input::is_interrupt(&key) || key.code == KeyCode::Char('q') {
+                self.stopped = true;
+                return Ok(Action::Quit);
+            }
+
+            let size = (view.width(), view.height());
+
+            // a count only applies to the move straight after it
+            let count = self.count.take();
+            if let KeyCode::Char(digit @ '0'..='9') = key.code {
+                let digit = digit as usize - '0' as usize;
+                self.count = Some((count.unwrap_or(0) * 10 + digit).min(size.0.max(size.1)));
+                continue;
+            }
+
+            // the cursor moves the same way it does in a game
+            if let Some(selection) = input::moved(&key, self.selection, size, &self.config, count) {
+                self.selection = selection;
+                continue;
+            }
+
+            let (x, y) = self.selection;
+
+            return Ok(match key.code {
+                KeyCode::Char(' ') | KeyCode::Enter => match view.seen(x, y) {
+                    Seen::Number(mines) if mines > 0 => Action::Chord(x, y),
+                    _ => Action::Reveal(x, y),
+                },
+                KeyCode::Char('m') => Action::Flag(x, y),
+                _ => continue,
+            });
+        }
+    }
+
+    // the board stays up with how it went until a key's pressed, q stops there instead of going on to the next one
+    fn show_ending(&mut self, view: &BoardView) -> Result<()> {
+        self.message = match view.standing() {
+            Standing::Won => "cleared it!",
+            _ => "that was a mine!",
+        }
+        .to_string();
+        self.message
+            .push_str(" press a key for the next board and q to stop");
+
+        loop {
+            self.draw(view)?;
+
+            if let Event::Key(key) = self.events.read()? {
+                self.stopped = input::is_interrupt(&key) || key.code == KeyCode::Char('q');
+                return Ok(());
+            }
+        }
+    }
+
+    fn draw(&mut self, view: &BoardView) -> Result<()> {
+        let theme = &self.config.theme;
+        let (width, height) = (view.width(), view.height());
+
+        // the cells are drawn from what the player can see, the board underneath is only there for the size
+        let blank = Board::new(width, height);
+        let tiles: HashMap<_, _> = view
+            .positions()
+            .map(|(x, y)| {
+                let glyph = theme.board.glyph(tile(view.seen(x, y)));
+                ((x, y), ui::span(glyph))
+            })
+            .collect();
+        let widget = MinesweeperWidget::new(&blank, theme).drawn_over(&tiles);
+        let rows = widget.rows();
+
+        let status = format!(
+            "Bench | board {} | {} mines left | space reveals, m flags, q stops",
+            self.boards,
+            view.mines() as isize - view.flags() as isize
+        );
+        let lines = vec![
+            Spans::from(ui::styled(status, &theme.status_bar)),
+            Spans::from(ui::styled(self.message.clone(), &theme.overlay)),
+        ];
+
+        let playing = view.standing() == Standing::Playing;
+        let top = self.config.safe_area().top;
+        let mut state = MinesweeperState::new(self.selection);
+
+        self.screen.draw(|frame| {
+            let area = frame.size();
+
+            frame.render_stateful_widget(widget, ui::below(area, top), &mut state);
+            frame.render_widget(
+                Paragraph::new(lines).wrap(Wrap { trim: false }),
+                ui::below(area, top + rows),
+            );
+
+            if let Some((column, row)) = state
+                .cursor()
+                .filter(|&(column, row)| playing && column < area.right() && row < area.bottom())
+            {
+                frame.set_cursor(column, row);
+            }
+        })
+    }
+}
+
+impl Player for Human {
+    fn next_action(&mut self, view: &BoardView) -> Action {
+        // a new board starts with the cursor in the middle, where the first click is always safe
+        if !self.playing {
+            self.playing = true;
+            self.boards += 1;
+            self.selection = (view.width() / 2, view.height() / 2);
+            self.message.clear();
+        }
+
+        match self.read(view) {
+            Ok(action) => action,
+            Err(error) => {
+                self.error = Some(error);
+                self.stopped = true;
+                Action::Quit
+            }
+        }
+    }
+
+    fn rejected(&mut self, reason: &str) {
+        self.message = reason.to_string();
+    }
+
+    fn happened(&mut self, _events: &[GameEvent]) {
+        self.message.clear();
+    }
+
+    fn finished(&mut self, view: &BoardView) {
+        self.playing = false;
+
+        if self.stopped {
+            return;
+        }
+
+        if let Err(error) = self.show_ending(view) {
+            self.error = Some(error);
+            self.stopped = true;
+        }
+    }
+
+    fn keeps_playing(&self) -> bool {
+        !self.stopped
+    }
+}
+
+fn tile(seen: Seen) -> Tile {
+    match seen {
+        Seen::Covered => Tile::Covered,
+        Seen::Flagged => Tile::Marked,
+        Seen::Wall => Tile::Wall,
+        Seen::Number(0) => Tile::Empty,
+        Seen::Number(mines) => Tile::Number(mines),
+        Seen::Mine | Seen::Exploded => Tile::Mine,
+    }
+}
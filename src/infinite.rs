@@ -0,0 +1,452 @@
+use std::{
+    collections::HashMap,
+    io::{stdout, Stdout, Write},
+    ops::RangeInclusive,
+};
+
+use crossterm::{
+    cursor::{MoveTo, Show},
+    event::{Event, KeyCode},
+    style::Print,
+    terminal::{self, Clear, ClearType},
+    ExecutableCommand, QueueableCommand, Result,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    config::Config,
+    input::{self, Events},
+    status::StatusPosition,
+    terminal::set_title,
+    theme::{Sound, Tile},
+};
+
+// how many cells along each side of a chunk
+const CHUNK_SIZE: i64 = 16;
+
+// the share of cells that are mines, unless --density says otherwise
+pub const DENSITY: f64 = 0.2;
+
+// how far --density can go. too few mines and openings can run on forever, too many and there's nothing to play
+pub const DENSITY_RANGE: RangeInclusive<f64> = 0.15..=0.5;
+
+// a square of the board, made the first time anything in it is looked at
+#[derive(Clone, Debug)]
+struct Chunk {
+    mines: Vec<bool>,
+    uncovered: Vec<bool>,
+    flagged: Vec<bool>,
+}
+
+// a board that goes on forever in every direction. (0, 0) is where the game starts and y goes up, like the rest of the game
+#[derive(Clone, Debug)]
+pub struct InfiniteBoard {
+    seed: u64,
+    density: f64,
+    chunks: HashMap<(i64, i64), Chunk>,
+    // safe cells uncovered so far, the score
+    revealed: usize,
+}
+
+impl InfiniteBoard {
+    pub fn new(seed: u64, density: f64) -> Self {
+        Self {
+            seed,
+            density,
+            chunks: HashMap::new(),
+            revealed: 0,
+        }
+    }
+
+    pub fn revealed(&self) -> usize {
+        self.revealed
+    }
+
+    // the chunk a cell is in and where it is in that chunk
+    fn locate(x: i64, y: i64) -> ((i64, i64), usize) {
+        let chunk = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+        let i = (y.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE) + x.rem_euclid(CHUNK_SIZE);
+
+        (chunk, i as usize)
+    }
+
+    fn chunk(&mut self, (cx, cy): (i64, i64)) -> &mut Chunk {
+        let (seed, density) = (self.seed, self.density);
+
+        self.chunks.entry((cx, cy)).or_insert_with(|| {
+            // every chunk gets its own stream of numbers from the seed, so it comes out the same whatever order they're made in
+            let chunk_seed = seed
+                ^ (cx as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+                ^ (cy as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+            let mut rng = StdRng::seed_from_u64(chunk_seed);
+            let cells = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+            let mines = (0..cells)
+                .map(|i| {
+                    let (x, y) = (
+                        (cx * CHUNK_SIZE) + (i as i64 % CHUNK_SIZE),
+                        (cy * CHUNK_SIZE) + (i as i64 / CHUNK_SIZE),
+                    );
+                    let mine = rng.gen_bool(density);
+
+                    // nothing on or around the start, so the first click opens something up
+                    mine && (x.abs() > 1 || y.abs() > 1)
+                })
+                .collect();
+
+            Chunk {
+                mines,
+                uncovered: vec![false; cells],
+                flagged: vec![false; cells],
+            }
+        })
+    }
+
+    pub fn is_mine(&mut self, x: i64, y: i64) -> bool {
+        let (chunk, i) = Self::locate(x, y);
+        self.chunk(chunk).mines[i]
+    }
+
+    pub fn is_uncovered(&mut self, x: i64, y: i64) -> bool {
+        let (chunk, i) = Self::locate(x, y);
+        self.chunk(chunk).uncovered[i]
+    }
+
+    pub fn is_flagged(&mut self, x: i64, y: i64) -> bool {
+        let (chunk, i) = Self::locate(x, y);
+        self.chunk(chunk).flagged[i]
+    }
+
+    // every cell touching (x, y), not including itself
+    pub fn neighbors(x: i64, y: i64) -> impl Iterator<Item = (i64, i64)> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&step| step != (0, 0))
+            .map(move |(dx, dy)| (x + dx, y + dy))
+    }
+
+    // how many mines touch (x, y)
+    pub fn adjacent_mines(&mut self, x: i64, y: i64) -> usize {
+        Self::neighbors(x, y)
+            .filter(|&(x, y)| self.is_mine(x, y))
+            .count()
+    }
+
+    // flag a covered cell, or take the flag off if it already has one
+    pub fn toggle_flag(&mut self, x: i64, y: i64) {
+        let (chunk, i) = Self::locate(x, y);
+        let chunk = self.chunk(chunk);
+
+        if !chunk.uncovered[i] {
+            chunk.flagged[i] = !chunk.flagged[i];
+        }
+    }
+
+    // uncover a cell, and if there aren't any mines around it everything around it as well.
+    // returns how many cells got uncovered
+    pub fn uncover(&mut self, x: i64, y: i64) -> usize {
+        let mut uncovered = 0;
+        let mut stack = vec![(x, y)];
+
+        while let Some((x, y)) = stack.pop() {
+            let (chunk, i) = Self::locate(x, y);
+            let chunk = self.chunk(chunk);
+
+            if chunk.uncovered[i] {
+                continue;
+            }
+
+            chunk.uncovered[i] = true;
+            chunk.flagged[i] = false;
+            uncovered += 1;
+
+            if chunk.mines[i] {
+                continue;
+            }
+
+            self.revealed += 1;
+
+            if self.adjacent_mines(x, y) == 0 {
+                stack.extend(Self::neighbors(x, y));
+            }
+        }
+
+        uncovered
+    }
+}
+
+// the infinite game: no edges and no winning, just see how many safe cells you can uncover before hitting a mine
+#[derive(Debug)]
+pub struct Infinite {
+    out: Stdout,
+    board: InfiniteBoard,
+    config: Config,
+    events: Events,
+    // the cell the cursor is on
+    selection: (i64, i64),
+    // the cell in the bottom left corner of the screen
+    view: (i64, i64),
+    // whether a mine has gone off
+    exploded: bool,
+    // the share of cells that are mines
+    density: f64,
+}
+
+impl Infinite {
+    pub fn new(density: f64, config: Config, events: Events) -> Result<Self> {
+        let mut out = stdout();
+
+        if config.set_title {
+            set_title(&mut out, "Minesweeper - Infinite")?;
+        }
+
+        Ok(Self {
+            out,
+            board: InfiniteBoard::new(rand::random(), density),
+            config,
+            events,
+            selection: (0, 0),
+            view: (0, 0),
+            exploded: false,
+            density,
+        })
+    }
+
+    // play a game, returns whether to play another one
+    pub fn run(&mut self) -> Result<bool> {
+        self.board = InfiniteBoard::new(rand::random(), self.density);
+        self.selection = (0, 0);
+        self.exploded = false;
+
+        // start with the first cell in the middle of the screen
+        let (columns, rows) = self.view_size()?;
+        self.view = (-columns / 2, -rows / 2);
+
+        self.out.execute(Clear(ClearType::All))?.execute(Show)?;
+        self.draw()?;
+
+        loop {
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                Event::Resize(..) => {
+                    self.out.execute(Clear(ClearType::All))?;
+                    self.draw()?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if let Some((dx, dy)) = self.config.movement.direction(key.code) {
+                self.selection = (self.selection.0 + dx as i64, self.selection.1 + dy as i64);
+                self.draw()?;
+                continue;
+            }
+
+            let (x, y) = self.selection;
+
+            match key.code {
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    if self.board.is_uncovered(x, y) {
+                        continue;
+                    }
+
+                    self.board.uncover(x, y);
+
+                    if self.board.is_mine(x, y) {
+                        self.ring(Sound::Explosion)?;
+                        return self.end_screen();
+                    }
+
+                    self.ring(Sound::Reveal)?;
+                }
+                KeyCode::Char('m' | '?') => {
+                    if self.board.is_uncovered(x, y) {
+                        continue;
+                    }
+
+                    self.board.toggle_flag(x, y);
+                    self.ring(Sound::Flag)?;
+                }
+                // ctrl+c quits the same way q does
+                _ if input::is_interrupt(&key) || key.code == KeyCode::Char('q') => {
+                    if self.board.revealed() > 0
+                        && self.config.confirm_quit
+                        && !self.confirm("quit? (y/n)")?
+                    {
+                        continue;
+                    }
+
+                    return Ok(false);
+                }
+                KeyCode::Char('r') => return Ok(true),
+                _ => continue,
+            }
+
+            self.draw()?;
+        }
+    }
+
+    // show every mine on screen along with the score and wait to hear whether to go again
+    fn end_screen(&mut self) -> Result<bool> {
+        self.exploded = true;
+        self.draw()?;
+
+        let message = format!(
+            "Boom! you uncovered {} safe cells, press r to play again and q to quit",
+            self.board.revealed()
+        );
+        self.draw_status_line(&message, true)?;
+
+        loop {
+            if let Event::Key(key) = self.events.read()? {
+                match key.code {
+                    _ if input::is_interrupt(&key) => return Ok(false),
+                    KeyCode::Char('q') => return Ok(false),
+                    KeyCode::Char('r') => return Ok(true),
+                    _ => continue,
+                }
+            }
+        }
+    }
+
+    // ask a yes or no question on the status line, anything other than y (or another ctrl+c) counts as no
+    fn confirm(&mut self, question: &str) -> Result<bool> {
+        self.draw_status_line(question, true)?;
+
+        let answer = loop {
+            if let Event::Key(key) = self.events.read()? {
+                break input::is_interrupt(&key) || matches!(key.code, KeyCode::Char('y' | 'Y'));
+            }
+        };
+
+        self.draw()?;
+
+        Ok(answer)
+    }
+
+    fn ring(&mut self, sound: Sound) -> Result<()> {
+        if self.config.theme.bell.rings(sound) {
+            self.out.execute(Print('\x07'))?;
+        }
+
+        Ok(())
+    }
+
+    // how many columns and rows of cells fit on screen
+    fn view_size(&self) -> Result<(i64, i64)> {
+        let (columns, rows) = terminal::size()?;
+        let safe_area = self.config.safe_area();
+        let (pitch_x, pitch_y) = self.pitch();
+
+        // one row goes to the status line
+        let rows = rows.saturating_sub(safe_area.top + safe_area.bottom + 1) as usize;
+
+        Ok((
+            (columns as usize / pitch_x).max(1) as i64,
+            rows.div_ceil(pitch_y).max(1) as i64,
+        ))
+    }
+
+    // how many columns and rows apart cells are drawn. there's no edge to put a border or labels on so grid mode is left out
+    fn pitch(&self) -> (usize, usize) {
+        let board = &self.config.theme.board;
+        (board.gap_x + board.cell_width(), board.gap_y + 1)
+    }
+
+    // the rows the board and the status line go on
+    fn rows(&self, view_rows: i64) -> (u16, u16) {
+        let top = self.config.safe_area().top;
+        let board_rows = (((view_rows as usize - 1) * self.pitch().1) + 1) as u16;
+
+        match self.config.status_bar {
+            StatusPosition::Top => (top + 1, top),
+            StatusPosition::Bottom => (top, top + board_rows),
+        }
+    }
+
+    // move the view just far enough to keep the cursor on screen
+    fn follow_cursor(&mut self, (columns, rows): (i64, i64)) {
+        let (x, y) = self.selection;
+
+        self.view.0 = self.view.0.clamp(x - columns + 1, x);
+        self.view.1 = self.view.1.clamp(y - rows + 1, y);
+    }
+
+    fn tile(&mut self, x: i64, y: i64) -> Tile {
+        let exploded = self.exploded && self.board.is_mine(x, y);
+
+        if self.board.is_flagged(x, y) && !exploded {
+            Tile::Marked
+        } else if !self.board.is_uncovered(x, y) && !exploded {
+            Tile::Covered
+        } else if self.board.is_mine(x, y) {
+            Tile::Mine
+        } else {
+            match self.board.adjacent_mines(x, y) {
+                0 => Tile::Empty,
+                num => Tile::Number(num),
+            }
+        }
+    }
+
+    // draw everything on screen in one go
+    fn draw(&mut self) -> Result<()> {
+        let (columns, rows) = self.view_size()?;
+        self.follow_cursor((columns, rows));
+
+        let (board_row, _) = self.rows(rows);
+        let (pitch_x, pitch_y) = self.pitch();
+        let gap = self.config.theme.board.gap();
+
+        for row in 0..rows {
+            // the top of the screen is the top of the view, y goes up the board
+            let y = self.view.1 + rows - 1 - row;
+            let mut line = String::new();
+
+            for column in 0..columns {
+                let tile = self.tile(self.view.0 + column, y);
+                line.push_str(&format!("{}{gap}", self.config.theme.board.glyph(tile)));
+            }
+
+            self.out
+                .queue(MoveTo(0, board_row + (row as usize * pitch_y) as u16))?
+                .queue(Print(line))?;
+        }
+
+        let status = format!(
+            "Infinite | {} safe cells uncovered | {},{}",
+            self.board.revealed(),
+            self.selection.0,
+            self.selection.1
+        );
+        self.draw_status_line(&status, false)?;
+
+        // put the cursor back on the selected cell
+        let column = (self.selection.0 - self.view.0) as usize * pitch_x;
+        let row = (self.view.1 + rows - 1 - self.selection.1) as usize * pitch_y;
+        self.out
+            .queue(MoveTo(column as u16, board_row + row as u16))?;
+
+        self.out.flush()?;
+        Ok(())
+    }
+
+    // replace what's on the status line, `overlay` for messages rather than the usual information
+    fn draw_status_line(&mut self, text: &str, overlay: bool) -> Result<()> {
+        let (_, rows) = self.view_size()?;
+        let (_, status_row) = self.rows(rows);
+        let theme = &self.config.theme;
+        let style = match overlay {
+            true => theme.overlay,
+            false => theme.status_bar,
+        };
+
+        self.out
+            .queue(MoveTo(0, status_row))?
+            .queue(Clear(ClearType::UntilNewLine))?
+            .queue(Print(style.apply(text)))?;
+
+        self.out.flush()?;
+        Ok(())
+    }
+}
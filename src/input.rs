@@ -0,0 +1,285 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+// how many cells the cursor moves when shift is held
+pub const JUMP_DISTANCE: usize = 5;
+
+// one terminal event and when it came in, a line in a --record-input file
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    // milliseconds since the game started
+    pub time: u64,
+    pub event: Event,
+}
+
+// raw mode stops the terminal turning ctrl+c into a signal, so it comes through as a key like any other
+// and it's up to us to treat it as a way out
+pub fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+// where `key` moves a cursor at `at` on a board `size` cells across and up, or None if it doesn't move it. page
+// up/page down/home/end jump to an edge, shift + a direction moves JUMP_DISTANCE cells and a direction on its own
+// moves `count` cells, or one if nothing was typed before it. with wrap_cursor it comes back in on the other side
+pub fn moved(
+    key: &KeyEvent,
+    at: (usize, usize),
+    size: (usize, usize),
+    config: &Config,
+    count: Option<usize>,
+) -> Option<(usize, usize)> {
+    let ((x, y), (top, right)) = (at, (size.1 - 1, size.0 - 1));
+
+    // keys that jump straight to an edge of the board
+    match key.code {
+        KeyCode::PageUp => return Some((x, top)),
+        KeyCode::PageDown => return Some((x, 0)),
+        KeyCode::Home => return Some((0, y)),
+        KeyCode::End => return Some((right, y)),
+        _ => (),
+    }
+
+    // shifted letters come through as uppercase so we lowercase them before looking up the direction
+    let (code, distance) = match key.code {
+        KeyCode::Char(char) if char.is_ascii_uppercase() => {
+            (KeyCode::Char(char.to_ascii_lowercase()), JUMP_DISTANCE)
+        }
+        code if key.modifiers.contains(KeyModifiers::SHIFT) => (code, JUMP_DISTANCE),
+        code => (code, count.unwrap_or(1)),
+    };
+
+    let direction = config.movement.direction(code)?;
+    let change = (
+        x as isize + direction.0 * distance as isize,
+        y as isize + direction.1 * distance as isize,
+    );
+
+    let change = if config.wrap_cursor {
+        // come back in on the opposite side of the same row/column
+        (
+            change.0.rem_euclid(size.0 as isize),
+            change.1.rem_euclid(size.1 as isize),
+        )
+    } else {
+        // stop at the edge of the board instead of running off it
+        (
+            change.0.clamp(0, right as isize),
+            change.1.clamp(0, top as isize),
+        )
+    };
+
+    Some((change.0 as usize, change.1 as usize))
+}
+
+// windows sends a key being let go of as well as it being pressed, the game only goes by presses
+pub fn is_release(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Release,
+            ..
+        })
+    )
+}
+
+// where the game gets its terminal events from.
+// this is separate from replays on purpose: replays remember what the player did on the board,
+// this remembers exactly what the terminal sent so input bugs can be reproduced on someone else's machine
+#[derive(Debug)]
+pub struct Events {
+    started: Instant,
+    source: Source,
+    // an event poll() had to read from the terminal to see it wasn't a key release, read() hands it out next
+    pending: Option<Event>,
+}
+
+#[derive(Debug)]
+enum Source {
+    Terminal,
+    // read from the terminal and write every event down as it comes in
+    Record(BufWriter<File>),
+    // events from a recording, sent at the times they were recorded.
+    // once they run out we go back to reading the terminal
+    Play(VecDeque<RecordedEvent>),
+}
+
+impl Events {
+    pub fn terminal() -> Self {
+        Self {
+            started: Instant::now(),
+            source: Source::Terminal,
+            pending: None,
+        }
+    }
+
+    pub fn record(path: &str) -> io::Result<Self> {
+        Ok(Self {
+            started: Instant::now(),
+            source: Source::Record(BufWriter::new(File::create(path)?)),
+            pending: None,
+        })
+    }
+
+    pub fn play(path: &str) -> io::Result<Self> {
+        let events = fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<_>>()
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+        Ok(Self {
+            started: Instant::now(),
+            source: Source::Play(events),
+            pending: None,
+        })
+    }
+
+    // whether events come straight from the terminal, rather than being recorded or played back
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.source, Source::Terminal)
+    }
+
+    // wait for the next event, like event::read()
+    pub fn read(&mut self) -> Result<Event> {
+        if let Source::Play(events) = &mut self.source {
+            if let Some(recorded) = events.pop_front() {
+                if let Some(wait) = self.due(recorded.time) {
+                    thread::sleep(wait);
+                }
+
+                return Ok(recorded.event);
+            }
+
+            self.source = Source::Terminal;
+        }
+
+        let event = match self.pending.take() {
+            Some(event) => event,
+            None => loop {
+                let event = event::read()?;
+                if !is_release(&event) {
+                    break event;
+                }
+            },
+        };
+
+        if let Source::Record(file) = &mut self.source {
+            let recorded = RecordedEvent {
+                time: self.started.elapsed().as_millis() as u64,
+                event: event.clone(),
+            };
+
+            // flush every line so the recording survives whatever bug is being chased crashing the game
+            writeln!(file, "{}", serde_json::to_string(&recorded)?)?;
+            file.flush()?;
+        }
+
+        Ok(event)
+    }
+
+    // whether an event is ready within `timeout`, like event::poll()
+    pub fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let next = match &self.source {
+            Source::Play(events) => events.front().map(|recorded| recorded.time),
+            _ => return self.poll_terminal(timeout),
+        };
+
+        match next {
+            Some(time) => match self.due(time) {
+                Some(wait) if wait > timeout => {
+                    thread::sleep(timeout);
+                    Ok(false)
+                }
+                _ => Ok(true),
+            },
+            None => self.poll_terminal(timeout),
+        }
+    }
+
+    // event::poll(), not counting key releases. they're read and dropped, and whatever else turns up is kept for read()
+    fn poll_terminal(&mut self, timeout: Duration) -> Result<bool> {
+        let until = Instant::now() + timeout;
+
+        while self.pending.is_none()
+            && event::poll(until.saturating_duration_since(Instant::now()))?
+        {
+            let event = event::read()?;
+            if !is_release(&event) {
+                self.pending = Some(event);
+            }
+        }
+
+        Ok(self.pending.is_some())
+    }
+
+    // how long until `time` milliseconds after the start, if it hasn't passed yet
+    fn due(&self, time: u64) -> Option<Duration> {
+        (self.started + Duration::from_millis(time)).checked_duration_since(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_cursor_moves_like_the_config_says() {
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        let mut config = Config::default();
+
+        let right = key(KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(moved(&right, (0, 0), (9, 9), &config, None), Some((1, 0)));
+        assert_eq!(
+            moved(&right, (0, 0), (9, 9), &config, Some(3)),
+            Some((3, 0))
+        );
+        assert_eq!(
+            moved(
+                &key(KeyCode::Char('D'), KeyModifiers::SHIFT),
+                (0, 0),
+                (9, 9),
+                &config,
+                None
+            ),
+            Some((JUMP_DISTANCE, 0))
+        );
+        assert_eq!(
+            moved(
+                &key(KeyCode::PageUp, KeyModifiers::NONE),
+                (4, 0),
+                (9, 9),
+                &config,
+                None
+            ),
+            Some((4, 8))
+        );
+        assert_eq!(moved(&right, (8, 0), (9, 9), &config, None), Some((8, 0)));
+
+        config.wrap_cursor = true;
+        assert_eq!(moved(&right, (8, 0), (9, 9), &config, None), Some((0, 0)));
+        assert_eq!(
+            moved(
+                &key(KeyCode::Char('m'), KeyModifiers::NONE),
+                (8, 0),
+                (9, 9),
+                &config,
+                None
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+// how many times each level keeps
+pub const PLACES: usize = 10;
+
+// the longest name anyone can put down
+pub const NAME_LENGTH: usize = 20;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    // milliseconds from the first reveal to the win
+    pub time: u64,
+}
+
+// the fastest unassisted wins on each level, with the names of whoever got them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    levels: BTreeMap<u8, Vec<Score>>,
+}
+
+impl Leaderboard {
+    pub fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        serde_json::from_str(&text).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    // the times on a level, fastest first
+    pub fn scores(&self, level: u8) -> &[Score] {
+        self.levels
+            .get(&level)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    // put a time in its place on the level's table, dropping whatever falls off the bottom
+    pub fn add(&mut self, level: u8, name: String, time: Duration) {
+        let scores = self.levels.entry(level).or_default();
+
+        scores.push(Score {
+            name,
+            time: time.as_millis() as u64,
+        });
+        scores.sort_by_key(|score| score.time);
+        scores.truncate(PLACES);
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("leaderboard.json"))
+    }
+}
@@ -0,0 +1,103 @@
+// the game itself, everything the minesweeper binary is made of. the binary is just the command line over this, so other
+// programs can use the same pieces, like the solver or ui::MinesweeperWidget to put a board in their own TUI.
+// the engine (the board, generator, solver, players and the bots' protocol) doesn't need a terminal, everything else comes
+// with the terminal feature. without std only the board, generator and solver are left, for no_std + alloc targets
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "terminal")]
+pub mod analysis;
+#[cfg(feature = "terminal")]
+pub mod autosave;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod board;
+#[cfg(feature = "terminal")]
+pub mod bug_report;
+#[cfg(feature = "terminal")]
+pub mod cast;
+#[cfg(feature = "terminal")]
+pub mod code;
+#[cfg(feature = "terminal")]
+pub mod color;
+#[cfg(feature = "terminal")]
+pub mod config;
+#[cfg(feature = "terminal")]
+pub mod coop;
+#[cfg(feature = "terminal")]
+pub mod daily;
+#[cfg(feature = "terminal")]
+pub mod dataset;
+#[cfg(feature = "terminal")]
+pub mod editor;
+#[cfg(feature = "std")]
+pub mod event;
+#[cfg(feature = "terminal")]
+pub mod game;
+pub mod generator;
+#[cfg(feature = "terminal")]
+pub mod ghost;
+#[cfg(feature = "std")]
+pub mod gui;
+#[cfg(feature = "terminal")]
+pub mod history;
+#[cfg(feature = "terminal")]
+pub mod human;
+#[cfg(feature = "terminal")]
+pub mod infinite;
+#[cfg(feature = "terminal")]
+pub mod input;
+#[cfg(feature = "terminal")]
+pub mod leaderboard;
+#[cfg(feature = "terminal")]
+pub mod net;
+#[cfg(feature = "terminal")]
+pub mod online;
+#[cfg(feature = "terminal")]
+pub mod paths;
+#[cfg(feature = "std")]
+pub mod player;
+#[cfg(feature = "terminal")]
+pub mod practice;
+#[cfg(feature = "std")]
+pub mod protocol;
+#[cfg(feature = "terminal")]
+pub mod puzzle;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(feature = "terminal")]
+pub mod race;
+#[cfg(feature = "terminal")]
+pub mod rating;
+#[cfg(feature = "terminal")]
+pub mod rawvf;
+#[cfg(feature = "terminal")]
+pub mod replay;
+#[cfg(feature = "terminal")]
+pub mod saves;
+#[cfg(feature = "std")]
+pub mod script;
+#[cfg(feature = "terminal")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod share;
+pub mod solver;
+#[cfg(feature = "terminal")]
+pub mod spectate;
+#[cfg(feature = "terminal")]
+pub mod ssh;
+#[cfg(feature = "terminal")]
+pub mod stats;
+#[cfg(feature = "terminal")]
+pub mod status;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+#[cfg(feature = "terminal")]
+pub mod theme;
+#[cfg(feature = "terminal")]
+pub mod ui;
+#[cfg(feature = "terminal")]
+pub mod versioned;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,661 +1,1152 @@
-use std::io::{stdout, Stdout, Write};
-
-use crossterm::{
-    cursor::{Hide, MoveTo, MoveToNextLine, Show},
-    event::{self, Event, KeyCode},
-    style::{Print, Stylize, StyledContent, self, Color},
-    terminal::{self, Clear, ClearType},
-    ExecutableCommand, Result,
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
 };
 
-use rand::prelude::SliceRandom;
+use clap::ArgMatches;
 
-const MENU: &'static str = r#"Welcome to Minesweeper
-Copyright 2022 Grant Handy
-
-Controls:
-    q - quit
-    r - restart
-    arrow keys/wasd - navigate board
-    enter/space - uncover cell
-    m/? - mark cell
-
-1. Beginner – 9 * 9 Board and 10 Mines
-2. Intermediate – 16 * 16 Board and 40 Mines
-3. Advanced – 24 * 24 Board and 99 Mines"#;
-
-const EMPTY: char = ' ';
-const MINE: char = '!';
-const COVERED: char = '·';
-const MARKED: char = '?';
-
-// characters (spaces) between cells (default "   ")
-const SPACE_WIDTH: &'static str = " ";
-// number of newlines inbetween lines
-const SPACE_HEIGHT: usize = 0;
+use minesweeper::{
+    analysis, bench, board, bug_report, cast, code, config, coop, daily, dataset, editor, game,
+    generator, gui, human, infinite, input, net, paths, player, practice, protocol, puzzle, rating,
+    replay, script, spectate, ssh, stats, terminal, theme, versioned,
+};
 
-// uncover everything at the beginning
-// use this for debugging
-const SHOW_EVERYTHING: bool = false;
+use analysis::Judgement;
+use board::Board;
+use code::GameCode;
+use config::Config;
+use coop::Coop;
+use dataset::Generated;
+use editor::Editor;
+use game::Game;
+use generator::{Placement, Settings};
+use human::Human;
+use infinite::Infinite;
+use input::Events;
+use player::{Player, RandomPlayer, SolverPlayer};
+use practice::Practice;
+use puzzle::Pack;
+use rating::{Band, Rating};
+use replay::Replay;
+use spectate::Spectator;
+use stats::Stats;
+use terminal::TerminalGuard;
 
 fn main() {
     let app = clap::app_from_crate!()
         .arg(clap::arg!(-l --level <LEVEL> "Which level to play (1-3, defaults to 1 if other is specified)").required(false))
+        .arg(clap::arg!(-c --config <FILE> "Path to a YAML config file, instead of config.yaml in your config directory").required(false))
+        .arg(
+            clap::arg!(--profile <NAME> "Whose stats, saves and settings to use, for when more than one person plays on this computer")
+                .required(false)
+                .global(true),
+        )
+        .arg(clap::arg!(--theme <NAME> "Built-in theme to start from, over the config file's preset: default, classic, dark, light, retro-dos, colorblind or high-contrast").required(false))
+        .arg(clap::arg!(--"mine-char" <CHAR> "Character for mines, over the config file's").required(false))
+        .arg(clap::arg!(--"covered-char" <CHAR> "Character for covered cells").required(false))
+        .arg(clap::arg!(--"empty-char" <CHAR> "Character for uncovered cells with no mines around them").required(false))
+        .arg(clap::arg!(--"marked-char" <CHAR> "Character for marked cells").required(false))
+        .arg(clap::arg!(--"gap-x" <SPACES> "Spaces between cells").required(false))
+        .arg(clap::arg!(--"gap-y" <LINES> "Blank lines between rows").required(false))
+        .arg(clap::arg!(--"ignore-config-errors" "Play with the default settings if the config file has a mistake in it, instead of stopping"))
+        .arg(clap::arg!(--plain "Plain ASCII with no colors, this is the default when NO_COLOR is set or TERM=dumb"))
+        .arg(clap::arg!(--nf "Play without flags, no-flag games keep their own stats and best times"))
+        .arg(clap::arg!(--ghost "Race your best run on a board you've played before, shown dimly as you play it again from its code"))
+        .arg(clap::arg!(--infinite "Play on a board with no edges until you hit a mine").conflicts_with_all(&["level", "replay"]))
+        .arg(clap::arg!(--density <FRACTION> "Share of the infinite board's cells that are mines, like 0.18 (defaults to 0.2)").required(false).requires("infinite"))
+        .arg(clap::arg!(--practice "Drill common patterns like 1-2-1 on small boards").conflicts_with_all(&["level", "replay", "infinite"]))
+        .arg(clap::arg!(--replay <FILE> "Watch a recorded game").required(false))
+        .arg(
+            clap::arg!(--board <FILE> "Play on a board from a file (a text grid, or JSON if it ends in .json) instead of a generated one")
+                .required(false)
+                .conflicts_with_all(&["level", "replay", "infinite", "practice"]),
+        )
+        .arg(
+            clap::arg!(--code <CODE> "Play the board from a game code, shown at the end of every game")
+                .required(false)
+                .conflicts_with_all(&["level", "replay", "infinite", "practice", "board"]),
+        )
+        .arg(
+            clap::arg!(--daily "Play today's daily challenge, the same intermediate board for everyone until midnight UTC")
+                .conflicts_with_all(&["level", "replay", "infinite", "practice", "board", "code"]),
+        )
+        .arg(
+            clap::arg!(--marathon "Clear boards one after another on one clock until you hit a mine")
+                .conflicts_with_all(&["replay", "infinite", "practice", "board", "code", "daily"]),
+        )
+        .arg(clap::arg!(--ramp "Go up a level every few boards in a marathon").requires("marathon"))
+        .arg(
+            clap::arg!(--race "Take turns with a friend on the same boards, the faster clear takes each round")
+                .conflicts_with_all(&["replay", "infinite", "practice", "board", "code", "daily", "marathon", "lives"]),
+        )
+        .arg(
+            clap::arg!(--players <NAMES> "The two players in a race, separated by a comma")
+                .required(false)
+                .use_delimiter(true)
+                .number_of_values(2)
+                .requires("race"),
+        )
+        .arg(
+            clap::arg!(--handicaps <SECONDS> "Seconds added to each race player's clears, separated by a comma, like 0,15")
+                .required(false)
+                .use_delimiter(true)
+                .number_of_values(2)
+                .requires("race"),
+        )
+        .arg(
+            clap::arg!(--placement <PLACEMENT> "How the mines are spread out: uniform (the default), clustered or border")
+                .required(false)
+                .conflicts_with_all(&["replay", "infinite", "practice", "board", "code", "daily"]),
+        )
+        .arg(
+            clap::arg!(--"difficulty-rating" <BAND> "Only play boards that rate as easy, medium or hard")
+                .required(false)
+                .conflicts_with_all(&["replay", "infinite", "practice", "board", "code", "daily"]),
+        )
+        .arg(
+            clap::arg!(--protocol "Let another program play: board states go out as lines of JSON on stdout and moves come in on stdin")
+                .conflicts_with_all(&["replay", "infinite", "practice", "code", "daily", "marathon", "lives", "race"]),
+        )
+        .arg(
+            clap::arg!(--gui "Play in a desktop window with the mouse, needs the gui feature")
+                .conflicts_with_all(&["replay", "infinite", "practice", "code", "daily", "marathon", "lives", "protocol", "race"]),
+        )
+        .arg(
+            clap::arg!(--speedrun "Time the game to the millisecond, split at the first opening, half cleared and the last flag against your best run")
+                .conflicts_with_all(&["replay", "infinite", "practice", "marathon", "race", "protocol", "gui"]),
+        )
+        .arg(
+            clap::arg!(--"time-limit" <SECONDS> "Clear the board within this many seconds or lose, timed games keep their own stats")
+                .required(false)
+                .conflicts_with_all(&["replay", "infinite", "practice", "daily", "marathon", "race", "protocol", "gui"]),
+        )
+        .arg(clap::arg!(--lives <N> "Play with N lives, each mine you hit costs one and gets flagged instead of ending the game").required(false))
+        .arg(clap::arg!(--spectators <ADDRESS> "Let others watch with minesweeper spectate: a port for TCP, or a path with a / in it for a local socket").required(false))
+        .arg(clap::arg!(--"dump-board" <FILE> "Write the board as it was when you quit to a file, as a text grid or JSON if it ends in .json").required(false))
+        .arg(clap::arg!(--"record-input" <FILE> "Write every terminal event to a file, for reproducing input bugs").required(false))
+        .arg(clap::arg!(--"play-input" <FILE> "Play back terminal events written with --record-input").required(false).conflicts_with("record-input"))
+        .arg(clap::arg!(--"bug-report" <FILE> "Write your terminal details, config, last crash and last game to an archive to attach to a bug report").required(false))
+        .arg(clap::arg!(--report <FILE> "Write a markdown report explaining the game given with --replay instead of watching it").required(false).requires("replay"))
+        .arg(clap::arg!(--cast <FILE> "Write the game given with --replay as an asciinema cast instead of watching it").required(false).requires("replay"))
+        .subcommand(
+            clap::App::new("puzzle")
+                .about("Play through a pack of puzzle boards, picking up from the first one that hasn't been solved")
+                .arg(clap::arg!(<PACK> "A YAML puzzle pack")),
+        )
+        .subcommand(
+            clap::App::new("host")
+                .about("Wait for someone to join and race them on the same board over the network")
+                .arg(clap::arg!(--port <PORT> "The port to listen on (defaults to 7777)").required(false))
+                .arg(clap::arg!(-l --level <LEVEL> "Which level to race on (1-3, defaults to 1)").required(false))
+                .arg(
+                    clap::arg!(--handicaps <SECONDS> "Seconds added to your clear and then theirs, like 0,15")
+                        .required(false)
+                        .use_delimiter(true)
+                        .number_of_values(2),
+                ),
+        )
+        .subcommand(
+            clap::App::new("join")
+                .about("Race someone who's hosting over the network")
+                .arg(clap::arg!(<ADDRESS> "Where they're hosting, like 192.168.1.20:7777 (the port defaults to 7777)")),
+        )
+        .subcommand(
+            clap::App::new("coop")
+                .about("Play one board together over the network, everyone with their own cursor")
+                .setting(clap::AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    clap::App::new("host")
+                        .about("Wait for everyone to join, then start")
+                        .arg(clap::arg!(--port <PORT> "The port to listen on (defaults to 7777)").required(false))
+                        .arg(clap::arg!(--players <N> "How many are playing, you included (2-4, defaults to 2)").required(false))
+                        .arg(clap::arg!(-l --level <LEVEL> "Which level to play (1-3, defaults to 1)").required(false)),
+                )
+                .subcommand(
+                    clap::App::new("join")
+                        .about("Join someone who's hosting")
+                        .arg(clap::arg!(<ADDRESS> "Where they're hosting, like 192.168.1.20:7777 (the port defaults to 7777)")),
+                ),
+        )
+        .subcommand(
+            clap::App::new("spectate")
+                .about("Watch a game that's taking spectators")
+                .arg(clap::arg!(<ADDRESS> "Where it's taking them, like 192.168.1.20:7777 or /tmp/minesweeper.sock")),
+        )
+        .subcommand(
+            clap::App::new("edit")
+                .about("Make a board by hand, try it out and add it to a puzzle pack")
+                .arg(clap::arg!(<PACK> "The puzzle pack to add boards to, made if it isn't there"))
+                .arg(clap::arg!(--width <CELLS> "Width of the board (defaults to 9)").required(false))
+                .arg(clap::arg!(--height <CELLS> "Height of the board (defaults to 9)").required(false)),
+        )
+        .subcommand(
+            clap::App::new("generate")
+                .about("Print generated boards with their seeds, codes and 3BV without playing them, for testing solvers")
+                .arg(clap::arg!(-n --count <N> "How many boards to make (defaults to 1)").required(false))
+                .arg(clap::arg!(-l --level <LEVEL> "Which level to make boards for (1-3, defaults to 1)").required(false))
+                .arg(clap::arg!(--format <FORMAT> "json for a line of JSON per board (the default) or text for grids").required(false))
+                .arg(clap::arg!(--placement <PLACEMENT> "How the mines are spread out: uniform (the default), clustered or border").required(false))
+                .arg(clap::arg!(--seed <SEED> "Seed for the first board, each one after uses the next seed (random if not given)").required(false))
+                .arg(clap::arg!(-o --out <DIR> "Write each board to its own file in DIR instead of printing them, ready for --board").required(false)),
+        )
+        .subcommand(
+            clap::App::new("solve")
+                .about("Work through a board file with the solver and print every move it makes and why")
+                .arg(clap::arg!(<FILE> "A board file, a text grid or JSON if it ends in .json"))
+                .arg(clap::arg!(--check "Only print the outcome, and exit with an error if the board needs a guess")),
+        )
+        .subcommand(
+            clap::App::new("rate")
+                .about("Print how hard board files are: their 3BV, openings, islands and how many guesses the solver needs")
+                .arg(clap::arg!(<FILE>... "Board files, text grids or JSON if they end in .json")),
+        )
+        .subcommand(
+            clap::App::new("bench")
+                .about("Play generated boards with the solver and report its win rate and how fast generating and playing are")
+                .arg(clap::arg!(--player <PLAYER> "Who plays: solver (the default), random to compare against, or human to play them yourself").required(false))
+                .arg(
+                    clap::arg!(--script <FILE> "Have a Rhai script with a next_action(board) function play, needs the scripting feature")
+                        .required(false)
+                        .conflicts_with("player"),
+                )
+                .arg(clap::arg!(--games <N> "How many games to play (defaults to 1000)").required(false))
+                .arg(clap::arg!(-l --level <LEVEL> "Which level to play (1-3, defaults to 1)").required(false))
+                .arg(clap::arg!(--placement <PLACEMENT> "How the mines are spread out: uniform (the default), clustered or border").required(false))
+                .arg(clap::arg!(--seed <SEED> "Seed for the first board, each one after uses the next seed (random if not given)").required(false)),
+        )
+        .subcommand(
+            clap::App::new("serve-ssh")
+                .about("Let people play over SSH, each connection getting a game of its own, needs the ssh feature")
+                .arg(clap::arg!(--port <PORT> "The port to listen on (defaults to 2222)").required(false))
+                .arg(clap::arg!(--"host-key" <FILE> "Where the server's key is kept, made if it isn't there (defaults to one in the data directory)").required(false))
+                .arg(clap::arg!(--"max-players" <N> "How many can play at once (defaults to 16)").required(false))
+                .arg(clap::arg!([ARGS]... "Options every game is started with, after a --, like -- --level 2").last(true)),
+        )
+        .subcommand(
+            clap::App::new("stats")
+                .about("Back up your stats, or bring them over from another computer")
+                .setting(clap::AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    clap::App::new("export")
+                        .about("Print every game and marathon you've played")
+                        .arg(clap::arg!(--format <FORMAT> "json (the default), the same as stats.json, or csv for a spreadsheet").required(false))
+                        .arg(clap::arg!(-o --out <FILE> "Write them to FILE instead of printing them").required(false)),
+                )
+                .subcommand(
+                    clap::App::new("import")
+                        .about("Add the games and marathons from an export to yours, skipping any that are already there")
+                        .arg(clap::arg!(<FILE> "An export or another stats.json, read as CSV if it ends in .csv")),
+                ),
+        )
+        .subcommand(
+            clap::App::new("init-config")
+                .about("Write a config file with every setting explained to your config directory")
+                .arg(clap::arg!(--force "Replace the config file if there already is one")),
+        )
         .get_matches();
 
-    let mut game = match Game::new(app.value_of("level")) {
-        Ok(game) => game,
-        Err(error) => {
-            Game::reset_terminal().unwrap();
-            eprintln!("Game initialization error: {error}");
+    // everything below reads and writes the profile's files
+    if let Err(error) = paths::set_profile(app.value_of("profile").map(str::to_string)) {
+        eprintln!("Profile error: {error}");
+        std::process::exit(1);
+    }
+
+    if let Some(init) = app.subcommand_matches("init-config") {
+        if let Err(error) = init_config(init.is_present("force")) {
+            eprintln!("Config error: {error}");
             std::process::exit(1);
         }
-    };
 
-    loop {
-        match game.run() {
-            Ok(res) => match res {
-                true => continue,
-                false => break,
-            }
-            Err(error) => {
-                Game::reset_terminal().unwrap();
-                eprintln!("Game runtime error: {error}");
-                std::process::exit(1);
-            }
+        return;
+    }
+
+    if let Some(stats) = app.subcommand_matches("stats") {
+        if let Err(error) = stats_command(stats) {
+            eprintln!("Stats error: {error}");
+            std::process::exit(1);
         }
+
+        return;
     }
 
-    Game::exit_message().unwrap();
-}
+    if let Some(generate) = app.subcommand_matches("generate") {
+        if let Err(error) = generate_boards(generate) {
+            eprintln!("Generate error: {error}");
+            std::process::exit(1);
+        }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-enum CellType {
-    Empty,
-    Adjacent(usize),
-    Mine,
-}
+        return;
+    }
 
-enum Input {
-    // Space / Enter
-    Select,
-    // Returns the next cursor direction from arrow keys/wasd
-    Direction((usize, usize)),
-    // q
-    Quit,
-    // m
-    Mark,
-    // r
-    Restart,
-}
+    if let Some(solve) = app.subcommand_matches("solve") {
+        match solve_board(solve) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(error) => {
+                eprintln!("Solve error: {error}");
+                std::process::exit(1);
+            }
+        }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Cell {
-    covered: bool,
-    cell_type: CellType,
-    marked: bool,
-}
+        return;
+    }
 
-#[derive(Debug)]
-pub struct Game {
-    // we use a single Stdout for simplicity
-    out: Stdout,
-    // board data: Y<X<Cell>>
-    data: Vec<Vec<Cell>>,
-    // number of mines on the board
-    num_mines: usize,
-    // width of the board
-    width: usize,
-    // height of the board
-    height: usize,
-    // coordinates of where our cursor is at the moment
-    selection: (usize, usize),
-    // if the board has been touched
-    is_touched: bool,
-    // if we should show everything
-    show_everything: bool,
-}
+    if let Some(rate) = app.subcommand_matches("rate") {
+        if let Err(error) = rate_boards(rate) {
+            eprintln!("Rate error: {error}");
+            std::process::exit(1);
+        }
 
-impl Game {
-    pub fn new(level: Option<&str>) -> Result<Self> {
-        let mut out = stdout();
-        let data = Vec::new();
-        let is_touched = false;
+        return;
+    }
 
-        let level = match level {
-            Some(level) => level.parse::<u8>().unwrap_or(1),
-            None => Self::choose_level(&mut out)?,
+    // -c wins, otherwise the config file in the platform's config directory gets used if there is one
+    let config_path = app
+        .value_of("config")
+        .map(PathBuf::from)
+        .or_else(|| paths::config_file().filter(|path| path.exists()));
+
+    if let Some(bench) = app.subcommand_matches("bench") {
+        let level = parsed(bench, "level")
+            .filter(|level| (1..=3).contains(level))
+            .unwrap_or(1);
+        let settings = Settings {
+            placement: parsed(bench, "placement").unwrap_or_default(),
+            ..Game::level_settings(level)
         };
+        let seed = parsed(bench, "seed").unwrap_or_else(rand::random);
+
+        let results = match bench.value_of("player") {
+            // a person gets through a lot fewer boards than the solver
+            Some("human") => {
+                let config = load_config(&app, config_path.as_deref());
+                let games = parsed(bench, "games").unwrap_or(10);
+
+                bench_human(config, settings, games, seed).unwrap_or_else(|error| {
+                    let _ = terminal::restore();
+                    eprintln!("Bench error: {error}");
+                    std::process::exit(1);
+                })
+            }
+            player => {
+                let games = parsed(bench, "games").unwrap_or(1000);
+                let player: Result<Box<dyn Player>, String> =
+                    match (bench.value_of("script"), player.unwrap_or("solver")) {
+                        (Some(path), _) => script::load(Path::new(path)),
+                        (None, "solver") => Ok(Box::new(SolverPlayer::default())),
+                        (None, "random") => Ok(Box::new(RandomPlayer::new(seed))),
+                        (None, name) => Err(format!(
+                            "{name} isn't a player, pick solver, random or human"
+                        )),
+                    };
+
+                let mut player = player.unwrap_or_else(|error| {
+                    eprintln!("Bench error: {error}");
+                    std::process::exit(1);
+                });
 
-        let (width, height) = match level {
-            1 => (9, 9),
-            2 => (16, 16),
-            3 => (24, 24),
-            _ => (9, 9),
+                bench::run(settings, games, seed, player.as_mut())
+            }
         };
 
-        let num_mines = match level {
-            1 => 10,
-            2 => 40,
-            3 => 99,
-            _ => 10,
-        };
+        println!(
+            "{} games of {}, {} placement",
+            results.games,
+            game::level_name(level),
+            settings.placement.name()
+        );
+        print!("{results}");
 
-        // starts at 0!! the board starts at 1.
-        let selection = ((width / 2), (height / 2));
-        let show_everything = SHOW_EVERYTHING;
+        return;
+    }
+
+    if let Some(serve) = app.subcommand_matches("serve-ssh") {
+        let port = parsed(serve, "port").unwrap_or(ssh::PORT);
+        let max_players = parsed(serve, "max-players")
+            .filter(|&players| players > 0)
+            .unwrap_or(ssh::MAX_PLAYERS);
+        let args = serve
+            .values_of("ARGS")
+            .map(|args| args.map(str::to_string).collect())
+            .unwrap_or_default();
+
+        if let Err(error) = ssh::serve(
+            port,
+            serve.value_of("host-key").map(Path::new),
+            max_players,
+            args,
+        ) {
+            eprintln!("SSH error: {error}");
+            std::process::exit(1);
+        }
 
-        Ok(Self {
-            out,
-            data,
-            num_mines,
-            width,
-            height,
-            selection,
-            is_touched,
-            show_everything,
-        })
+        return;
     }
 
-    pub fn run(&mut self) -> Result<bool> {
-        self.create_blank_board();
+    if let Some(path) = app.value_of("bug-report") {
+        match bug_report::write(path, config_path.as_deref()) {
+            Ok(()) => println!("Wrote a bug report to {path}, please attach it to your issue."),
+            Err(error) => {
+                eprintln!("Bug report error: {error}");
+                std::process::exit(1);
+            }
+        }
 
-        // reset data from last game
-        self.selection = ((self.width / 2), (self.height / 2));
-        self.show_everything = SHOW_EVERYTHING;
-        self.is_touched = false;
+        return;
+    }
 
-        terminal::enable_raw_mode()?;
+    let config = load_config(&app, config_path.as_deref());
+
+    let density = parsed(&app, "density").unwrap_or(infinite::DENSITY);
+    if !infinite::DENSITY_RANGE.contains(&density) {
+        let (low, high) = (
+            infinite::DENSITY_RANGE.start(),
+            infinite::DENSITY_RANGE.end(),
+        );
+        eprintln!(
+            "Density error: {density} is out of range, it has to be between {low} and {high}"
+        );
+        std::process::exit(1);
+    }
 
-        // show the cursor
-        self.out.execute(Show)?;
+    let placement: Option<Placement> = parsed(&app, "placement");
+    let wanted_rating: Option<Band> = parsed(&app, "difficulty-rating");
 
-        // draw the boards initial state
-        self.draw_board()?;
-        self.update_cursor()?;
+    let time_limit: Option<f64> = parsed(&app, "time-limit");
+    if let Some(seconds) = time_limit.filter(|seconds| !(*seconds > 0.0 && seconds.is_finite())) {
+        eprintln!(
+            "Time limit error: {seconds} isn't a number of seconds the board could be cleared in"
+        );
+        std::process::exit(1);
+    }
 
-        loop {
-            // this event blocks the thread until we get a keypress
-            let event = event::read()?;
-
-            // get an Input from the event
-            match self.get_input(event) {
-                // if we have one...
-                Some(input) => match input {
-                    // if it's a new direction update the cursor and reload the loop.
-                    Input::Direction(next_selection) => {
-                        self.selection = next_selection;
-                        self.update_cursor()?;
-                        continue;
-                    }
-                    // if the user said to mark the cell
-                    Input::Mark => {
-                        // if it isn't already marked and uncovered
-                        if !self.get_current_cell().marked && self.get_current_cell().covered {
-                            // mark it
-                            self.data[self.selection.1][self.selection.0].marked = true;
-                        } else if self.get_current_cell().covered {
-                            // otherwise unmark it if it's covered
-                            self.data[self.selection.1][self.selection.0].marked = false;
-                        } else {
-                            // if it's uncovered already then restart the loop. no need to redraw and fill up the terminal buffer.
-                            continue;
-                        }
-                    }
-                    // if the user quit then end the function sending "false" which means don't restart it. Go to the exit message.
-                    Input::Quit => {
-                        return Ok(false);
-                    },
-                    // if the user selected the cell...
-                    Input::Select => {
-                        // if we haven't touched the board yet populate the board so the user doesn't click on a mine their first try
-                        if !self.is_touched {
-                            self.is_touched = true;
-                            self.populate_board();
-                        }
-
-                        // if we clicked on an uncovered on restart the loop and don't redraw
-                        if !self.get_current_cell().covered {
-                            continue;
-                        }
-
-                        // uncover the cell
-                        self.uncover_cell(self.selection);
-                    }
-                    Input::Restart => return Ok(true),
-                },
-                // if the input was not a recognized one then restart the loop and wait for the next input.
-                // the less times we redraw the board the better, we don't want to fill up the terminal buffer.
-                None => continue,
+    let pack = match app
+        .subcommand_matches("puzzle")
+        .and_then(|puzzle| puzzle.value_of("PACK"))
+    {
+        Some(path) => match Pack::load(Path::new(path)) {
+            Ok(pack) => Some(pack),
+            Err(error) => {
+                eprintln!("Puzzle pack error in {path}: {error}");
+                std::process::exit(1);
             }
+        },
+        None => None,
+    };
 
-            // if we clicked on a mine go to the losing screen.
-            if self.get_current_cell().cell_type == CellType::Mine
-                && self.get_current_cell().covered == false
-            {
-                return self.end_screen("You lost! press r to try again and q to quit");
+    let board = match app.value_of("board") {
+        Some(path) => match Board::load(Path::new(path)) {
+            Ok(board) => Some(board),
+            Err(error) => {
+                eprintln!("Board error in {path}: {error}");
+                std::process::exit(1);
             }
+        },
+        None => None,
+    };
 
-            // if we won go to the winning screen
-            if self.has_won() {
-                return self.end_screen("You won! press r to play again and q to quit");
-            }
+    if app.is_present("protocol") {
+        let level = parsed(&app, "level")
+            .filter(|level| (1..=3).contains(level))
+            .unwrap_or(1);
+        let settings = Settings {
+            placement: placement.unwrap_or_default(),
+            ..Game::level_settings(level)
+        };
 
-            // update the board on screen after everything else is done
-            self.draw_board()?;
+        if let Err(error) = protocol::run(settings, board, io::stdin().lock(), io::stdout().lock())
+        {
+            eprintln!("Protocol error: {error}");
+            std::process::exit(1);
         }
+
+        return;
     }
 
-    // the screen that shows up when you lose or win
-    fn end_screen(&mut self, message: &str) -> Result<bool> {
-        // hide the cursor
-        self.out.execute(Hide)?;
+    if app.is_present("gui") {
+        let level = parsed(&app, "level")
+            .filter(|level| (1..=3).contains(level))
+            .unwrap_or(1);
+        let settings = Settings {
+            placement: placement.unwrap_or_default(),
+            ..Game::level_settings(level)
+        };
+
+        if let Err(error) = gui::run(settings, board) {
+            eprintln!("Couldn't open the window: {error}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
 
-        // show everything to the user because they've lost
-        // it's nice for them to see how they could've won
-        self.show_everything = true;
+    let replay = match app.value_of("replay").map(Replay::load) {
+        Some(Ok(replay)) => Some(replay),
+        Some(Err(error)) => {
+            eprintln!("Replay error: {error}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
 
-        // update the board state
-        self.draw_board()?;
+    if let (Some(replay), Some(path)) = (&replay, app.value_of("report")) {
+        if let Err(error) = fs::write(path, analysis::analyse(replay).report()) {
+            eprintln!("Report error: {error}");
+            std::process::exit(1);
+        }
 
-        // print the message at the bottom of the board
-        self.out
-            .execute(MoveTo(0, (((SPACE_HEIGHT + 1) * self.height) + 1) as u16))?
-            .execute(Print(message.bold()))?;
+        return;
+    }
 
-        // loop through the events.
-        loop {
-            let event = event::read()?;
-
-            match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Char(char) => match char {
-                        // return false because we don't want to restart
-                        'q' => return Ok(false),
-                        // return true because we want to restart
-                        'r' => return Ok(true),
-                        _ => continue,
-                    },
-                    _ => continue,
-                },
-                _ => continue,
-            }
+    if let (Some(replay), Some(path)) = (&replay, app.value_of("cast")) {
+        if let Err(error) = fs::write(path, cast::export(replay, &config)) {
+            eprintln!("Cast error: {error}");
+            std::process::exit(1);
         }
+
+        return;
     }
 
-    fn has_won(&mut self) -> bool {
-        let mut num_uncovered_cells = 0;
+    let events = match (app.value_of("record-input"), app.value_of("play-input")) {
+        (Some(path), _) => Events::record(path),
+        (_, Some(path)) => Events::play(path),
+        (None, None) => Ok(Events::terminal()),
+    };
+
+    let events = match events {
+        Ok(events) => events,
+        Err(error) => {
+            eprintln!("Input recording error: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    // co-op is set up before the terminal's taken over, so there's somewhere to say who we're waiting for
+    let coop = app.subcommand_matches("coop").map(|coop| match (coop.subcommand_matches("host"), coop.subcommand_matches("join")) {
+        (Some(host), _) => {
+            let level = parsed(host, "level").filter(|level| (1..=3).contains(level)).unwrap_or(1);
+            let port = parsed(host, "port").unwrap_or(net::PORT);
+            let players = parsed(host, "players").unwrap_or(2).clamp(2, coop::MAX_PLAYERS);
+            let settings = Game::level_settings(level);
+            let code = GameCode {
+                seed: rand::random(),
+                settings,
+                start: settings.start(),
+            };
 
-        for y in &self.data {
-            for cell in y {
-                if !cell.covered {
-                    if cell.cell_type == CellType::Mine {
-                        return false;
-                    }
+            println!("Waiting for {} more to join with: minesweeper coop join <this computer's address>:{port}", players - 1);
+            coop::host(port, players, code)
+        }
+        (None, join) => {
+            println!("Waiting for everyone to join");
+            coop::join(join.and_then(|join| join.value_of("ADDRESS")).unwrap_or_default())
+        }
+    });
 
-                    num_uncovered_cells += 1;
+    let coop = match coop.transpose() {
+        Ok(coop) => coop,
+        Err(error) => {
+            eprintln!("Network error: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    // a race over the network is played on the board the host picked, like a game code
+    let versus = match (
+        app.subcommand_matches("host"),
+        app.subcommand_matches("join"),
+    ) {
+        (Some(host), _) => {
+            let level = parsed(host, "level")
+                .filter(|level| (1..=3).contains(level))
+                .unwrap_or(1);
+            let port = parsed(host, "port").unwrap_or(net::PORT);
+            let settings = Game::level_settings(level);
+            let code = GameCode {
+                seed: rand::random(),
+                settings,
+                start: settings.start(),
+            };
+
+            println!("Waiting for someone to join with: minesweeper join <this computer's address>:{port}");
+            net::host(port, code, handicaps(host)).map(|opponent| Some((code, opponent)))
+        }
+        (_, Some(join)) => net::join(join.value_of("ADDRESS").unwrap_or_default()).map(Some),
+        _ => Ok(None),
+    };
+
+    let (code, opponent) = match versus {
+        Ok(Some((code, opponent))) => (Some(code), Some(opponent)),
+        Ok(None) => (parsed(&app, "code"), None),
+        Err(error) => {
+            eprintln!("Network error: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    let spectators = match app.value_of("spectators").map(spectate::serve) {
+        Some(Ok(spectators)) => Some(spectators),
+        Some(Err(error)) => {
+            eprintln!("Couldn't take spectators: {error}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    // replays carry their own level so we don't show the menu for them
+    let level = replay.as_ref().map(|replay| replay.level.to_string());
+    let level = level.as_deref().or_else(|| app.value_of("level"));
+
+    terminal::install_panic_hook();
+
+    let guard = match TerminalGuard::enter() {
+        Ok(guard) => guard,
+        Err(error) => {
+            terminal::restore().unwrap();
+            eprintln!("Terminal setup error: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    if app.is_present("infinite") {
+        let mut game = match Infinite::new(density, config, events) {
+            Ok(game) => game,
+            Err(error) => {
+                drop(guard);
+                eprintln!("Game initialization error: {error}");
+                std::process::exit(1);
+            }
+        };
+
+        loop {
+            match game.run() {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(error) => {
+                    drop(guard);
+                    eprintln!("Game runtime error: {error}");
+                    std::process::exit(1);
                 }
             }
         }
 
-        if num_uncovered_cells == ((self.width * self.height) - self.num_mines) {
-            return true;
-        } else {
-            return false;
-        }
+        drop(guard);
+        Game::exit_message().unwrap();
+        return;
     }
 
-    // uncover the cell the cursor is currently on
-    fn uncover_cell(&mut self, cell: (usize, usize)) {
-        // clear the current cell
-        self.data[cell.1][cell.0].covered = false;
-        self.data[cell.1][cell.0].marked = false;
+    if let Some(spectate) = app.subcommand_matches("spectate") {
+        let address = spectate.value_of("ADDRESS").unwrap_or_default();
+        let result =
+            Spectator::connect(address, config, events).and_then(|mut spectator| spectator.run());
+        drop(guard);
 
-        // clear the empty cells around it if we're empty
-        if self.get_current_cell().cell_type == CellType::Empty {
-            self.remove_surrounding_empty_cells(cell);
+        if let Err(error) = result {
+            eprintln!("Couldn't watch {address}: {error}");
+            std::process::exit(1);
         }
+
+        Game::exit_message().unwrap();
+        return;
     }
 
-    // recursively remove the surrounding empty cells of a cell
-    fn remove_surrounding_empty_cells(&mut self, cell: (usize, usize)) {
-        for (x, y, cell_type) in self.get_surrounding_cells(cell) {
-            if cell_type == CellType::Empty && self.data[y][x].covered == true {
-                self.data[y][x].covered = false;
-                self.data[y][x].marked = false;
-                self.remove_surrounding_empty_cells((x, y));
-            } else {
-                self.data[y][x].covered = false;
-                self.data[y][x].marked = false;
-            }
+    if let Some(connection) = coop {
+        let result = Coop::new(connection, config, events).and_then(|mut coop| coop.run());
+        drop(guard);
+
+        if let Err(error) = result {
+            eprintln!("Game runtime error: {error}");
+            std::process::exit(1);
         }
-    }
 
-    // a simple shortcut function that gives us the Cell the cursor is at
-    fn get_current_cell(&self) -> Cell {
-        return self.data[self.selection.1][self.selection.0];
+        Game::exit_message().unwrap();
+        return;
     }
 
-    // update's the cursor's position on screen from memory. this doesn't take a terminal redraw.
-    fn update_cursor(&mut self) -> Result<()> {
-        let right = (self.selection.0 * (SPACE_WIDTH.chars().count() + 1)) as u16;
-        let up = ((self.height - (self.selection.1 + 1)) * (SPACE_HEIGHT + 1)) as u16;
+    // a puzzle pack and --practice are both played by the same thing, --practice just has its own built-in pack
+    if let Some(edit) = app.subcommand_matches("edit") {
+        let size = |name| parsed(edit, name).unwrap_or(9).clamp(1, editor::MAX_SIZE);
+        let path = PathBuf::from(edit.value_of("PACK").unwrap_or_default());
 
-        self.out.execute(MoveTo(right, up))?;
+        let result = Editor::new(path, size("width"), size("height"), config, events)
+            .and_then(|mut editor| editor.run());
+        drop(guard);
 
-        Ok(())
+        if let Err(error) = result {
+            eprintln!("Editor error: {error}");
+            std::process::exit(1);
+        }
+
+        Game::exit_message().unwrap();
+        return;
     }
 
-    // this was the most technical function in the program, it randomly places mines on the board
-    // the hardest part was not placing any mines where the user's cursor is, and not adjacent to the cursor either
-    // this is done so that the user's first click is not on a bomb or adjacent square so they can have a chance to win each time
-    fn populate_board(&mut self) {
-        // Random mine placement indice idea: credit @asuradev99
-        let num_cells = self.width * self.height;
+    if pack.is_some() || app.is_present("practice") {
+        let practice = match pack {
+            Some(pack) => Practice::pack(pack, config, events),
+            None => Practice::patterns(config, events),
+        };
+
+        let mut practice = match practice {
+            Ok(practice) => practice,
+            Err(error) => {
+                drop(guard);
+                eprintln!("Game initialization error: {error}");
+                std::process::exit(1);
+            }
+        };
+
+        loop {
+            match practice.run() {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(error) => {
+                    drop(guard);
+                    eprintln!("Game runtime error: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
 
-        let mut mine_indices: Vec<usize> = (0..num_cells).collect();
+        drop(guard);
+        Game::exit_message().unwrap();
+        return;
+    }
 
-        // remove the spot at our cursor from the indices
-        mine_indices.remove((self.selection.1 * self.width) + self.selection.0);
+    let game = match (board, code) {
+        (Some(board), _) => Game::with_board(board, config, events),
+        (None, Some(code)) => Game::with_code(code, config, events),
+        (None, None) if app.is_present("daily") => Game::daily(daily::today(), config, events),
+        (None, None) if app.is_present("marathon") => {
+            Game::marathon(level, app.is_present("ramp"), config, events)
+        }
+        (None, None) if app.is_present("race") => {
+            let players = match app.values_of("players") {
+                Some(mut names) => [0, 1].map(|_| names.next().unwrap_or_default().to_string()),
+                None => ["Player 1".to_string(), "Player 2".to_string()],
+            };
 
-        // remove all the spots around our cursor so that we don't click on an adjacent square.
-        for (x, y, _cell) in self.get_surrounding_cells(self.selection) {
-            // we need to search the indices by value for the correct index to remove using rposition.
-            // this is done because each time we remove a cell it skews the positions of all other ones by 1.
-            mine_indices.remove(
-                mine_indices
-                    .iter()
-                    .rposition(|&a| a == ((y * self.width) + x))
-                    .unwrap(),
-            );
+            Game::race(level, players, handicaps(&app), config, events)
+        }
+        // a game the last session was cut off in the middle of can be picked back up, unless there's a replay to watch
+        (None, None) if replay.is_none() && events.is_terminal() => {
+            Game::recover(level, config, events)
         }
+        (None, None) => Game::new(level, config, events),
+    };
+
+    let mut game = match game {
+        Ok(mut game) => {
+            if let Some(opponent) = opponent {
+                game.set_opponent(opponent);
+            }
+
+            if let Some(spectators) = spectators {
+                game.set_spectators(spectators);
+            }
+
+            if let Some(lives) = parsed(&app, "lives") {
+                game.set_lives(lives);
+            }
+
+            if app.is_present("speedrun") {
+                game.set_speedrun();
+            }
 
-        // shuffle mine placement using rand
-        mine_indices.shuffle(&mut rand::thread_rng());
+            if let Some(seconds) = time_limit {
+                game.set_time_limit(Duration::from_secs_f64(seconds));
+            }
 
-        // place mines on board based on indices
-        for i in &mine_indices[0..self.num_mines] {
-            let x = i % self.width;
-            let y = i / self.height;
-            self.data[y][x].cell_type = CellType::Mine;
+            if let Some(placement) = placement {
+                game.set_placement(placement);
+            }
+
+            if let Some(band) = wanted_rating {
+                game.set_wanted_rating(band);
+            }
+
+            game
+        }
+        Err(error) => {
+            drop(guard);
+            eprintln!("Game initialization error: {error}");
+            std::process::exit(1);
         }
+    };
 
-        // add "adjacent" cells based on where the bombs are
-        for y in 0..self.height {
-            for x in 0..self.width {
-                // if the cell is empty
-                if self.data[y][x].cell_type == CellType::Empty {
-                    // the number of adjacent mines to the cell
-                    let mut num_adj_mines = 0;
+    // the level menu's history can pick a game to watch too
+    let replay = match replay
+        .or_else(|| game.take_watching())
+        .map(|replay| game.offer_transform(replay))
+    {
+        Some(Ok(replay)) => Some(replay),
+        Some(Err(error)) => {
+            drop(guard);
+            eprintln!("Game runtime error: {error}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
 
-                    // go through all the surrounding cells and add one to num_adj_mines if the cell is a mine
-                    for cell in self.get_surrounding_cells((x, y)) {
-                        if cell.2 == CellType::Mine {
-                            num_adj_mines += 1;
-                        }
-                    }
+    #[cfg(feature = "tokio")]
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            drop(guard);
+            eprintln!("Couldn't start the async runtime: {error}");
+            std::process::exit(1);
+        }
+    };
 
-                    // if we have any adjacent mines set our cell's type as adjacent with the number of mines
-                    if num_adj_mines > 0 {
-                        self.data[y][x].cell_type = CellType::Adjacent(num_adj_mines);
-                    }
-                }
+    loop {
+        let res = match &replay {
+            Some(replay) => game.watch(replay),
+            // recorded and played back input only works on the sync loop
+            #[cfg(feature = "tokio")]
+            None if game.reads_terminal() => runtime.block_on(game.run_async()),
+            None => game.run(),
+        };
+
+        match res {
+            Ok(res) => match res {
+                true => continue,
+                false => break,
+            },
+            Err(error) => {
+                drop(guard);
+                eprintln!("Game runtime error: {error}");
+                std::process::exit(1);
             }
         }
     }
 
-    // draw the board to the terminal based on the game's internal state
-    fn draw_board(&mut self) -> Result<()> {
-        // clear the screen and move to 0,0
-        self.out
-            // .execute(Clear(ClearType::All))?
-            .execute(MoveTo(0, 0))?;
-
-        // draw all of the cells
-        // we reverse the iterator so that we'll show the data from the bottom up (1st quadrant of a Cartesian plane) while still drawing from the top down
-        for line in self.data.iter().rev() {
-            for cell in line {
-                // if the cell is marked we aren't showing everything
-                let cell = if cell.marked && !self.show_everything {
-                    // print the marked symbol as cyan and bold
-                    format!("{}{}", MARKED.cyan().bold(), SPACE_WIDTH)
-                // if the cell is covered and we aren't showing everything
-                } else if cell.covered && !self.show_everything {
-                    // print the covered symbol
-                    format!("{COVERED}{SPACE_WIDTH}")
-                } else {
-                    // else print the symbol from what the data is normally
-                    match cell.cell_type {
-                        CellType::Empty => format!("{EMPTY}{SPACE_WIDTH}"),
-                        CellType::Adjacent(num) => format!(
-                            "{}{}",
-                            Self::create_stylized_num(num).bold(),
-                            SPACE_WIDTH
-                        ),
-                        CellType::Mine => format!(
-                            "{}{}",
-                            MINE.red().bold(),
-                            SPACE_WIDTH
-                        ),
-                    }
-                };
+    // still on the game's screen, before it's put back
+    if let Err(error) = game.show_session() {
+        drop(guard);
+        eprintln!("Game runtime error: {error}");
+        std::process::exit(1);
+    }
 
-                self.out.execute(Print(cell))?;
-            }
+    drop(guard);
+    Game::exit_message().unwrap();
 
-            // move the correct number of lines :)
-            self.out
-                .execute(MoveToNextLine((SPACE_HEIGHT + 1) as u16))?;
+    if let Some(path) = app.value_of("dump-board") {
+        if let Err(error) = game.board().save(Path::new(path)) {
+            eprintln!("Couldn't write the board to {path}: {error}");
+            std::process::exit(1);
         }
+    }
 
-        self.update_cursor()?;
+    if let Some(summary) = game.exit_summary() {
+        print!("{summary}");
+    }
 
-        Ok(())
+    if let Some(standings) = game.race_standings() {
+        print!("\n{standings}\n");
     }
 
-    fn create_blank_board(&mut self) {
-        self.data.clear();
+    for text in game.shared() {
+        print!("\n{text}");
+    }
+}
 
-        for _y in 0..self.height {
-            let mut row_data = Vec::new();
+// an argument's value if it was given, exiting with clap's usual message if it doesn't parse
+fn parsed<T: FromStr>(app: &ArgMatches, name: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    app.is_present(name)
+        .then(|| app.value_of_t(name).unwrap_or_else(|error| error.exit()))
+}
 
-            for _x in 0..self.width {
-                row_data.push(Cell {
-                    covered: true,
-                    cell_type: CellType::Empty,
-                    marked: false,
-                });
+// the seconds added to each racer's clears, none unless they're given
+fn handicaps(app: &ArgMatches) -> [Duration; 2] {
+    let seconds = match app.is_present("handicaps") {
+        true => app
+            .values_of_t::<u64>("handicaps")
+            .unwrap_or_else(|error| error.exit()),
+        false => Vec::new(),
+    };
+
+    [0, 1].map(|player| Duration::from_secs(seconds.get(player).copied().unwrap_or(0)))
+}
+
+// minesweeper stats export and import
+fn stats_command(app: &ArgMatches) -> Result<(), String> {
+    if let Some(export) = app.subcommand_matches("export") {
+        let stats = Stats::load().map_err(|error| error.to_string())?;
+        let text = match export.value_of("format").unwrap_or("json") {
+            "json" => {
+                versioned::to_string_pretty(&stats).map_err(|error| error.to_string())? + "\n"
             }
+            "csv" => stats.to_csv(),
+            format => return Err(format!("{format} isn't a format, pick json or csv")),
+        };
 
-            self.data.push(row_data);
+        match export.value_of("out") {
+            Some(path) => {
+                fs::write(path, text).map_err(|error| format!("couldn't write {path}: {error}"))?
+            }
+            None => print!("{text}"),
         }
     }
 
-    fn choose_level<W: Write>(out: &mut W) -> Result<u8> {
-        let mut level = 1;
-        let mut draw = true;
+    if let Some(import) = app.subcommand_matches("import") {
+        let path = import.value_of("FILE").unwrap_or_default();
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("couldn't read {path}: {error}"))?;
+        let imported = match Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+        {
+            true => Stats::from_csv(&text)?,
+            false => versioned::from_str(&text).map_err(|error| error.to_string())?,
+        };
+
+        let (games, marathons) = Stats::import(imported).map_err(|error| error.to_string())?;
+        println!("Added {games} games and {marathons} marathons, anything that was already there was left alone.");
+    }
 
-        terminal::enable_raw_mode()?;
+    Ok(())
+}
 
-        // hide the cursor
-        out.execute(Hide)?.execute(Clear(ClearType::All))?;
+// make boards without playing them and print them or write them to files
+fn generate_boards(app: &ArgMatches) -> Result<(), String> {
+    let count = parsed(app, "count").unwrap_or(1);
+    let level = parsed(app, "level")
+        .filter(|level| (1..=3).contains(level))
+        .unwrap_or(1);
+    let settings = Settings {
+        placement: parsed(app, "placement").unwrap_or_default(),
+        ..Game::level_settings(level)
+    };
+    let seed = parsed(app, "seed").unwrap_or_else(rand::random::<u64>);
+    let json = match app.value_of("format").unwrap_or("json") {
+        "json" => true,
+        "text" => false,
+        format => return Err(format!("{format} isn't a format, pick json or text")),
+    };
 
-        // loop on every keypress
-        loop {
-            if draw {
-                // clear the screen
-                out
-                    // .execute(Clear(ClearType::All))?
-                    .execute(MoveTo(0, 0))?;
-
-                // draw the menu
-                for line in MENU.split("\n") {
-                    // if the line as our number we draw it in bold to show our selection
-                    if line.contains(&format!("{}. ", level)) {
-                        out.execute(Print(line.bold()))?;
-                    } else {
-                        out.execute(Print(line))?;
-                    }
-
-                    out.execute(MoveToNextLine(1))?;
-                }
-            }
+    if let Some(dir) = app.value_of("out") {
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
 
-            // get our event
-            // this blocks so the loop doesn't run constantly
-            let event = event::read()?;
-
-            // get our next level from the key event
-            level = match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Up => level - 1,
-                    KeyCode::Down => level + 1,
-                    KeyCode::Enter => break,
-                    KeyCode::Char(char) => match char {
-                        ' ' => break,
-                        '1' => 1,
-                        '2' => 2,
-                        '3' => 3,
-                        'q' => {
-                            Self::exit_message()?;
-                            std::process::exit(0);
-                        },
-                        _ => {
-                            draw = false;
-                            continue;
-                        }
-                    },
-                    _ => {
-                        draw = false;
-                        continue;
-                    }
-                },
-                _ => {
-                    draw = false;
-                    continue;
-                }
-            };
+    for i in 0..count {
+        let generated = Generated::new(settings, seed.wrapping_add(i as u64));
+
+        match app.value_of("out") {
+            // numbered so they sort in the order they were made
+            Some(dir) => {
+                let name = format!(
+                    "board-{:0width$}.{}",
+                    i + 1,
+                    if json { "json" } else { "txt" },
+                    width = count.to_string().len()
+                );
+                let path = Path::new(dir).join(name);
+                let text = match json {
+                    true => serde_json::to_string_pretty(&generated)
+                        .map_err(|error| error.to_string())?,
+                    false => generated.board.to_text(),
+                };
 
-            // if we try to set level as a level out of bounds set it back in bounds
-            if level > 3 {
-                level = 3;
-            } else if level < 1 {
-                level = 1;
+                fs::write(&path, text)
+                    .map_err(|error| format!("couldn't write {}: {error}", path.display()))?;
             }
-
-            draw = true;
+            None if json => println!(
+                "{}",
+                serde_json::to_string(&generated).map_err(|error| error.to_string())?
+            ),
+            None => println!("{}", generated.text()),
         }
+    }
 
-        Self::reset_terminal()?;
+    Ok(())
+}
+
+// print how the solver clears a board file. false if it needed a guess and --check was given
+fn solve_board(app: &ArgMatches) -> Result<bool, String> {
+    let path = app.value_of("FILE").unwrap_or_default();
+    let mut board = Board::load(Path::new(path)).map_err(|error| format!("{path}: {error}"))?;
+    let quiet = app.is_present("check");
+    let first = first_click(&mut board).map_err(|error| format!("{path}: {error}"))?;
 
-        // return our level :)
-        Ok(level)
+    if let (Some((x, y)), false) = (first, quiet) {
+        println!("{} is the first click", board::cell_name(x, y));
     }
 
-    // make sure the terminal is back to normal
-    fn reset_terminal() -> Result<()> {
-        terminal::disable_raw_mode()?;
-        stdout()
-            .execute(Clear(ClearType::All))?
-            .execute(MoveTo(0, 0))?
-            .execute(Show)?
-            .flush()?;
+    let steps = analysis::solution(&board);
+    let moves = steps.len() + first.is_some() as usize;
 
-        Ok(())
+    if !quiet {
+        for step in &steps {
+            println!("{}", step.describe(&board));
+        }
     }
 
-    // print a nice goodbye message and clear the screen
-    fn exit_message() -> Result<()> {
-        Self::reset_terminal()?;
+    let guesses = steps
+        .iter()
+        .filter(|step| matches!(step.judgement, Judgement::ForcedGuess { .. }))
+        .count();
+    let exploded = steps.iter().filter(|step| step.exploded).count();
+
+    match guesses {
+        0 => println!(
+            "Cleared in {moves} {} without guessing.",
+            if moves == 1 { "move" } else { "moves" }
+        ),
+        _ => println!(
+            "Cleared in {moves} moves, but needed {guesses} {} and hit {exploded} {} on the way.",
+            if guesses == 1 { "guess" } else { "guesses" },
+            if exploded == 1 { "mine" } else { "mines" },
+        ),
+    }
 
-        stdout()
-            .execute(Print("Thanks for playing!"))?
-            .execute(MoveToNextLine(2))?;
-        Ok(())
+    Ok(guesses == 0 || !app.is_present("check"))
+}
+
+// print a line about how hard each board file is, so they can be sorted before going in a puzzle pack
+fn rate_boards(app: &ArgMatches) -> Result<(), String> {
+    for path in app.values_of("FILE").into_iter().flatten() {
+        let mut board = Board::load(Path::new(path)).map_err(|error| format!("{path}: {error}"))?;
+        let (three_bv, openings, islands) = (board.three_bv().1, board.openings(), board.islands());
+
+        first_click(&mut board).map_err(|error| format!("{path}: {error}"))?;
+        let steps = analysis::solution(&board);
+        let guesses = steps
+            .iter()
+            .filter(|step| matches!(step.judgement, Judgement::ForcedGuess { .. }))
+            .count();
+        let rating = Rating::from_solution(&board, &steps);
+
+        println!(
+            "{path}: 3BV {three_bv}, {openings} {}, {islands} {}, {guesses} {}, {}",
+            if openings == 1 { "opening" } else { "openings" },
+            if islands == 1 { "island" } else { "islands" },
+            if guesses == 1 { "guess" } else { "guesses" },
+            rating.band(),
+        );
     }
 
-    // return true if the cell exists on the board
-    fn cell_exists(&self, cell: (usize, usize)) -> bool {
-        if self.data.get(cell.1) != None && self.data[cell.1].get(cell.0) != None {
-            return true;
-        } else {
-            return false;
-        }
+    Ok(())
+}
+
+// give a board nobody's clicked on yet its first click in the middle, where generated boards are always safe. on a
+// board file the middle might be a wall or a mine, so it goes on the closest cell that isn't
+fn first_click(board: &mut Board) -> Result<Option<(usize, usize)>, String> {
+    let untouched = board
+        .positions()
+        .all(|(x, y)| !board.is_uncovered(x, y) || board.is_wall(x, y));
+
+    if !untouched {
+        return Ok(None);
     }
 
-    fn get_surrounding_cells(&self, cell: (usize, usize)) -> Vec<(usize, usize, CellType)> {
-        let cell = (cell.0 as isize, cell.1 as isize);
-        let directions = [
-            (-1, 1),  // NW
-            (-1, 0),  // W
-            (-1, -1), // SW
-            (0, 1),   // N
-            (0, -1),  // S
-            (1, 1),   // NE
-            (1, 0),   // E
-            (1, -1),  // SE
-        ];
+    let (middle_x, middle_y) = (board.width() / 2, board.height() / 2);
+    let (x, y) = board
+        .positions()
+        .filter(|&(x, y)| !board.is_wall(x, y) && !board.is_mine(x, y))
+        .min_by_key(|&(x, y)| x.abs_diff(middle_x).pow(2) + y.abs_diff(middle_y).pow(2))
+        .ok_or("there's no playable cell, every cell is a wall or a mine")?;
+
+    board.uncover(x, y);
+    Ok(Some((x, y)))
+}
 
-        let mut cells = Vec::new();
+// bench --player human: the boards are played in the terminal, and it's put back before the results are printed
+fn bench_human(
+    config: Config,
+    settings: Settings,
+    games: usize,
+    seed: u64,
+) -> io::Result<bench::Results> {
+    terminal::install_panic_hook();
+    let guard = TerminalGuard::enter()?;
+    let mut human = Human::new(config, Events::terminal())?;
+    let results = bench::run(settings, games, seed, &mut human);
+    drop(guard);
+
+    match human.error() {
+        Some(error) => Err(error),
+        None => Ok(results),
+    }
+}
 
-        for d in directions {
-            if self.cell_exists(((cell.0 + d.0) as usize, (cell.1 + d.1) as usize)) {
-                cells.push((
-                    (cell.0 + d.0) as usize,
-                    (cell.1 + d.1) as usize,
-                    self.data[(cell.1 + d.1) as usize][(cell.0 + d.0) as usize].cell_type,
-                ));
+// the config from `path`, or the defaults, with the command line's flags on top. a config with a mistake in it stops
+// everything unless --ignore-config-errors was passed
+fn load_config(app: &ArgMatches, path: Option<&Path>) -> Config {
+    let preset = parsed(app, "theme");
+    let mut config = match path {
+        Some(path) => match Config::load(path, preset) {
+            Ok(config) => config,
+            Err(error) if app.is_present("ignore-config-errors") => {
+                eprintln!(
+                    "Config error in {}: {error}, using the default settings",
+                    path.display()
+                );
+                Config::with_preset(preset)
             }
-        }
+            Err(error) => {
+                eprintln!("Config error in {}: {error}", path.display());
+                eprintln!(
+                    "Fix it, or pass --ignore-config-errors to play with the default settings."
+                );
+                std::process::exit(1);
+            }
+        },
+        None => Config::with_preset(preset),
+    };
 
-        return cells;
-    }
-
-    fn get_input(&self, event: Event) -> Option<Input> {
-        let selection = (self.selection.0 as isize, self.selection.1 as isize);
-
-        let change = match event {
-            Event::Key(key) => match key.code {
-                KeyCode::Enter => return Some(Input::Select),
-                KeyCode::Up => (selection.0, selection.1 + 1),
-                KeyCode::Down => (selection.0, selection.1 - 1),
-                KeyCode::Left => (selection.0 - 1, selection.1),
-                KeyCode::Right => (selection.0 + 1, selection.1),
-                KeyCode::Char(char) => match char {
-                    'q' => return Some(Input::Quit),
-                    ' ' => return Some(Input::Select),
-                    'w' => (selection.0, selection.1 + 1),
-                    's' => (selection.0, selection.1 - 1),
-                    'a' => (selection.0 - 1, selection.1),
-                    'd' => (selection.0 + 1, selection.1),
-                    'm' => return Some(Input::Mark),
-                    '?' => return Some(Input::Mark),
-                    'r' => return Some(Input::Restart),
-                    _ => return None,
-                },
-                _ => return None,
-            },
-            _ => return None,
-        };
+    if app.is_present("nf") {
+        config.no_flags = true;
+    }
 
-        if self.cell_exists((change.0 as usize, change.1 as usize)) {
-            Some(Input::Direction((change.0 as usize, change.1 as usize)))
-        } else {
-            Some(Input::Direction(self.selection))
-        }
+    if app.is_present("ghost") {
+        config.ghost = true;
     }
 
-    fn create_stylized_num(num: usize) -> StyledContent<String> {
-        match num {
-            1 => style::style(num.to_string()).with(Color::Blue),
-            2 => style::style(num.to_string()).with(Color::Green),
-            3 => style::style(num.to_string()).with(Color::Yellow),
-            4 => style::style(num.to_string()).with(Color::DarkBlue),
-            5 => style::style(num.to_string()).with(Color::DarkGreen),
-            6 => style::style(num.to_string()).with(Color::DarkMagenta),
-            7 => style::style(num.to_string()).with(Color::Red),
-            8 => style::style(num.to_string()).with(Color::DarkRed),
-            _ => style::style(num.to_string()),
-        }
+    let colors = config.color_support();
+    config.theme.downgrade(colors);
+
+    if app.is_present("plain") || theme::plain_terminal() {
+        config.theme.make_plain();
+        config.set_title = false;
     }
+
+    // style flags go on top of everything else, even --plain
+    let board = &mut config.theme.board;
+    board.mine = parsed(app, "mine-char").unwrap_or(board.mine);
+    board.covered = parsed(app, "covered-char").unwrap_or(board.covered);
+    board.empty = parsed(app, "empty-char").unwrap_or(board.empty);
+    board.marked = parsed(app, "marked-char").unwrap_or(board.marked);
+    board.gap_x = parsed(app, "gap-x").unwrap_or(board.gap_x);
+    board.gap_y = parsed(app, "gap-y").unwrap_or(board.gap_y);
+
+    config
+}
+
+// write the commented default config to where it'll get picked up without -c
+fn init_config(force: bool) -> Result<(), String> {
+    let path = paths::config_file().ok_or("couldn't find a config directory for this platform")?;
+
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists, pass --force to replace it",
+            path.display()
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+
+    fs::write(&path, config::TEMPLATE).map_err(|error| error.to_string())?;
+    println!("Wrote the default config to {}", path.display());
+
+    Ok(())
 }
@@ -0,0 +1,327 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::code::GameCode;
+
+// the port host listens on and join connects to when none is given
+pub const PORT: u16 = 7777;
+
+// the longest line either end of a race sends is a game code, so anything near this long means something's wrong
+pub const MAX_LINE: u64 = 64 * 1024;
+
+// what goes over the connection, one JSON object a line like {"type": "progress", "revealed": 40, "flags": 3}.
+// the host sends the board first and after that both sides only say how they're getting on
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+    // the game code of the board to race on, and the seconds added to the host's clear and then the guest's
+    Board {
+        code: String,
+        #[serde(default)]
+        handicaps: [u64; 2],
+    },
+    // how much of the board's safe cells are uncovered, in percent
+    Progress {
+        revealed: u8,
+        flags: usize,
+    },
+    // cleared the board in this many milliseconds
+    Won {
+        time: u64,
+    },
+    Lost,
+}
+
+// how the other player's getting on, as far as we've heard
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Standing {
+    Playing { revealed: u8, flags: usize },
+    Won(Duration),
+    Lost,
+    // the connection's gone, they quit or lost their network
+    Left,
+}
+
+// the player we're racing, on the other end of a TCP connection
+#[derive(Debug)]
+pub struct Opponent {
+    stream: TcpStream,
+    incoming: Receiver<Message>,
+    standing: Standing,
+    // added to our clear and then theirs, the host picks both
+    handicaps: [Duration; 2],
+}
+
+// wait for someone to join on `port` and send them the board we'll both play, with the host's handicap and then the
+// guest's
+pub fn host(port: u16, code: GameCode, handicaps: [Duration; 2]) -> io::Result<Opponent> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let (stream, _) = listener.accept()?;
+
+    let mut opponent = Opponent::new(stream)?;
+    opponent.handicaps = handicaps;
+    opponent.send(&Message::Board {
+        code: code.to_string(),
+        handicaps: handicaps.map(|handicap| handicap.as_secs()),
+    })?;
+
+    Ok(opponent)
+}
+
+// connect to someone hosting a race and get the board from them
+pub fn join(address: &str) -> io::Result<(GameCode, Opponent)> {
+    let mut opponent = Opponent::new(connect(address)?)?;
+
+    let code = match opponent.incoming.recv() {
+        Ok(Message::Board { code, handicaps }) => {
+            // the host's is theirs and the guest's ours
+            opponent.handicaps = [handicaps[1], handicaps[0]].map(Duration::from_secs);
+            code.parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the host didn't send a board",
+            ))
+        }
+    };
+
+    Ok((code, opponent))
+}
+
+// the lines coming in on a connection until it closes, or sends one longer than `limit` bytes. what's been read of a
+// line is held until its newline turns up, so without a limit the other end could fill up our memory
+pub fn lines(mut reader: impl BufRead, limit: u64) -> impl Iterator<Item = String> {
+    std::iter::from_fn(move || {
+        let mut line = String::new();
+
+        match (&mut reader).take(limit).read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            // the limit ran out before the end of the line
+            Ok(_) if !line.ends_with('\n') => None,
+            Ok(_) => Some(line),
+        }
+    })
+}
+
+// connect to someone who's hosting. an address without a port gets the usual one
+pub fn connect(address: &str) -> io::Result<TcpStream> {
+    match address.contains(':') {
+        true => TcpStream::connect(address),
+        false => TcpStream::connect((address, PORT)),
+    }
+}
+
+impl Opponent {
+    // whatever they send is read on its own thread, so the game never waits on the network
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        let (sender, incoming) = mpsc::channel();
+        let reader = BufReader::new(stream.try_clone()?);
+
+        thread::spawn(move || {
+            for line in lines(reader, MAX_LINE) {
+                let message = match serde_json::from_str(&line).ok() {
+                    Some(message) => message,
+                    None => break,
+                };
+
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            stream,
+            incoming,
+            standing: Standing::Playing {
+                revealed: 0,
+                flags: 0,
+            },
+            handicaps: [Duration::ZERO; 2],
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.stream.write_all(line.as_bytes())
+    }
+
+    // tell them how we're getting on. losing the connection doesn't stop our game, they're just shown as gone
+    pub fn report(&mut self, revealed: u8, flags: usize) {
+        self.send_or_leave(&Message::Progress { revealed, flags });
+    }
+
+    pub fn report_won(&mut self, time: Duration) {
+        self.send_or_leave(&Message::Won {
+            time: time.as_millis() as u64,
+        });
+    }
+
+    pub fn report_lost(&mut self) {
+        self.send_or_leave(&Message::Lost);
+    }
+
+    fn send_or_leave(&mut self, message: &Message) {
+        if self.standing != Standing::Left && self.send(message).is_err() {
+            self.standing = Standing::Left;
+        }
+    }
+
+    // catch up on what they've sent, returns whether anything changed
+    pub fn update(&mut self) -> bool {
+        let before = self.standing;
+
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Message::Progress { revealed, flags }) => {
+                    self.standing = Standing::Playing { revealed, flags }
+                }
+                Ok(Message::Won { time }) => {
+                    self.standing = Standing::Won(Duration::from_millis(time))
+                }
+                Ok(Message::Lost) => self.standing = Standing::Lost,
+                Ok(Message::Board { .. }) => {}
+                Err(mpsc::TryRecvError::Empty) => break,
+                // they can't finish any more once they've gone
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    if let Standing::Playing { .. } = self.standing {
+                        self.standing = Standing::Left;
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        self.standing != before
+    }
+
+    pub fn standing(&self) -> Standing {
+        self.standing
+    }
+
+    // what our clear counts as once our handicap's on top, if we have one
+    pub fn handicapped(&self, time: Duration) -> Option<String> {
+        let handicap = self.handicaps[0];
+        (!handicap.is_zero()).then(|| {
+            format!(
+                "that's {:.1}s with your {}s handicap",
+                time.saturating_add(handicap).as_secs_f64(),
+                handicap.as_secs()
+            )
+        })
+    }
+
+    // their progress for the status bar, with their handicap already added to their time
+    pub fn line(&self) -> String {
+        let handicap = self.handicaps[1];
+
+        match self.standing {
+            Standing::Playing { revealed, flags } if !handicap.is_zero() => format!(
+                "opponent {} {revealed}% with {flags} flags, +{}s",
+                bar(revealed),
+                handicap.as_secs()
+            ),
+            Standing::Playing { revealed, flags } => {
+                format!("opponent {} {revealed}% with {flags} flags", bar(revealed))
+            }
+            Standing::Won(time) if !handicap.is_zero() => format!(
+                "opponent cleared it in {:.1}s with their {}s handicap",
+                time.saturating_add(handicap).as_secs_f64(),
+                handicap.as_secs()
+            ),
+            Standing::Won(time) => format!("opponent cleared it in {:.1}s", time.as_secs_f64()),
+            Standing::Lost => "opponent blew up".to_string(),
+            Standing::Left => "opponent left".to_string(),
+        }
+    }
+}
+
+// the reading thread has its own handle on the connection, so it has to be shut down for the other end to see us go
+impl Drop for Opponent {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// a percentage as a little progress bar, ten blocks long
+fn bar(percent: u8) -> String {
+    let filled = (percent.min(100) as usize + 5) / 10;
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(10 - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_goes_both_ways() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let code: GameCode = "AaVkStfEugSsCQAJAAQABAAKAAAA".parse().unwrap();
+
+        let joining = thread::spawn(move || join(&address).unwrap());
+        let (stream, _) = listener.accept().unwrap();
+        let mut host = Opponent::new(stream).unwrap();
+        host.handicaps = [Duration::from_secs(10), Duration::ZERO];
+        host.send(&Message::Board {
+            code: code.to_string(),
+            handicaps: [10, 0],
+        })
+        .unwrap();
+
+        let (joined, mut guest) = joining.join().unwrap();
+        assert_eq!(joined, code);
+
+        guest.report(40, 3);
+        host.report_won(Duration::from_millis(12345));
+
+        // give the reader threads a moment to pass them on
+        thread::sleep(Duration::from_millis(100));
+        assert!(host.update());
+        assert!(guest.update());
+        assert_eq!(
+            host.standing(),
+            Standing::Playing {
+                revealed: 40,
+                flags: 3
+            }
+        );
+        assert_eq!(host.line(), "opponent [####------] 40% with 3 flags");
+        assert_eq!(
+            guest.line(),
+            "opponent cleared it in 22.3s with their 10s handicap"
+        );
+        assert_eq!(
+            host.handicapped(Duration::from_millis(12345)).unwrap(),
+            "that's 22.3s with your 10s handicap"
+        );
+        assert_eq!(guest.handicapped(Duration::from_secs(20)), None);
+
+        drop(guest);
+        thread::sleep(Duration::from_millis(100));
+        host.update();
+        assert_eq!(host.standing(), Standing::Left);
+    }
+
+    #[test]
+    fn lines_stop_at_the_limit() {
+        let text = format!("short\n{}\nafter\n", "x".repeat(100));
+        let read: Vec<_> = lines(text.as_bytes(), 50).collect();
+        assert_eq!(read, ["short\n"]);
+
+        // and the last line only counts once its newline's there
+        let read: Vec<_> = lines("one\ntwo".as_bytes(), 50).collect();
+        assert_eq!(read, ["one\n"]);
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Online,
+    daily,
+    player::{Action, Round, Standing},
+    replay::{ActionKind, Replay},
+};
+
+// a daily challenge win as it goes up to the online leaderboard. the replay stays here, its hash goes with the result
+// so it can be asked for and checked later
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Submission {
+    pub day: u64,
+    pub seed: u64,
+    pub name: String,
+    // in milliseconds
+    pub time: u64,
+    // the SHA-256 of the replay's JSON, in hex
+    pub replay: String,
+}
+
+// one place in the online rankings
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Ranking {
+    pub name: String,
+    // in milliseconds
+    pub time: u64,
+}
+
+// whether `replay` really is a win on `day`'s board: it's the day's size with the mines the day's code makes, and
+// playing its moves back by the game's rules clears the board without setting one off
+pub fn verify(replay: &Replay, day: u64) -> bool {
+    let code = daily::code(day);
+
+    if replay.daily != Some(day)
+        || replay.seed != Some(code.seed)
+        || (replay.width, replay.height) != (code.settings.width, code.settings.height)
+        || replay.mines != code.mines()
+        || replay.check().is_err()
+    {
+        return false;
+    }
+
+    let mut round = Round::with_board(replay.board());
+
+    for action in &replay.actions {
+        // a move the rules refuse changes nothing, the same as when it was played
+        let _ = round.apply(match action.kind {
+            ActionKind::Reveal => Action::Reveal(action.x, action.y),
+            ActionKind::Mark => Action::Flag(action.x, action.y),
+        });
+    }
+
+    round.standing() == Standing::Won
+}
+
+// the name results go up under
+#[cfg(feature = "online")]
+fn name(online: &Online) -> String {
+    let mut name = online
+        .name
+        .clone()
+        .or_else(crate::paths::profile)
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "anonymous".to_string());
+    name.truncate(crate::leaderboard::NAME_LENGTH);
+    name
+}
+
+// send a daily win to the online leaderboard, if it checks out against its replay
+#[cfg(feature = "online")]
+pub fn submit(online: &Online, replay: &Replay, day: u64) -> Result<(), String> {
+    let url = online
+        .url
+        .as_deref()
+        .ok_or("there's no online leaderboard in the config")?;
+
+    if !verify(replay, day) {
+        return Err("the replay doesn't check out".to_string());
+    }
+
+    let submission = Submission {
+        day,
+        seed: replay.seed.unwrap_or_default(),
+        name: name(online),
+        time: replay.duration().as_millis() as u64,
+        replay: http::hash(replay),
+    };
+
+    http::post(
+        &format!("{}/daily/{day}", url.trim_end_matches('/')),
+        &submission,
+    )
+}
+
+#[cfg(not(feature = "online"))]
+pub fn submit(_online: &Online, _replay: &Replay, _day: u64) -> Result<(), String> {
+    Err("this copy of minesweeper was built without the online feature".to_string())
+}
+
+// everyone's times on `day`'s board, fastest first
+#[cfg(feature = "online")]
+pub fn rankings(online: &Online, day: u64) -> Result<Vec<Ranking>, String> {
+    let url = online
+        .url
+        .as_deref()
+        .ok_or("there's no online leaderboard in the config")?;
+    http::get(&format!("{}/daily/{day}", url.trim_end_matches('/')))
+}
+
+#[cfg(not(feature = "online"))]
+pub fn rankings(_online: &Online, _day: u64) -> Result<Vec<Ranking>, String> {
+    Err("this copy of minesweeper was built without the online feature".to_string())
+}
+
+#[cfg(feature = "online")]
+mod http {
+    use std::time::Duration;
+
+    use sha2::{Digest, Sha256};
+
+    use super::{Ranking, Submission};
+    use crate::replay::Replay;
+
+    // the game waits on these, so a leaderboard that's down shouldn't keep it waiting long
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn hash(replay: &Replay) -> String {
+        let json = serde_json::to_string(replay).unwrap_or_default();
+        Sha256::digest(json.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    pub fn post(url: &str, submission: &Submission) -> Result<(), String> {
+        ureq::post(url)
+            .timeout(TIMEOUT)
+            .send_json(submission)
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    pub fn get(url: &str) -> Result<Vec<Ranking>, String> {
+        let response = ureq::get(url)
+            .timeout(TIMEOUT)
+            .call()
+            .map_err(|error| error.to_string())?;
+        response.into_json().map_err(|error| error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, replay};
+
+    #[test]
+    fn only_real_wins_check_out() {
+        let day = 20_000;
+        let code = daily::code(day);
+        let mut replay = Replay::new(2, code.settings.width, code.settings.height);
+        replay.seed = Some(code.seed);
+        replay.mines = code.mines();
+        replay.daily = Some(day);
+
+        // reveal every safe cell, one at a time
+        let board = Board::with_mines(replay.width, replay.height, &replay.mines);
+        for (x, y) in board.positions().filter(|&(x, y)| !board.is_mine(x, y)) {
+            replay.actions.push(replay::Action {
+                time: 1000,
+                kind: ActionKind::Reveal,
+                x,
+                y,
+                uncovered: 0,
+            });
+        }
+        assert!(verify(&replay, day));
+
+        // someone else's day, or a board that's been tampered with, doesn't
+        assert!(!verify(&replay, day + 1));
+        let mut wider = replay.clone();
+        wider.width += 1;
+        assert!(!verify(&wider, day));
+        replay.mines.pop();
+        assert!(!verify(&replay, day));
+    }
+}
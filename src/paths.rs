@@ -0,0 +1,105 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use directories::ProjectDirs;
+
+// the longest a profile's name can be
+pub const PROFILE_LENGTH: usize = 24;
+
+// the player whose stats, saves and settings are being used, None for the one that's there without --profile
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+// where we keep everything the game saves between runs (best runs, stats...)
+// returns None if the platform doesn't give us a home directory
+pub fn data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "minesweeper").map(|dirs| in_profile(dirs.data_dir()))
+}
+
+// the config file that's loaded when -c isn't given, ~/.config/minesweeper/config.yaml on Linux
+pub fn config_file() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "minesweeper")
+        .map(|dirs| in_profile(dirs.config_dir()).join("config.yaml"))
+}
+
+// a profile's files go in a directory of their own under the usual one, the default profile's are where they've
+// always been
+fn in_profile(dir: &Path) -> PathBuf {
+    match profile() {
+        Some(name) => dir.join("profiles").join(name),
+        None => dir.to_path_buf(),
+    }
+}
+
+pub fn profile() -> Option<String> {
+    PROFILE.read().unwrap().clone()
+}
+
+// use `name`'s files from now on, or the default profile's for None
+pub fn set_profile(name: Option<String>) -> Result<(), String> {
+    if let Some(name) = &name {
+        if !is_profile_name(name) {
+            return Err(format!(
+                "{name:?} can't be a profile, use up to {PROFILE_LENGTH} letters, digits, - and _"
+            ));
+        }
+    }
+
+    *PROFILE.write().unwrap() = name;
+    Ok(())
+}
+
+// profiles are directory names, so they're kept to characters that are safe in one on every platform
+pub fn is_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= PROFILE_LENGTH
+        && name
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '-' || char == '_')
+}
+
+// every profile that's been made on this machine, in alphabetical order
+pub fn profiles() -> Vec<String> {
+    let dirs = match ProjectDirs::from("", "", "minesweeper") {
+        Some(dirs) => dirs,
+        None => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = [dirs.data_dir(), dirs.config_dir()]
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir.join("profiles")).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| is_profile_name(name))
+        .collect();
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+// make the profile that's in use show up in profiles() before it's saved anything
+pub fn create_profile() -> io::Result<()> {
+    match data_dir() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names_stay_inside_their_directory() {
+        assert!(is_profile_name("kid_2"));
+        assert!(!is_profile_name(""));
+        assert!(!is_profile_name("../mum"));
+        assert!(!is_profile_name("a/b"));
+        assert!(!is_profile_name(&"x".repeat(PROFILE_LENGTH + 1)));
+    }
+}
@@ -0,0 +1,445 @@
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+
+use crate::{
+    board::Board,
+    event::GameEvent,
+    generator::{self, Settings},
+    solver::Solver,
+};
+
+// something that plays minesweeper: a bot on the other end of --protocol, the solver, someone clicking at random or a
+// person at the terminal (human::Human). play() drives any of them the same way, asking for a move and making it until
+// the game's over. the full terminal game isn't one of these: between moves it animates openings, pauses the clock,
+// rewinds and flags mines for lives on the board itself, so it makes its moves with Round::apply() instead
+pub trait Player {
+    fn next_action(&mut self, view: &BoardView) -> Action;
+
+    // the last action couldn't be made, like a reveal off the edge of the board
+    fn rejected(&mut self, _reason: &str) {}
+
+    // what the last action did, see Round::apply()
+    fn happened(&mut self, _events: &[GameEvent]) {}
+
+    // the game's over, with the board as it ended up
+    fn finished(&mut self, _view: &BoardView) {}
+
+    // whether there's to be another game after this one, a person can have had enough
+    fn keeps_playing(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+    Reveal(usize, usize),
+    // flag a covered cell, or take the flag off if it's already flagged
+    Flag(usize, usize),
+    // open everything around a number that has all its flags
+    Chord(usize, usize),
+    // stop playing this game
+    Quit,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Standing {
+    Playing,
+    Won,
+    Lost,
+}
+
+// a cell as the player sees it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Seen {
+    Covered,
+    Flagged,
+    Wall,
+    // uncovered, with this many mines around it
+    Number(usize),
+    // only shown once the game's lost
+    Mine,
+    Exploded,
+}
+
+// the board with everything the player shouldn't know hidden
+pub struct BoardView<'a> {
+    board: &'a Board,
+    mines: usize,
+    standing: Standing,
+}
+
+impl BoardView<'_> {
+    pub fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    // how many mines there are, even before the first click has placed them
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn flags(&self) -> usize {
+        self.board.num_flagged()
+    }
+
+    pub fn standing(&self) -> Standing {
+        self.standing
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        self.board.positions()
+    }
+
+    pub fn seen(&self, x: usize, y: usize) -> Seen {
+        let cell = self.board.cell(x, y);
+
+        match (cell.is_mine(), cell.is_uncovered()) {
+            _ if cell.is_wall() => Seen::Wall,
+            (true, true) => Seen::Exploded,
+            (true, false) if self.standing == Standing::Lost => Seen::Mine,
+            (_, false) if cell.is_flagged() => Seen::Flagged,
+            (_, false) => Seen::Covered,
+            (false, true) => Seen::Number(cell.adjacent()),
+        }
+    }
+
+    // whether nothing's been uncovered yet, so the next reveal is the first click
+    pub fn untouched(&self) -> bool {
+        self.positions()
+            .all(|(x, y)| !matches!(self.seen(x, y), Seen::Number(_)))
+    }
+}
+
+// one game and the rules for playing it, without anything drawn
+#[derive(Debug)]
+pub struct Round {
+    board: Board,
+    settings: Settings,
+    // where the mines come from when the first reveal places them, None once they're down
+    seed: Option<u64>,
+}
+
+impl Round {
+    // a generated board, with the mines going in around the first reveal like any other game
+    pub fn new(settings: Settings, seed: u64) -> Self {
+        Self {
+            board: Board::new(settings.width, settings.height),
+            settings,
+            seed: Some(seed),
+        }
+    }
+
+    // a board that already has its mines
+    pub fn with_board(board: Board) -> Self {
+        let settings = Settings {
+            width: board.width(),
+            height: board.height(),
+            mines: board.num_mines(),
+            placement: Default::default(),
+        };
+
+        Self {
+            board,
+            settings,
+            seed: None,
+        }
+    }
+
+    pub fn standing(&self) -> Standing {
+        let board = &self.board;
+
+        if board
+            .positions()
+            .any(|(x, y)| board.is_mine(x, y) && board.is_uncovered(x, y))
+        {
+            Standing::Lost
+        } else if board.num_uncovered() == board.width() * board.height() - board.num_mines() {
+            Standing::Won
+        } else {
+            Standing::Playing
+        }
+    }
+
+    pub fn view(&self) -> BoardView<'_> {
+        BoardView {
+            board: &self.board,
+            mines: self.settings.mines,
+            standing: self.standing(),
+        }
+    }
+
+    // the board with nothing hidden, for drawing it
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // the board to change outside the rules, for games that bend them like extra lives covering a mine back up
+    // and for animations
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    // put the mines down on a board that was made without them, for games that pick their own layout
+    pub fn place_mines(&mut self, mines: &[usize]) {
+        self.board.place_mines(mines);
+        self.settings.mines = self.board.num_mines();
+        self.seed = None;
+    }
+
+    // make a move, saying what it did: a CellRevealed for every cell that was opened on its own (a chord opens each
+    // of its neighbors) and then Exploded or Won if that's how the game ended. frontends and stats go by these
+    // rather than working out what changed on the board
+    pub fn apply(&mut self, action: Action) -> Result<Vec<GameEvent>, String> {
+        if self.standing() != Standing::Playing {
+            return Err("the game's over".to_string());
+        }
+
+        if let Action::Reveal(x, y) | Action::Flag(x, y) | Action::Chord(x, y) = action {
+            if x >= self.board.width() || y >= self.board.height() {
+                return Err(format!("({x}, {y}) isn't on the board"));
+            }
+        }
+
+        let mut events = Vec::new();
+
+        match action {
+            Action::Reveal(x, y) => {
+                if let Some(seed) = self.seed.take() {
+                    self.board
+                        .place_mines(&generator::layout(self.settings, seed, (x, y)));
+                }
+
+                // flags are only reminders, revealing one still opens it
+                self.reveal(x, y, &mut events);
+            }
+            Action::Flag(x, y) => {
+                if self.board.is_uncovered(x, y) {
+                    return Err(format!("({x}, {y}) is uncovered, there's nothing to flag"));
+                }
+
+                self.board.toggle_flag(x, y);
+                events.push(GameEvent::Flagged {
+                    cell: (x, y),
+                    flagged: self.board.is_flagged(x, y),
+                });
+            }
+            Action::Chord(x, y) => {
+                let board = &self.board;
+                let flags = board
+                    .neighbors(x, y)
+                    .filter(|&(x, y)| board.is_flagged(x, y))
+                    .count();
+
+                // walls and empty cells have nothing to chord, and with no flags to match they'd open every
+                // neighbor, mines and all
+                if !board.is_uncovered(x, y)
+                    || board.is_wall(x, y)
+                    || board.adjacent_mines(x, y) == 0
+                    || board.adjacent_mines(x, y) != flags
+                {
+                    return Err(format!(
+                        "({x}, {y}) isn't a number with all its flags around it"
+                    ));
+                }
+
+                for (x, y) in board.neighbors(x, y).collect::<Vec<_>>() {
+                    if !self.board.is_flagged(x, y) {
+                        self.reveal(x, y, &mut events);
+                    }
+                }
+            }
+            Action::Quit => {}
+        }
+
+        match self.standing() {
+            Standing::Lost => {
+                if let Some(&cell) = events.iter().find_map(|event| match event {
+                    GameEvent::CellRevealed { opened, .. } => {
+                        opened.iter().find(|&&(x, y)| self.board.is_mine(x, y))
+                    }
+                    _ => None,
+                }) {
+                    events.push(GameEvent::Exploded { cell });
+                }
+            }
+            Standing::Won => events.push(GameEvent::Won),
+            Standing::Playing => {}
+        }
+
+        Ok(events)
+    }
+
+    // uncover a cell and everything that opens up with it, if it wasn't already
+    fn reveal(&mut self, x: usize, y: usize, events: &mut Vec<GameEvent>) {
+        let opened = self.board.uncover(x, y);
+
+        if !opened.is_empty() {
+            events.push(GameEvent::CellRevealed {
+                cell: (x, y),
+                opened,
+                assisted: false,
+            });
+        }
+    }
+}
+
+// play `round` with `player` until it's won, lost or the player quits
+pub fn play(player: &mut dyn Player, round: &mut Round) -> Standing {
+    while round.standing() == Standing::Playing {
+        match player.next_action(&round.view()) {
+            Action::Quit => break,
+            action => match round.apply(action) {
+                Ok(events) => player.happened(&events),
+                Err(reason) => player.rejected(&reason),
+            },
+        }
+    }
+
+    player.finished(&round.view());
+    round.standing()
+}
+
+// the built-in solver: it reveals whatever it can prove safe and takes the least risky guess when it can't prove anything
+#[derive(Debug, Default)]
+pub struct SolverPlayer {
+    // cells proven safe that haven't been revealed yet
+    queue: Vec<(usize, usize)>,
+}
+
+impl Player for SolverPlayer {
+    fn next_action(&mut self, view: &BoardView) -> Action {
+        // generated boards are always safe in the middle
+        if view.untouched() {
+            return Action::Reveal(view.width() / 2, view.height() / 2);
+        }
+
+        while let Some((x, y)) = self.queue.pop() {
+            if view.seen(x, y) == Seen::Covered {
+                return Action::Reveal(x, y);
+            }
+        }
+
+        // the solver only looks at what the player can see, so it can have the real board
+        let solver = Solver::new(view.board);
+        self.queue = solver.safe_cells();
+
+        match self
+            .queue
+            .pop()
+            .or_else(|| solver.best_guess().map(|(cell, _)| cell))
+        {
+            Some((x, y)) => Action::Reveal(x, y),
+            None => Action::Quit,
+        }
+    }
+
+    fn finished(&mut self, _view: &BoardView) {
+        self.queue.clear();
+    }
+}
+
+// reveals covered cells at random, for something to measure the other players against
+#[derive(Debug)]
+pub struct RandomPlayer {
+    rng: StdRng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn next_action(&mut self, view: &BoardView) -> Action {
+        let covered = view
+            .positions()
+            .filter(|&(x, y)| view.seen(x, y) == Seen::Covered);
+
+        match covered.choose(&mut self.rng) {
+            Some((x, y)) => Action::Reveal(x, y),
+            None => Action::Quit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_solver_clears_boards_that_need_no_guessing() {
+        // the first click in the middle opens everything but the corner mine
+        let mut round = Round::with_board(Board::with_mines(5, 5, &[0]));
+        assert_eq!(
+            play(&mut SolverPlayer::default(), &mut round),
+            Standing::Won
+        );
+
+        let mut round = Round::with_board(Board::with_mines(5, 5, &[0]));
+        round.apply(Action::Reveal(9, 9)).unwrap_err();
+        assert_ne!(
+            play(&mut RandomPlayer::new(1), &mut round),
+            Standing::Playing
+        );
+    }
+
+    #[test]
+    fn moves_say_what_they_did() {
+        let mut round = Round::with_board(Board::with_mines(4, 1, &[3]));
+
+        assert_eq!(
+            round.apply(Action::Flag(3, 0)).unwrap(),
+            [GameEvent::Flagged {
+                cell: (3, 0),
+                flagged: true
+            }]
+        );
+        assert_eq!(
+            round.apply(Action::Reveal(0, 0)).unwrap(),
+            [
+                GameEvent::CellRevealed {
+                    cell: (0, 0),
+                    opened: vec![(0, 0), (1, 0), (2, 0)],
+                    assisted: false
+                },
+                GameEvent::Won
+            ]
+        );
+
+        let mut round = Round::with_board(Board::with_mines(4, 1, &[3]));
+        round.apply(Action::Reveal(2, 0)).unwrap();
+        round.apply(Action::Flag(1, 0)).unwrap();
+        assert_eq!(
+            round.apply(Action::Chord(2, 0)).unwrap().last(),
+            Some(&GameEvent::Exploded { cell: (3, 0) })
+        );
+    }
+
+    #[test]
+    fn only_numbers_with_their_flags_chord() {
+        let mut round = Round::with_board(Board::from_text("-*--\n#---").unwrap());
+        round.apply(Action::Reveal(3, 0)).unwrap();
+
+        round.apply(Action::Chord(0, 0)).unwrap_err();
+        round.apply(Action::Chord(3, 0)).unwrap_err();
+        round.apply(Action::Chord(2, 0)).unwrap_err();
+        round.apply(Action::Flag(3, 0)).unwrap_err();
+        assert_eq!(round.standing(), Standing::Playing);
+
+        round.apply(Action::Flag(1, 1)).unwrap();
+        round.apply(Action::Chord(2, 0)).unwrap();
+        assert!(round.board().is_uncovered(1, 0));
+        assert_eq!(round.standing(), Standing::Playing);
+
+        // flags are only reminders, revealing one opens it
+        round.apply(Action::Flag(0, 1)).unwrap();
+        round.apply(Action::Reveal(0, 1)).unwrap();
+        assert_eq!(round.standing(), Standing::Won);
+    }
+}
@@ -0,0 +1,414 @@
+use std::io::{stdout, Stdout, Write};
+
+use crossterm::{
+    cursor::{MoveTo, Show},
+    event::{Event, KeyCode},
+    style::Print,
+    terminal::{Clear, ClearType},
+    ExecutableCommand, QueueableCommand, Result,
+};
+
+use crate::{
+    board::Board,
+    config::Config,
+    input::{self, Events},
+    puzzle::{Pack, Progress, Puzzle},
+    replay::Transform,
+    solver::Solver,
+    terminal::set_title,
+    theme::{Sound, Tile},
+};
+
+// the patterns --practice drills
+const PATTERNS: &str = include_str!("puzzles/patterns.yaml");
+
+// playing through a pack of puzzles one after another. the built-in patterns are drilled: they come up turned
+// a random way, over and over, and only need what they prove worked out. other packs are played once each,
+// cleared like any other board, and remember which puzzles have been solved
+#[derive(Debug)]
+pub struct Practice {
+    out: Stdout,
+    config: Config,
+    events: Events,
+    pack: Pack,
+    // drilling the built-in patterns rather than clearing boards
+    drill: bool,
+    // the puzzles that have been solved so far, when they're being kept track of
+    progress: Option<Progress>,
+    // which puzzle is up
+    puzzle: usize,
+    board: Board,
+    selection: (usize, usize),
+    // the cells a drilled pattern proves safe and proves mined, which have to be uncovered and flagged to solve it
+    safe: Vec<(usize, usize)>,
+    mines: Vec<(usize, usize)>,
+    exploded: bool,
+    // the line under the board, the hint or whatever just happened
+    message: String,
+}
+
+impl Practice {
+    // drill the built-in patterns
+    pub fn patterns(config: Config, events: Events) -> Result<Self> {
+        let pack = Pack::parse(PATTERNS)?;
+        Self::new(pack, true, None, config, events)
+    }
+
+    // play through a puzzle pack, starting from the first puzzle that hasn't been solved
+    pub fn pack(pack: Pack, config: Config, events: Events) -> Result<Self> {
+        let progress = Progress::load()?;
+        Self::new(pack, false, Some(progress), config, events)
+    }
+
+    // play one board over and over without keeping track of it, like one straight out of the editor
+    pub fn single(puzzle: Puzzle, config: Config, events: Events) -> Result<Self> {
+        let pack = Pack {
+            name: "Test".to_string(),
+            puzzles: vec![puzzle],
+        };
+
+        Self::new(pack, false, None, config, events)
+    }
+
+    fn new(
+        pack: Pack,
+        drill: bool,
+        progress: Option<Progress>,
+        config: Config,
+        events: Events,
+    ) -> Result<Self> {
+        let mut out = stdout();
+
+        if config.set_title {
+            set_title(&mut out, &format!("Minesweeper - {}", pack.name))?;
+        }
+
+        let puzzle = match &progress {
+            Some(progress) => pack
+                .puzzles
+                .iter()
+                .position(|puzzle| !progress.is_solved(&pack, puzzle))
+                .unwrap_or(0),
+            None => 0,
+        };
+
+        Ok(Self {
+            out,
+            config,
+            events,
+            pack,
+            drill,
+            progress,
+            puzzle,
+            board: Board::new(1, 1),
+            selection: (0, 0),
+            safe: Vec::new(),
+            mines: Vec::new(),
+            exploded: false,
+            message: String::new(),
+        })
+    }
+
+    // play the current puzzle, returns whether to keep going
+    pub fn run(&mut self) -> Result<bool> {
+        // drilled patterns come up the way they're written or turned, so it's the shape that gets learned and not the layout
+        let transform = match (self.is_drill(), rand::random::<u8>() % 4) {
+            (true, 0) => Some(Transform::Transpose),
+            (true, 1) => Some(Transform::RotateClockwise),
+            (true, 2) => Some(Transform::RotateAnticlockwise),
+            _ => None,
+        };
+
+        self.board = self.current().board(transform);
+        self.selection = (0, 0);
+        self.exploded = false;
+        self.message = match self.is_drill() {
+            true => "uncover every cell this proves safe and flag every cell it proves is a mine",
+            false => "uncover every cell that isn't a mine",
+        }
+        .to_string();
+
+        let solver = Solver::new(&self.board);
+        self.safe = solver.safe_cells();
+        self.mines = self
+            .board
+            .positions()
+            .filter(|&(x, y)| solver.is_mine(x, y))
+            .collect();
+
+        self.out.execute(Clear(ClearType::All))?.execute(Show)?;
+        self.draw()?;
+
+        loop {
+            let key = match self.events.read()? {
+                Event::Key(key) => key,
+                Event::Resize(..) => {
+                    self.out.execute(Clear(ClearType::All))?;
+                    self.draw()?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if let Some((dx, dy)) = self.config.movement.direction(key.code) {
+                let (x, y) = self.selection;
+                self.selection = (
+                    x.saturating_add_signed(dx).min(self.board.width() - 1),
+                    y.saturating_add_signed(dy).min(self.board.height() - 1),
+                );
+                self.draw()?;
+                continue;
+            }
+
+            let (x, y) = self.selection;
+
+            match key.code {
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    if self.board.is_uncovered(x, y) {
+                        continue;
+                    }
+
+                    self.board.uncover(x, y);
+
+                    if self.board.is_mine(x, y) {
+                        self.ring(Sound::Explosion)?;
+                        self.exploded = true;
+                        self.message = format!("that was a mine. {}", self.current().hint);
+                        return self.wait("press any key to try again");
+                    }
+
+                    self.ring(Sound::Reveal)?;
+                }
+                KeyCode::Char('m') => {
+                    if self.board.is_uncovered(x, y) {
+                        continue;
+                    }
+
+                    self.board.toggle_flag(x, y);
+                    self.ring(Sound::Flag)?;
+                }
+                KeyCode::Char('?') => self.message = self.current().hint.clone(),
+                KeyCode::Char('n') => {
+                    self.next();
+                    return Ok(true);
+                }
+                KeyCode::Char('r') => return Ok(true),
+                // ctrl+c quits the same way q does
+                _ if input::is_interrupt(&key) || key.code == KeyCode::Char('q') => {
+                    return Ok(false)
+                }
+                _ => continue,
+            }
+
+            if self.is_solved() {
+                self.ring(Sound::Win)?;
+                self.message = format!("solved! {}", self.current().hint);
+
+                let (pack, puzzle) = (&self.pack, &self.pack.puzzles[self.puzzle]);
+                if let Some(progress) = &mut self.progress {
+                    progress.add(pack, puzzle);
+                    progress.save()?;
+
+                    if progress.solved(pack) == pack.puzzles.len() {
+                        self.message
+                            .push_str(" that's every puzzle in the pack solved!");
+                    }
+                }
+
+                let prompt = match self.pack.puzzles.len() {
+                    1 => "press any key to play it again and q to stop",
+                    _ => "press any key for the next puzzle",
+                };
+                let more = self.wait(prompt)?;
+                self.next();
+                return Ok(more);
+            }
+
+            self.draw()?;
+        }
+    }
+
+    fn current(&self) -> &Puzzle {
+        &self.pack.puzzles[self.puzzle]
+    }
+
+    fn is_drill(&self) -> bool {
+        self.drill
+    }
+
+    // hand the events back once we're done with them
+    pub fn into_events(self) -> Events {
+        self.events
+    }
+
+    fn next(&mut self) {
+        self.puzzle = (self.puzzle + 1) % self.pack.puzzles.len();
+    }
+
+    // a drilled pattern is solved once everything it proves has been found and nothing it doesn't has been flagged,
+    // any other puzzle once every safe cell is uncovered
+    fn is_solved(&self) -> bool {
+        match self.is_drill() {
+            true => {
+                let flagged: Vec<(usize, usize)> = self
+                    .board
+                    .positions()
+                    .filter(|&(x, y)| self.board.is_flagged(x, y))
+                    .collect();
+                self.safe
+                    .iter()
+                    .all(|&(x, y)| self.board.is_uncovered(x, y))
+                    && flagged == self.mines
+            }
+            false => {
+                self.board.num_uncovered() + self.board.num_mines()
+                    == self.board.width() * self.board.height()
+            }
+        }
+    }
+
+    // show the board and the message with `prompt` under it until a key is pressed
+    fn wait(&mut self, prompt: &str) -> Result<bool> {
+        self.draw()?;
+        self.out
+            .execute(MoveTo(0, self.message_row() + 1))?
+            .execute(Print(self.config.theme.overlay.apply(prompt)))?;
+
+        loop {
+            if let Event::Key(key) = self.events.read()? {
+                return Ok(!input::is_interrupt(&key) && key.code != KeyCode::Char('q'));
+            }
+        }
+    }
+
+    // the title goes on the top row and the board starts a row under it
+    fn board_row(&self) -> u16 {
+        self.config.safe_area().top + 2
+    }
+
+    // a blank line under the board
+    fn message_row(&self) -> u16 {
+        let pitch_y = self.config.theme.board.gap_y + 1;
+        self.board_row() + (((self.board.height() - 1) * pitch_y) + 2) as u16
+    }
+
+    fn ring(&mut self, sound: Sound) -> Result<()> {
+        if self.config.theme.bell.rings(sound) {
+            self.out.execute(Print('\x07'))?;
+        }
+
+        Ok(())
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        let exploded = self.exploded && self.board.is_mine(x, y);
+
+        if self.board.is_wall(x, y) {
+            Tile::Wall
+        } else if self.board.is_flagged(x, y) && !exploded {
+            Tile::Marked
+        } else if !self.board.is_uncovered(x, y) && !exploded {
+            Tile::Covered
+        } else if self.board.is_mine(x, y) {
+            Tile::Mine
+        } else {
+            match self.board.adjacent_mines(x, y) {
+                0 => Tile::Empty,
+                num => Tile::Number(num),
+            }
+        }
+    }
+
+    // the pack and puzzle name, the board and the message under it, with the cursor left on the selected cell
+    fn draw(&mut self) -> Result<()> {
+        let theme = &self.config.theme;
+        let board = &theme.board;
+        let (pitch_x, pitch_y) = (board.gap_x + board.cell_width(), board.gap_y + 1);
+        let (top, board_row, message_row) = (
+            self.config.safe_area().top,
+            self.board_row(),
+            self.message_row(),
+        );
+        let height = self.board.height();
+
+        let solved = match &self.progress {
+            Some(progress) if progress.is_solved(&self.pack, self.current()) => ", solved",
+            _ => "",
+        };
+        let title = format!(
+            "{}: {} ({}/{}{solved}) | m flag, ? hint, n next, r start over, q quit",
+            self.pack.name,
+            self.current().name,
+            self.puzzle + 1,
+            self.pack.puzzles.len()
+        );
+        self.out
+            .queue(MoveTo(0, top))?
+            .queue(Clear(ClearType::FromCursorDown))?
+            .queue(Print(theme.status_bar.apply(title)))?;
+
+        for row in 0..height {
+            let y = height - 1 - row;
+            let line: String = (0..self.board.width())
+                .map(|x| format!("{}{}", board.glyph(self.tile(x, y)), board.gap()))
+                .collect();
+
+            self.out
+                .queue(MoveTo(0, board_row + (row * pitch_y) as u16))?
+                .queue(Print(line))?;
+        }
+
+        self.out
+            .queue(MoveTo(0, message_row))?
+            .queue(Print(theme.menu.apply(&self.message)))?;
+
+        let (x, y) = self.selection;
+        self.out.queue(MoveTo(
+            (x * pitch_x) as u16,
+            board_row + ((height - 1 - y) * pitch_y) as u16,
+        ))?;
+
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every pattern should look like a real game (no open mines, no open 0 next to a covered cell)
+    // and prove something, however it's turned
+    #[test]
+    fn patterns_are_solvable() {
+        let transforms = [
+            None,
+            Some(Transform::Transpose),
+            Some(Transform::RotateClockwise),
+            Some(Transform::RotateAnticlockwise),
+        ];
+
+        for pattern in &Pack::parse(PATTERNS).unwrap().puzzles {
+            for transform in transforms {
+                let board = pattern.board(transform);
+
+                for (x, y) in board.positions().filter(|&(x, y)| board.is_uncovered(x, y)) {
+                    assert!(!board.is_mine(x, y), "{} has an open mine", pattern.name);
+                    assert!(
+                        board.adjacent_mines(x, y) > 0
+                            || board.neighbors(x, y).all(|(x, y)| board.is_uncovered(x, y)),
+                        "{} has an open 0 next to a covered cell",
+                        pattern.name
+                    );
+                }
+
+                let solver = Solver::new(&board);
+                assert!(
+                    !solver.safe_cells().is_empty(),
+                    "{} doesn't prove anything safe",
+                    pattern.name
+                );
+            }
+        }
+    }
+}
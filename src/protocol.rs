@@ -0,0 +1,236 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::Board,
+    generator::Settings,
+    player::{self, Action, BoardView, Player, Round, Seen, Standing},
+};
+
+// what a bot can send, one JSON object a line like {"action": "reveal", "x": 4, "y": 4}.
+// (0, 0) is the bottom left cell, the same as A1
+#[derive(Deserialize, Debug)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum Command {
+    Reveal { x: usize, y: usize },
+    // flag a covered cell, or take the flag off if it's already flagged
+    Flag { x: usize, y: usize },
+    // open everything around a number that has all its flags
+    Chord { x: usize, y: usize },
+    New,
+    Quit,
+}
+
+// what goes back, one JSON object a line
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+    State {
+        status: &'static str,
+        width: usize,
+        height: usize,
+        mines: usize,
+        flags: usize,
+        // the board as the player sees it, top row first: - covered, f flagged, # wall, 0-8 uncovered with that many
+        // mines around it. when the game's lost every mine shows as *, and the one that went off as x
+        board: Vec<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl Message {
+    fn state(view: &BoardView) -> Self {
+        let rows = (0..view.height())
+            .rev()
+            .map(|y| (0..view.width()).map(|x| symbol(view.seen(x, y))).collect())
+            .collect();
+
+        Message::State {
+            status: status(view.standing()),
+            width: view.width(),
+            height: view.height(),
+            mines: view.mines(),
+            flags: view.flags(),
+            board: rows,
+        }
+    }
+}
+
+// how a cell is written in the board that gets sent
+pub fn symbol(seen: Seen) -> char {
+    match seen {
+        Seen::Covered => '-',
+        Seen::Flagged => 'f',
+        Seen::Wall => '#',
+        Seen::Number(mines) => char::from_digit(mines as u32, 10).unwrap_or('?'),
+        Seen::Mine => '*',
+        Seen::Exploded => 'x',
+    }
+}
+
+pub fn status(standing: Standing) -> &'static str {
+    match standing {
+        Standing::Playing => "playing",
+        Standing::Won => "won",
+        Standing::Lost => "lost",
+    }
+}
+
+// the state of the game as one line of JSON, the same as a bot gets after every move
+pub fn state(view: &BoardView) -> String {
+    serde_json::to_string(&Message::state(view)).expect("the state is always valid JSON")
+}
+
+// a program on the other end of stdin and stdout, playing like any other player
+struct Bot<R, W> {
+    input: R,
+    output: W,
+    // whether the last command already got its reply, an error, so the board doesn't need sending
+    replied: bool,
+    // whether the bot asked for another game
+    again: bool,
+    // stdin or stdout went wrong, there's no one left to play
+    error: Option<io::Error>,
+}
+
+impl<R: BufRead, W: Write> Bot<R, W> {
+    fn send(&mut self, message: &Message) {
+        let sent = serde_json::to_writer(&mut self.output, message)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(self.output))
+            .and_then(|_| self.output.flush());
+
+        if let Err(error) = sent {
+            self.error.get_or_insert(error);
+        }
+    }
+
+    // the next command, skipping blank lines and answering the ones that don't make sense. None once the bot's gone
+    fn read(&mut self) -> Option<Command> {
+        while self.error.is_none() {
+            let mut line = String::new();
+
+            match self.input.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) if line.trim().is_empty() => {}
+                Ok(_) => match serde_json::from_str(&line) {
+                    Ok(command) => return Some(command),
+                    Err(error) => self.send(&Message::Error {
+                        message: format!("couldn't read that command: {error}"),
+                    }),
+                },
+                Err(error) => self.error = Some(error),
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: BufRead, W: Write> Player for Bot<R, W> {
+    fn next_action(&mut self, view: &BoardView) -> Action {
+        if !std::mem::take(&mut self.replied) {
+            self.send(&Message::state(view));
+        }
+
+        match self.read() {
+            Some(Command::Reveal { x, y }) => Action::Reveal(x, y),
+            Some(Command::Flag { x, y }) => Action::Flag(x, y),
+            Some(Command::Chord { x, y }) => Action::Chord(x, y),
+            Some(Command::New) => {
+                self.again = true;
+                Action::Quit
+            }
+            Some(Command::Quit) | None => Action::Quit,
+        }
+    }
+
+    fn rejected(&mut self, reason: &str) {
+        self.send(&Message::Error {
+            message: reason.to_string(),
+        });
+        self.replied = true;
+    }
+
+    // send the final board and wait to hear whether there's another game
+    fn finished(&mut self, view: &BoardView) {
+        if view.standing() == Standing::Playing {
+            return;
+        }
+
+        self.send(&Message::state(view));
+
+        loop {
+            match self.read() {
+                Some(Command::New) => return self.again = true,
+                Some(Command::Quit) | None => return,
+                Some(_) => self.send(&Message::Error {
+                    message: "the game's over, send new to start another or quit".to_string(),
+                }),
+            }
+        }
+    }
+}
+
+// play games for a bot on `input` and `output` until it sends quit or closes its end. every command gets the state of
+// the board back, or an error if it couldn't be done. games are on `settings`, or on `fixed` if there is one
+pub fn run(
+    settings: Settings,
+    fixed: Option<Board>,
+    input: impl BufRead,
+    output: impl Write,
+) -> io::Result<()> {
+    let mut bot = Bot {
+        input,
+        output,
+        replied: false,
+        again: true,
+        error: None,
+    };
+
+    while std::mem::take(&mut bot.again) {
+        let mut round = match &fixed {
+            Some(board) => Round::with_board(board.clone()),
+            None => Round::new(settings, rand::random()),
+        };
+
+        player::play(&mut bot, &mut round);
+    }
+
+    bot.error.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_get_the_board_back_after_every_command() {
+        let board = Board::with_mines(3, 1, &[2]);
+        let input = "{\"action\": \"flag\", \"x\": 2, \"y\": 0}\nnonsense\n{\"action\": \"reveal\", \"x\": 0, \"y\": 0}\n";
+        let mut output = Vec::new();
+
+        let settings = Settings {
+            width: 9,
+            height: 9,
+            mines: 10,
+            placement: Default::default(),
+        };
+
+        run(settings, Some(board), input.as_bytes(), &mut output).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1]["board"][0], "--f");
+        assert_eq!(lines[2]["type"], "error");
+        assert_eq!(lines[3]["status"], "won");
+        assert_eq!(lines[3]["board"][0], "01f");
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{board::Board, paths, replay::Transform};
+
+// a set of boards to play through in order, written as YAML:
+//
+// name: Starter pack
+// puzzles:
+//   - name: 1-2-1
+//     hint: the mines are under the 1s
+//     board:
+//       - "*-*"
+//       - "ooo"
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pack {
+    pub name: String,
+    pub puzzles: Vec<Puzzle>,
+}
+
+// one board with its mines and the cells that start out uncovered
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub name: String,
+    // how to solve it, shown after a mistake or when asked for
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub hint: String,
+    // the board from the top row down: * is a covered mine, - a covered safe cell, o an uncovered cell and # a wall
+    pub board: Vec<String>,
+}
+
+impl Pack {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = serde_yaml::to_string(self)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(ErrorKind::InvalidData, message);
+        let pack: Self = serde_yaml::from_str(text).map_err(|error| invalid(error.to_string()))?;
+
+        if pack.puzzles.is_empty() {
+            return Err(invalid(format!("{} doesn't have any puzzles", pack.name)));
+        }
+
+        let mut names = BTreeSet::new();
+
+        for puzzle in &pack.puzzles {
+            if !names.insert(&puzzle.name) {
+                return Err(invalid(format!(
+                    "there's more than one puzzle called {}",
+                    puzzle.name
+                )));
+            }
+
+            let width = puzzle
+                .board
+                .first()
+                .map(|row| row.chars().count())
+                .unwrap_or(0);
+
+            if width == 0 || puzzle.board.iter().any(|row| row.chars().count() != width) {
+                return Err(invalid(format!(
+                    "{}: every row of the board has to be the same length, and not empty",
+                    puzzle.name
+                )));
+            }
+
+            if let Some(cell) = puzzle
+                .board
+                .iter()
+                .flat_map(|row| row.chars())
+                .find(|cell| !"*-o#".contains(*cell))
+            {
+                return Err(invalid(format!(
+                    "{}: {cell:?} isn't a cell, use * for mines, - for covered cells, o for uncovered ones and # for walls",
+                    puzzle.name
+                )));
+            }
+        }
+
+        Ok(pack)
+    }
+}
+
+impl Puzzle {
+    // the puzzle laid out on a board turned by `transform`, with its open cells uncovered
+    pub fn board(&self, transform: Option<Transform>) -> Board {
+        let (width, height) = (self.board[0].chars().count(), self.board.len());
+        let place = |x, y| match transform {
+            Some(transform) => transform.apply(x, y, width, height),
+            None => (x, y),
+        };
+        let board_width = match transform {
+            Some(_) => height,
+            None => width,
+        };
+
+        // the rows go from the top down and y goes up the board
+        let cells: Vec<((usize, usize), char)> = self
+            .board
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(x, cell)| (place(x, height - 1 - row), cell))
+            })
+            .collect();
+
+        let mines: Vec<usize> = cells
+            .iter()
+            .filter(|(_, cell)| *cell == '*')
+            .map(|&((x, y), _)| y * board_width + x)
+            .collect();
+
+        let mut board = Board::with_mines(board_width, cells.len() / board_width, &mines);
+        for &((x, y), cell) in &cells {
+            match cell {
+                'o' => board.cell_mut(x, y).set_uncovered(true),
+                '#' => board.add_wall(x, y),
+                _ => {}
+            }
+        }
+
+        board
+    }
+}
+
+// the puzzles that have been solved in every pack, by pack and puzzle name, kept in puzzles.json
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    packs: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Progress {
+    pub fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        serde_json::from_str(&text).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn is_solved(&self, pack: &Pack, puzzle: &Puzzle) -> bool {
+        self.packs
+            .get(&pack.name)
+            .is_some_and(|solved| solved.contains(&puzzle.name))
+    }
+
+    pub fn solved(&self, pack: &Pack) -> usize {
+        pack.puzzles
+            .iter()
+            .filter(|puzzle| self.is_solved(pack, puzzle))
+            .count()
+    }
+
+    pub fn add(&mut self, pack: &Pack, puzzle: &Puzzle) {
+        self.packs
+            .entry(pack.name.clone())
+            .or_default()
+            .insert(puzzle.name.clone());
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("puzzles.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_are_checked() {
+        let error = |text: &str| Pack::parse(text).unwrap_err().to_string();
+
+        assert_eq!(
+            error("name: empty\npuzzles: []"),
+            "empty doesn't have any puzzles"
+        );
+        assert_eq!(
+            error("name: p\npuzzles:\n  - { name: a, board: ['*-', 'ooo'] }"),
+            "a: every row of the board has to be the same length, and not empty"
+        );
+        assert!(error("name: p\npuzzles:\n  - { name: a, board: ['*x'] }")
+            .starts_with("a: 'x' isn't a cell"));
+        assert_eq!(
+            error(
+                "name: p\npuzzles:\n  - { name: a, board: ['*-'] }\n  - { name: a, board: ['-*'] }"
+            ),
+            "there's more than one puzzle called a"
+        );
+    }
+}
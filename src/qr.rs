@@ -0,0 +1,37 @@
+use qrcode::{Color, QrCode};
+
+// draw `data` as a QR code out of half block characters, two rows of modules to a line of text.
+// it's drawn light on dark with a quiet zone around it since that's what phones expect to see
+pub fn render(data: &str) -> Option<String> {
+    let code = QrCode::new(data).ok()?;
+    let width = code.width();
+    let colors = code.to_colors();
+
+    // the quiet zone every scanner wants around the code, in modules
+    let margin = 2;
+    let size = width + (margin * 2);
+
+    let dark = |x: usize, y: usize| {
+        let (x, y) = (x.checked_sub(margin)?, y.checked_sub(margin)?);
+        (x < width && y < width).then(|| colors[(y * width) + x] == Color::Dark)
+    };
+
+    let mut lines = Vec::new();
+
+    for y in (0..size).step_by(2) {
+        let line: String = (0..size)
+            .map(
+                |x| match (dark(x, y).unwrap_or(false), dark(x, y + 1).unwrap_or(false)) {
+                    (false, false) => '█',
+                    (false, true) => '▀',
+                    (true, false) => '▄',
+                    (true, true) => ' ',
+                },
+            )
+            .collect();
+
+        lines.push(line);
+    }
+
+    Some(lines.join("\n"))
+}
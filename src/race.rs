@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use crate::code::GameCode;
+
+// two players taking turns at the same keyboard, each round on one board they both play. whoever clears it faster
+// takes the round, and a mine or giving up means no time at all
+#[derive(Clone, Debug)]
+pub struct Race {
+    pub players: [String; 2],
+    // added to each player's clears, so someone faster can give the other a head start
+    pub handicaps: [Duration; 2],
+    // the board this round is on, picked when its first turn starts
+    pub code: Option<GameCode>,
+    // how long each player took on every round so far, None if they didn't clear it
+    rounds: Vec<[Option<Duration>; 2]>,
+    // the round being played
+    current: [Option<Duration>; 2],
+    // how many turns of the round being played are done
+    turns: usize,
+}
+
+impl Race {
+    pub fn new(players: [String; 2], handicaps: [Duration; 2]) -> Self {
+        Self {
+            players,
+            handicaps,
+            code: None,
+            rounds: Vec::new(),
+            current: [None; 2],
+            turns: 0,
+        }
+    }
+
+    // the round being played, counting from 1
+    pub fn round(&self) -> usize {
+        self.rounds.len() + 1
+    }
+
+    // which player's turn it is. the first go swaps every round, since whoever goes second gets to watch
+    pub fn turn(&self) -> usize {
+        (self.rounds.len() + self.turns) % 2
+    }
+
+    pub fn player(&self) -> &str {
+        &self.players[self.turn()]
+    }
+
+    // write down how the player whose turn it was did, returns whether that was the end of the round. the time's
+    // the one on their clock, their handicap goes on top
+    pub fn finish(&mut self, time: Option<Duration>) -> bool {
+        let handicap = self.handicaps[self.turn()];
+        self.current[self.turn()] = time.map(|time| time.saturating_add(handicap));
+        self.turns += 1;
+
+        if self.turns < 2 {
+            return false;
+        }
+
+        self.rounds.push(std::mem::take(&mut self.current));
+        self.turns = 0;
+        self.code = None;
+        true
+    }
+
+    pub fn is_started(&self) -> bool {
+        !self.rounds.is_empty()
+    }
+
+    // the rounds each player's taken
+    pub fn wins(&self) -> [usize; 2] {
+        let mut wins = [0; 2];
+
+        for winner in self.rounds.iter().filter_map(winner) {
+            wins[winner] += 1;
+        }
+
+        wins
+    }
+
+    // who took the last round, or what happened if nobody did
+    pub fn last_round(&self) -> String {
+        match self.rounds.last().and_then(winner) {
+            Some(player) => format!(
+                "round {} goes to {}",
+                self.rounds.len(),
+                self.players[player]
+            ),
+            None => format!("nobody cleared round {}", self.rounds.len()),
+        }
+    }
+
+    // every round's times and who's ahead, as a table
+    pub fn scoreboard(&self) -> Vec<String> {
+        let width = self
+            .players
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(6)
+            + 2;
+        let seconds = |time: Option<Duration>| {
+            time.map(|time| format!("{:.1}s", time.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let mut lines = vec![format!(
+            "{:<10}{:>width$}{:>width$}",
+            "", self.players[0], self.players[1]
+        )];
+
+        if self.handicaps != [Duration::ZERO; 2] {
+            let handicaps = self
+                .handicaps
+                .map(|handicap| format!("+{}s", handicap.as_secs()));
+            lines.push(format!(
+                "{:<10}{:>width$}{:>width$}",
+                "handicap", handicaps[0], handicaps[1]
+            ));
+        }
+
+        for (n, times) in self.rounds.iter().enumerate() {
+            let won = |player: usize| {
+                if winner(times) == Some(player) {
+                    "*"
+                } else {
+                    ""
+                }
+            };
+            let times = [0, 1].map(|player| format!("{}{}", won(player), seconds(times[player])));
+            lines.push(format!(
+                "{:<10}{:>width$}{:>width$}",
+                format!("round {}", n + 1),
+                times[0],
+                times[1]
+            ));
+        }
+
+        let wins = self.wins();
+        lines.push(format!(
+            "{:<10}{:>width$}{:>width$}",
+            "rounds", wins[0], wins[1]
+        ));
+
+        lines
+    }
+}
+
+// the faster of a round's clears, a draw if they're the same to the millisecond
+fn winner(times: &[Option<Duration>; 2]) -> Option<usize> {
+    match *times {
+        [Some(first), Some(second)] if first.as_millis() == second.as_millis() => None,
+        [Some(first), Some(second)] => Some(if first < second { 0 } else { 1 }),
+        [Some(_), None] => Some(0),
+        [None, Some(_)] => Some(1),
+        [None, None] => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_fastest_clear_takes_the_round() {
+        let mut race = Race::new(["Ann".to_string(), "Bob".to_string()], [Duration::ZERO; 2]);
+        let seconds = |seconds| Some(Duration::from_secs(seconds));
+
+        // Ann goes first in round 1
+        assert_eq!(race.player(), "Ann");
+        assert!(!race.finish(seconds(30)));
+        assert!(race.finish(seconds(20)));
+        assert_eq!(race.last_round(), "round 1 goes to Bob");
+
+        // and Bob in round 2, where a mine loses to any time
+        assert_eq!(race.player(), "Bob");
+        assert!(!race.finish(None));
+        assert!(race.finish(seconds(90)));
+        assert_eq!(race.wins(), [1, 1]);
+
+        race.finish(None);
+        race.finish(None);
+        assert_eq!(race.last_round(), "nobody cleared round 3");
+        assert_eq!(race.scoreboard()[2], "round 2     *90.0s       -");
+    }
+
+    #[test]
+    fn handicaps_go_on_top_of_the_clock() {
+        let mut race = Race::new(
+            ["Ann".to_string(), "Bob".to_string()],
+            [Duration::ZERO, Duration::from_secs(15)],
+        );
+
+        // Bob's quicker on the clock but not once his 15 seconds are added
+        race.finish(Some(Duration::from_secs(30)));
+        race.finish(Some(Duration::from_secs(20)));
+        assert_eq!(race.last_round(), "round 1 goes to Ann");
+
+        let scoreboard = race.scoreboard();
+        assert_eq!(scoreboard[1], "handicap       +0s    +15s");
+        assert_eq!(scoreboard[2], "round 1     *30.0s   35.0s");
+    }
+}
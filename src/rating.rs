@@ -0,0 +1,125 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{
+    analysis::{self, Judgement, Step},
+    board::Board,
+};
+
+// roughly how hard a board is to clear, going by how much clicking it takes and how much luck
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Band {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Band {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Band::Easy => "easy",
+            Band::Medium => "medium",
+            Band::Hard => "hard",
+        }
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Band {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [Band::Easy, Band::Medium, Band::Hard]
+            .into_iter()
+            .find(|band| band.name() == name)
+            .ok_or_else(|| "pick easy, medium or hard".to_string())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Rating {
+    // how much of the board has to be clicked instead of opening up by itself: its 3BV for every safe cell, compared
+    // to the usual for its mine density, which is about twice the density. 1 is average, higher means more clicking
+    pub clicks: f64,
+    // the chance the solver hits a mine on one of the guesses it's forced into on the way to clearing the board
+    pub luck: f64,
+}
+
+impl Rating {
+    // rate a board that's been generated, as it is after the first click at `start`
+    pub fn of(board: &Board, start: (usize, usize)) -> Self {
+        let mut board = board.clone();
+        board.uncover(start.0, start.1);
+
+        Self::from_solution(&board, &analysis::solution(&board))
+    }
+
+    // rate a board that's had its first click from the solver's line through the rest of it
+    pub fn from_solution(board: &Board, steps: &[Step]) -> Self {
+        let cells = board.width() * board.height();
+        let safe = cells - board.num_mines();
+        let usual = 2.0 * board.num_mines() as f64 / cells as f64;
+        let clicks = board.three_bv().1 as f64 / safe.max(1) as f64 / usual.max(f64::EPSILON);
+
+        let survival: f64 = steps
+            .iter()
+            .filter_map(|step| match step.judgement {
+                Judgement::ForcedGuess { probability } => Some(1.0 - probability),
+                _ => None,
+            })
+            .product();
+
+        Self {
+            clicks,
+            luck: 1.0 - survival,
+        }
+    }
+
+    // rate the board on another thread, it can take a moment on the bigger boards and there's no need to hold up the game
+    pub fn in_background(board: Board, start: (usize, usize)) -> Receiver<Self> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(Self::of(&board, start));
+        });
+
+        receiver
+    }
+
+    // a board's hard if it's likely to come down to a bad guess, easy if it needs no guessing and opens up a lot
+    pub fn band(&self) -> Band {
+        if self.luck >= 0.5 || self.clicks >= 1.1 {
+            Band::Hard
+        } else if self.luck == 0.0 && self.clicks < 0.95 {
+            Band::Easy
+        } else {
+            Band::Medium
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coin_flips_make_a_board_hard() {
+        // the 1s can't tell which of the last two cells is the mine
+        let board = Board::with_mines(3, 2, &[2]);
+        let rating = Rating::of(&board, (0, 1));
+        assert_eq!(rating.luck, 0.5);
+        assert_eq!(rating.band(), Band::Hard);
+
+        // one click opens the whole thing
+        let board = Board::with_mines(3, 1, &[2]);
+        assert_eq!(Rating::of(&board, (0, 0)).band(), Band::Easy);
+    }
+}
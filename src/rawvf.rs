@@ -0,0 +1,273 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    board::Board,
+    game::Game,
+    replay::{Action, ActionKind, Replay},
+};
+
+// the binary videos from Minesweeper Arbiter (.avf), Minesweeper Clone and Viennasweeper (.rmv) and Minesweeper X
+// (.mvf). they're read once they've been turned into the RAW video format
+const BINARY_VIDEOS: [&str; 3] = ["avf", "rmv", "mvf"];
+
+// whether `path` is one of the binary videos, which have to be converted before they can be watched
+pub fn is_binary_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| BINARY_VIDEOS.contains(&extension.to_lowercase().as_str()))
+}
+
+// whether `text` is a video in the RAW video format, which starts with the revision it's written in
+pub fn is_rawvf(text: &str) -> bool {
+    text.trim_start().starts_with("RawVF_Version")
+}
+
+// a game from the RAW video format as a replay. the parts that are read are the Width, Height and Mines headers, the
+// board after "Board:" (one row a line from the top, * for a mine and anything else for a safe cell) and the mouse
+// events after "Events:", one a line like `1.23 lr 4 5 (52 68)`: the time in seconds, what the mouse did and the
+// column and row it was over counting from 1 at the top left. lr (left up) opens a cell, rc (right down) flags one,
+// and letting go of both buttons or the middle one on a number chords it. everything else is skipped
+pub fn parse(text: &str) -> Result<Replay, String> {
+    let mut headers = HashMap::new();
+    let mut rows = Vec::new();
+    let mut events = Vec::new();
+    let mut section = "";
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match line {
+            "Board:" | "Events:" => section = line,
+            _ if section == "Board:" => rows.push(line),
+            _ if section == "Events:" => events.push(line),
+            _ => {
+                if let Some((key, value)) = line.split_once(':') {
+                    headers.insert(key.trim(), value.trim());
+                }
+            }
+        }
+    }
+
+    let header = |key: &str| -> Result<usize, String> {
+        let value = headers
+            .get(key)
+            .ok_or(format!("there's no {key} in the header"))?;
+        value
+            .parse()
+            .map_err(|_| format!("{key} should be a number, not {value:?}"))
+    };
+
+    let (width, height) = (header("Width")?, header("Height")?);
+
+    if rows.len() != height || rows.iter().any(|row| row.chars().count() != width) {
+        return Err(format!(
+            "the board should be {height} rows of {width} cells"
+        ));
+    }
+
+    let mines: Vec<usize> = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|&(_, cell)| cell == '*')
+                .map(move |(x, _)| ((height - 1 - row) * width) + x)
+        })
+        .collect();
+
+    if mines.len() != header("Mines")? {
+        return Err(format!(
+            "the board has {} mines but the header says {}",
+            mines.len(),
+            header("Mines")?
+        ));
+    }
+
+    let level = (1..=3)
+        .find(|&level| {
+            let settings = Game::level_settings(level);
+            (settings.width, settings.height, settings.mines) == (width, height, mines.len())
+        })
+        .unwrap_or(0);
+
+    let mut replay = Replay::new(level, width, height);
+    replay.mines = mines;
+
+    let mut video = Video {
+        board: Board::with_mines(width, height, &replay.mines),
+        replay,
+        started: None,
+        left: false,
+        right: false,
+    };
+
+    for event in events {
+        let mut fields = event.split_whitespace();
+        let time: f64 = match fields.next().and_then(|time| time.parse().ok()) {
+            Some(time) => time,
+            None => continue,
+        };
+        let kind = fields.next().unwrap_or_default();
+        let cell = match (
+            fields.next().and_then(|x| x.parse().ok()),
+            fields.next().and_then(|y| y.parse().ok()),
+        ) {
+            (Some(column), Some(row))
+                if (1..=width).contains(&column) && (1..=height).contains(&row) =>
+            {
+                Some((column - 1, height - row))
+            }
+            _ => None,
+        };
+
+        video.event(time, kind, cell);
+
+        // nothing after a mine's gone off changes the game
+        if video
+            .board
+            .positions()
+            .any(|(x, y)| video.board.is_mine(x, y) && video.board.is_uncovered(x, y))
+        {
+            break;
+        }
+    }
+
+    if video.replay.actions.is_empty() {
+        return Err("there aren't any clicks in the video".to_string());
+    }
+
+    Ok(video.replay)
+}
+
+// the game as the video plays out, with which mouse buttons are down
+struct Video {
+    board: Board,
+    replay: Replay,
+    // when the first cell was opened, in the video's seconds
+    started: Option<f64>,
+    left: bool,
+    right: bool,
+}
+
+impl Video {
+    fn event(&mut self, time: f64, kind: &str, cell: Option<(usize, usize)>) {
+        let both = self.left && self.right;
+
+        match kind {
+            "lc" => self.left = true,
+            "rc" => self.right = true,
+            "lr" => self.left = false,
+            "rr" => self.right = false,
+            _ => {}
+        }
+
+        let (x, y) = match cell {
+            Some(cell) => cell,
+            None => return,
+        };
+
+        match kind {
+            // a right click on its own flags, with the left button down it's the start of a chord
+            "rc" if !self.left && !self.board.is_uncovered(x, y) => {
+                self.act(time, ActionKind::Mark, x, y)
+            }
+            "lr" | "rr" | "mr" if both || kind == "mr" => self.chord(time, x, y),
+            "lr" if !self.board.is_uncovered(x, y) && !self.board.is_flagged(x, y) => {
+                self.act(time, ActionKind::Reveal, x, y)
+            }
+            _ => {}
+        }
+    }
+
+    // open everything around a number that has all its flags, the same as chording in the game
+    fn chord(&mut self, time: f64, x: usize, y: usize) {
+        let board = &self.board;
+        let flags = board
+            .neighbors(x, y)
+            .filter(|&(x, y)| board.is_flagged(x, y))
+            .count();
+
+        if !board.is_uncovered(x, y) || board.adjacent_mines(x, y) != flags {
+            return;
+        }
+
+        let cells: Vec<_> = board
+            .neighbors(x, y)
+            .filter(|&(x, y)| !board.is_uncovered(x, y) && !board.is_flagged(x, y))
+            .collect();
+
+        for (x, y) in cells {
+            self.act(time, ActionKind::Reveal, x, y);
+        }
+    }
+
+    fn act(&mut self, time: f64, kind: ActionKind, x: usize, y: usize) {
+        match kind {
+            ActionKind::Reveal => {
+                self.started.get_or_insert(time);
+                self.board.uncover(x, y);
+            }
+            ActionKind::Mark => self.board.toggle_flag(x, y),
+        }
+
+        // replays count from the first cell opened, so flags from before then all go down at the start
+        let since = time - self.started.unwrap_or(time);
+
+        self.replay.actions.push(Action {
+            time: (since.max(0.0) * 1000.0).round() as u64,
+            kind,
+            x,
+            y,
+            uncovered: self.board.num_uncovered(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_and_chords_become_actions() {
+        let video = "RawVF_Version: Rev5
+Program: Minesweeper Arbiter
+Width: 4
+Height: 3
+Mines: 2
+Board:
+*...
+....
+...*
+Events:
+0.00 start
+0.00 lc 4 1 (56 8)
+0.00 lr 4 1 (56 8)
+0.50 rc 1 1 (8 8)
+0.60 rr 1 1 (8 8)
+1.00 lc 2 2 (24 24)
+1.05 rc 2 2 (24 24)
+1.10 lr 2 2 (24 24)
+1.12 rr 2 2 (24 24)
+1.50 lc 3 3 (40 40)
+1.60 lr 3 3 (40 40)
+";
+
+        let replay = parse(video).unwrap();
+        assert_eq!((replay.level, replay.width, replay.height), (0, 4, 3));
+        // the top left and the bottom right, with y going up
+        assert_eq!(replay.mines, vec![8, 3]);
+
+        let actions: Vec<_> = replay
+            .actions
+            .iter()
+            .map(|action| (action.time, action.kind, action.x, action.y))
+            .collect();
+        assert_eq!(actions[0], (0, ActionKind::Reveal, 3, 2));
+        assert_eq!(actions[1], (500, ActionKind::Mark, 0, 2));
+        // the chord on the 1 next to the flag opens everything left, and the click after it has nothing to open
+        assert!(actions[2..]
+            .iter()
+            .all(|&(time, kind, ..)| (time, kind) == (1100, ActionKind::Reveal)));
+        assert_eq!(replay.actions.last().unwrap().uncovered, 10);
+        assert!(parse("RawVF_Version: Rev5\nWidth: 4\n").is_err());
+    }
+}
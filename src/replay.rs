@@ -0,0 +1,406 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, MAX_CELLS},
+    config::Assist,
+    generator::Placement,
+    paths, rawvf,
+    versioned::{self, Versioned},
+};
+
+// what a speedrun's split at, in the order splits() gives them
+pub const SPLITS: [&str; 3] = ["first opening", "half cleared", "last flag"];
+
+// a record of a single game: the board it was played on and everything the player did
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub level: u8,
+    pub width: usize,
+    pub height: usize,
+    // the seed the layout was generated from, before it was shifted under the first click
+    #[serde(default)]
+    pub seed: Option<u64>,
+    // indices (y * width + x) of every mine on the board
+    pub mines: Vec<usize>,
+    pub actions: Vec<Action>,
+    // how much the game helped, the cells it opened are in the actions like any other reveal
+    #[serde(default)]
+    pub assist: Assist,
+    // played with flagging turned off
+    #[serde(default)]
+    pub no_flags: bool,
+    // the day (since 1970-01-01) of the daily challenge this was, if it was one
+    #[serde(default)]
+    pub daily: Option<u64>,
+    // how the mines were spread out
+    #[serde(default)]
+    pub placement: Placement,
+    // how long there was to clear the board in a timed challenge, in milliseconds
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Action {
+    // milliseconds since the first cell was uncovered
+    pub time: u64,
+    pub kind: ActionKind,
+    pub x: usize,
+    pub y: usize,
+    // how many cells were uncovered on the board after this action
+    pub uncovered: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionKind {
+    Reveal,
+    Mark,
+}
+
+// saved games carry a replay inside them, a change here needs a new version of theirs too
+impl Versioned for Replay {
+    const VERSION: u32 = 1;
+}
+
+impl Replay {
+    pub fn new(level: u8, width: usize, height: usize) -> Self {
+        Self {
+            level,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    // how long the whole game took
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.actions.last().map(|action| action.time).unwrap_or(0))
+    }
+
+    // how long it took this run to uncover at least `uncovered` cells
+    pub fn pace_at(&self, uncovered: usize) -> Option<Duration> {
+        self.actions
+            .iter()
+            .find(|action| action.uncovered >= uncovered)
+            .map(|action| Duration::from_millis(action.time))
+    }
+
+    // when the game got to each of SPLITS: the first reveal that opened more than the one cell, half the safe cells
+    // being open and the flag that made as many flags as mines. None for the ones it never got to
+    pub fn splits(&self) -> [Option<Duration>; 3] {
+        let safe = (self.width * self.height).saturating_sub(self.mines.len());
+        let mut splits = [None; 3];
+        let mut uncovered = 0;
+        let mut flags = HashSet::new();
+
+        for action in &self.actions {
+            let time = Some(Duration::from_millis(action.time));
+
+            match action.kind {
+                ActionKind::Reveal => {
+                    if splits[0].is_none() && action.uncovered > uncovered + 1 {
+                        splits[0] = time;
+                    }
+                    if splits[1].is_none() && action.uncovered * 2 >= safe {
+                        splits[1] = time;
+                    }
+                    uncovered = action.uncovered;
+                }
+                ActionKind::Mark => {
+                    if !flags.insert((action.x, action.y)) {
+                        flags.remove(&(action.x, action.y));
+                    }
+                    if splits[2].is_none()
+                        && !self.mines.is_empty()
+                        && flags.len() == self.mines.len()
+                    {
+                        splits[2] = time;
+                    }
+                }
+            }
+        }
+
+        splits
+    }
+
+    // a replay of ours, or a video from another minesweeper in the RAW video format
+    pub fn load(path: &str) -> io::Result<Self> {
+        if rawvf::is_binary_video(Path::new(path)) {
+            let message = "videos from other minesweepers have to be converted to the RAW video format (RawVF) first";
+            return Err(io::Error::new(ErrorKind::InvalidData, message));
+        }
+
+        let text = fs::read_to_string(path)?;
+
+        let replay: Self = match rawvf::is_rawvf(&text) {
+            true => rawvf::parse(&text)
+                .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?,
+            false => versioned::from_str(&text)?,
+        };
+
+        replay.check()?;
+        Ok(replay)
+    }
+
+    // whether this could be a game that was played: a board minesweeper can hold, with every mine and move on it.
+    // anything read from a file goes through here before a board's made from it
+    pub fn check(&self) -> io::Result<()> {
+        let invalid = |message: String| Err(io::Error::new(ErrorKind::InvalidData, message));
+        let (width, height) = (self.width, self.height);
+
+        let size = match width.checked_mul(height) {
+            Some(0) => return invalid(format!("the replay's {width}x{height} board has no cells")),
+            Some(size) if size <= MAX_CELLS => size,
+            _ => {
+                return invalid(format!(
+                    "the replay's {width}x{height} board is bigger than minesweeper can play"
+                ))
+            }
+        };
+
+        if let Some(mine) = self.mines.iter().find(|&&mine| mine >= size) {
+            return invalid(format!(
+                "the replay has a mine at {mine}, off its {width}x{height} board"
+            ));
+        }
+
+        if let Some(action) = self
+            .actions
+            .iter()
+            .find(|action| action.x >= width || action.y >= height)
+        {
+            return invalid(format!(
+                "the replay has a move at ({}, {}), off its {width}x{height} board",
+                action.x, action.y
+            ));
+        }
+
+        Ok(())
+    }
+
+    // the board the game was played on, before any of its moves. only for replays that have been checked
+    pub fn board(&self) -> Board {
+        Board::with_mines(self.width, self.height, &self.mines)
+    }
+
+    // the same game played on the board turned by `transform`
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let (width, height) = (self.height, self.width);
+        let map = |x, y| transform.apply(x, y, self.width, self.height);
+
+        let mines = self
+            .mines
+            .iter()
+            .map(|i| {
+                let (x, y) = map(i % self.width, i / self.width);
+                (y * width) + x
+            })
+            .collect();
+
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| {
+                let (x, y) = map(action.x, action.y);
+                Action { x, y, ..*action }
+            })
+            .collect();
+
+        Self {
+            level: self.level,
+            width,
+            height,
+            seed: self.seed,
+            mines,
+            actions,
+            assist: self.assist,
+            no_flags: self.no_flags,
+            daily: self.daily,
+            placement: self.placement,
+            time_limit: self.time_limit,
+        }
+    }
+
+    // load the fastest winning run for a level with the given assist and flagging, if there is one
+    pub fn load_best(level: u8, assist: Assist, no_flags: bool) -> io::Result<Option<Self>> {
+        match Self::best_path(level, assist, no_flags) {
+            Some(path) => Self::load_saved(path),
+            None => Ok(None),
+        }
+    }
+
+    // the last game that was finished, if there's been one
+    pub fn load_last() -> io::Result<Option<Self>> {
+        match Self::last_path() {
+            Some(path) => Self::load_saved(path),
+            None => Ok(None),
+        }
+    }
+
+    pub fn last_path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("last.json"))
+    }
+
+    fn load_saved(path: PathBuf) -> io::Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let replay: Self = versioned::from_str(&text)?;
+        replay.check()?;
+        Ok(Some(replay))
+    }
+
+    pub fn save_best(&self) -> io::Result<()> {
+        match Self::best_path(self.level, self.assist, self.no_flags) {
+            Some(path) => self.save(path),
+            None => Ok(()),
+        }
+    }
+
+    // keep the game that just finished around so it can be watched or analysed
+    pub fn save_last(&self) -> io::Result<()> {
+        match Self::last_path() {
+            Some(path) => self.save(path),
+            None => Ok(()),
+        }
+    }
+
+    fn save(&self, path: PathBuf) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, versioned::to_string(self)?)
+    }
+
+    // assisted and no-flag runs are kept apart so they never count against regular ones
+    fn best_path(level: u8, assist: Assist, no_flags: bool) -> Option<PathBuf> {
+        let mut name = format!("level-{level}");
+
+        if assist != Assist::Off {
+            name.push_str(&format!("-{}", assist.name()));
+        }
+
+        if no_flags {
+            name.push_str("-nf");
+        }
+
+        let name = format!("{name}.json");
+
+        paths::data_dir().map(|dir| dir.join("best").join(name))
+    }
+}
+
+// ways to turn a board on its side so a tall board fits a wide terminal (or the other way around)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Transform {
+    // mirror along the diagonal, swapping x and y
+    Transpose,
+    RotateClockwise,
+    RotateAnticlockwise,
+}
+
+impl Transform {
+    // where (x, y) on a width * height board ends up. y goes up the board
+    pub fn apply(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Transform::Transpose => (y, x),
+            Transform::RotateClockwise => (y, width - 1 - x),
+            Transform::RotateAnticlockwise => (height - 1 - y, x),
+        }
+    }
+
+    // suggests turning the board if it doesn't fit in the terminal as it is but would once turned.
+    // every cell takes up gap_x + 1 columns and gap_y + 1 rows, plus two rows under the board for messages
+    pub fn suggest(
+        width: usize,
+        height: usize,
+        gap_x: usize,
+        gap_y: usize,
+        columns: u16,
+        rows: u16,
+    ) -> Option<Self> {
+        let fits = |width: usize, height: usize| {
+            width * (gap_x + 1) <= columns as usize && (height * (gap_y + 1)) + 2 <= rows as usize
+        };
+
+        if !fits(width, height) && fits(height, width) {
+            Some(Transform::Transpose)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_off_their_board_are_refused() {
+        let mut replay = Replay::new(1, 3, 3);
+        replay.mines = vec![50];
+        assert!(replay.check().is_err());
+
+        replay.mines = vec![8];
+        assert!(replay.check().is_ok());
+
+        replay.actions.push(Action {
+            time: 0,
+            kind: ActionKind::Reveal,
+            x: 3,
+            y: 0,
+            uncovered: 1,
+        });
+        assert!(replay.check().is_err());
+
+        assert!(Replay::new(1, 3_000_000, 3_000_000).check().is_err());
+        assert!(Replay::new(1, usize::MAX, 2).check().is_err());
+        assert!(Replay::new(1, 0, 9).check().is_err());
+    }
+
+    #[test]
+    fn splits_come_from_the_actions() {
+        let action = |time, kind, x, uncovered| Action {
+            time,
+            kind,
+            x,
+            y: 0,
+            uncovered,
+        };
+
+        // a 4x1 board with a mine at each end: one click opens a cell, the next opens the other, then both get flagged
+        let mut replay = Replay::new(0, 4, 1);
+        replay.mines = vec![0, 3];
+        replay.actions = vec![
+            action(0, ActionKind::Reveal, 1, 1),
+            action(400, ActionKind::Reveal, 2, 2),
+            action(900, ActionKind::Mark, 0, 2),
+            action(1000, ActionKind::Mark, 0, 2),
+            action(1200, ActionKind::Mark, 3, 2),
+            action(1500, ActionKind::Mark, 0, 2),
+        ];
+
+        let at = |millis| Some(Duration::from_millis(millis));
+        // the first reveal only opened the one cell, and the flag taken back off doesn't count
+        assert_eq!(replay.splits(), [None, at(0), at(1500)]);
+
+        // on a 5x1 board with one mine at the end, the first click opens everything else up
+        let mut replay = Replay::new(0, 5, 1);
+        replay.mines = vec![0];
+        replay.actions = vec![action(0, ActionKind::Reveal, 4, 4)];
+        assert_eq!(replay.splits(), [at(0), at(0), None]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    paths,
+    replay::Replay,
+    versioned::{self, Versioned},
+};
+
+// the longest name a save slot can have
+pub const NAME_LENGTH: usize = 24;
+
+// a game part way through, enough to put it back on the board and carry on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    // the seed, the mines and everything done so far
+    pub replay: Replay,
+    // how long the clock had been running, in milliseconds. it keeps going after the last move, so it's kept apart
+    pub elapsed: u64,
+}
+
+// the autosave
+impl Versioned for SavedGame {
+    const VERSION: u32 = 1;
+}
+
+impl SavedGame {
+    pub fn new(replay: &Replay, elapsed: Duration) -> Self {
+        Self {
+            replay: replay.clone(),
+            elapsed: elapsed.as_millis() as u64,
+        }
+    }
+
+    // how far into the game it was, never before its last move
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed).max(self.replay.duration())
+    }
+
+    // how many of the board's safe cells are open, as a percentage
+    pub fn progress(&self) -> usize {
+        let safe = (self.replay.width * self.replay.height).saturating_sub(self.replay.mines.len());
+        let opened = self
+            .replay
+            .actions
+            .last()
+            .map(|action| action.uncovered)
+            .unwrap_or(0);
+
+        (opened * 100).checked_div(safe).unwrap_or(0)
+    }
+}
+
+// a game saved under a name so it can be carried on later
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Slot {
+    pub name: String,
+    pub game: SavedGame,
+}
+
+// every save slot, in the order they were first saved to
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Slots {
+    pub slots: Vec<Slot>,
+}
+
+impl Versioned for Slots {
+    const VERSION: u32 = 1;
+}
+
+impl Slots {
+    pub fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        // a slot that couldn't have been played is left out rather than losing the rest with it
+        let mut slots: Self = versioned::from_str(&text)?;
+        slots.slots.retain(|slot| slot.game.replay.check().is_ok());
+        Ok(slots)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, versioned::to_string(self)?)
+    }
+
+    // save `game` to the slot called `name`, over whatever was there before
+    pub fn put(&mut self, name: &str, game: SavedGame) {
+        let slot = Slot {
+            name: name.to_string(),
+            game,
+        };
+
+        match self.slots.iter_mut().find(|slot| slot.name == name) {
+            Some(kept) => *kept = slot,
+            None => self.slots.push(slot),
+        }
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("saves.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::{Action, ActionKind};
+
+    #[test]
+    fn saving_to_a_slot_again_writes_over_it() {
+        let mut replay = Replay::new(1, 9, 9);
+        replay.mines = (0..10).collect();
+        replay.actions.push(Action {
+            time: 2000,
+            kind: ActionKind::Reveal,
+            x: 8,
+            y: 8,
+            uncovered: 35,
+        });
+
+        let mut slots = Slots::default();
+        slots.put("lunch", SavedGame::new(&replay, Duration::from_secs(5)));
+        slots.put("train", SavedGame::new(&replay, Duration::from_secs(1)));
+        slots.put("lunch", SavedGame::new(&replay, Duration::from_secs(9)));
+
+        let names: Vec<_> = slots.slots.iter().map(|slot| slot.name.as_str()).collect();
+        assert_eq!(names, ["lunch", "train"]);
+        assert_eq!(slots.slots[0].game.elapsed(), Duration::from_secs(9));
+
+        // the clock never goes back before the last move, and 35 of the 71 safe cells are open
+        assert_eq!(slots.slots[1].game.elapsed(), Duration::from_secs(2));
+        assert_eq!(slots.slots[1].game.progress(), 49);
+    }
+}
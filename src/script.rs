@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use crate::player::Player;
+
+// a bot written as a Rhai script, if this was built with the scripting feature
+#[cfg(feature = "scripting")]
+pub fn load(path: &Path) -> Result<Box<dyn Player>, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    Ok(Box::new(rhai_player::ScriptPlayer::new(&text)?))
+}
+
+#[cfg(not(feature = "scripting"))]
+pub fn load(_path: &Path) -> Result<Box<dyn Player>, String> {
+    Err("this copy of minesweeper was built without the scripting feature".to_string())
+}
+
+#[cfg(feature = "scripting")]
+mod rhai_player {
+    use rhai::{
+        module_resolvers::DummyModuleResolver, Array, Dynamic, Engine, EvalAltResult, Scope, AST,
+    };
+
+    use crate::{
+        player::{Action, BoardView, Player},
+        protocol,
+    };
+
+    // how much work a script gets for each move before it's stopped, so one stuck in a loop can't hang the game
+    const MAX_OPERATIONS: u64 = 10_000_000;
+
+    // what a script gets to see of the board, copied out of the game so it can't touch anything else
+    #[derive(Clone, Debug)]
+    struct ScriptBoard {
+        width: usize,
+        height: usize,
+        mines: usize,
+        flags: usize,
+        // the same characters --protocol sends, bottom row first so (x, y) is at y * width + x
+        cells: Vec<char>,
+    }
+
+    impl ScriptBoard {
+        fn new(view: &BoardView) -> Self {
+            let cells = (0..view.height())
+                .flat_map(|y| (0..view.width()).map(move |x| (x, y)))
+                .map(|(x, y)| protocol::symbol(view.seen(x, y)))
+                .collect();
+
+            Self {
+                width: view.width(),
+                height: view.height(),
+                mines: view.mines(),
+                flags: view.flags(),
+                cells,
+            }
+        }
+
+        fn position(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+            let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+            (x < self.width && y < self.height).then_some((x, y))
+        }
+
+        // the cell's character, or an empty string off the edge of the board
+        fn cell(&mut self, x: i64, y: i64) -> String {
+            self.position(x, y)
+                .map(|(x, y)| self.cells[y * self.width + x].to_string())
+                .unwrap_or_default()
+        }
+
+        fn neighbors(&mut self, x: i64, y: i64) -> Array {
+            let mut cells = Array::new();
+
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                if (dx, dy) != (0, 0) && self.position(x + dx, y + dy).is_some() {
+                    cells.push(Dynamic::from_array(vec![(x + dx).into(), (y + dy).into()]));
+                }
+            }
+
+            cells
+        }
+    }
+
+    // an action on (x, y), as long as neither is negative. anything else off the board gets turned away by the game
+    fn on(
+        x: i64,
+        y: i64,
+        action: fn(usize, usize) -> Action,
+    ) -> Result<Action, Box<EvalAltResult>> {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) => Ok(action(x, y)),
+            _ => Err(format!("({x}, {y}) isn't on the board").into()),
+        }
+    }
+
+    pub struct ScriptPlayer {
+        engine: Engine,
+        ast: AST,
+        // why the script stopped playing, it gives up every game after that
+        error: Option<String>,
+        // moves made this game, so one that flags and unflags forever still ends
+        moves: usize,
+    }
+
+    impl ScriptPlayer {
+        pub fn new(text: &str) -> Result<Self, String> {
+            // rhai can't touch the network, but out of the box `import` reads scripts off the disk. without a
+            // module resolver all a script gets is the standard library, the board and the actions
+            let mut engine = Engine::new();
+            engine.set_module_resolver(DummyModuleResolver::new());
+            engine.set_max_operations(MAX_OPERATIONS);
+            engine.set_max_expr_depths(256, 256);
+            engine.on_print(|text| eprintln!("{text}"));
+
+            engine
+                .register_type_with_name::<ScriptBoard>("Board")
+                .register_get("width", |board: &mut ScriptBoard| board.width as i64)
+                .register_get("height", |board: &mut ScriptBoard| board.height as i64)
+                .register_get("mines", |board: &mut ScriptBoard| board.mines as i64)
+                .register_get("flags", |board: &mut ScriptBoard| board.flags as i64)
+                .register_fn("cell", ScriptBoard::cell)
+                .register_fn("neighbors", ScriptBoard::neighbors);
+
+            engine
+                .register_type_with_name::<Action>("Action")
+                .register_fn("reveal", |x: i64, y: i64| on(x, y, Action::Reveal))
+                .register_fn("flag", |x: i64, y: i64| on(x, y, Action::Flag))
+                .register_fn("chord", |x: i64, y: i64| on(x, y, Action::Chord))
+                .register_fn("quit", || Action::Quit);
+
+            let ast = engine.compile(text).map_err(|error| error.to_string())?;
+
+            if !ast
+                .iter_functions()
+                .any(|function| function.name == "next_action" && function.params.len() == 1)
+            {
+                return Err("the script needs a next_action(board) function".to_string());
+            }
+
+            Ok(Self {
+                engine,
+                ast,
+                error: None,
+                moves: 0,
+            })
+        }
+
+        // stop the script for good, saying why once
+        fn stop(&mut self, reason: String) {
+            if self.error.is_none() {
+                eprintln!("The script stopped playing: {reason}");
+                self.error = Some(reason);
+            }
+        }
+    }
+
+    impl Player for ScriptPlayer {
+        fn next_action(&mut self, view: &BoardView) -> Action {
+            self.moves += 1;
+            if self.moves > view.width() * view.height() * 4 {
+                self.stop(format!(
+                    "it made {} moves without finishing a game",
+                    self.moves - 1
+                ));
+            }
+
+            if self.error.is_some() {
+                return Action::Quit;
+            }
+
+            let board = ScriptBoard::new(view);
+
+            match self.engine.call_fn::<Action>(
+                &mut Scope::new(),
+                &self.ast,
+                "next_action",
+                (board,),
+            ) {
+                Ok(action) => action,
+                Err(error) => {
+                    self.stop(error.to_string());
+                    Action::Quit
+                }
+            }
+        }
+
+        // a script that keeps making the same bad move would never finish, so one bad move ends it
+        fn rejected(&mut self, reason: &str) {
+            self.stop(reason.to_string());
+        }
+
+        fn finished(&mut self, _view: &BoardView) {
+            self.moves = 0;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{
+            board::Board,
+            player::{self, Round, Standing},
+        };
+
+        #[test]
+        fn scripts_play_through_the_same_loop() {
+            let mut script = ScriptPlayer::new(
+                "fn next_action(board) { reveal(board.width / 2, board.height / 2) }",
+            )
+            .unwrap();
+            let mut round = Round::with_board(Board::with_mines(5, 5, &[0]));
+            assert_eq!(player::play(&mut script, &mut round), Standing::Won);
+
+            assert!(ScriptPlayer::new("fn something_else() {}").is_err());
+        }
+
+        #[test]
+        fn scripts_cant_import_files() {
+            let module = std::env::temp_dir().join("minesweeper-script-import.rhai");
+            std::fs::write(&module, "fn middle() { 2 }").unwrap();
+
+            let mut script = ScriptPlayer::new(&format!(
+                "import {:?} as other; fn next_action(board) {{ reveal(other::middle(), 2) }}",
+                module.display().to_string()
+            ))
+            .unwrap();
+            let mut round = Round::with_board(Board::with_mines(5, 5, &[0]));
+            let standing = player::play(&mut script, &mut round);
+            std::fs::remove_file(module).ok();
+
+            assert_ne!(standing, Standing::Won);
+            assert!(script.error.is_some());
+        }
+    }
+}
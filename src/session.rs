@@ -0,0 +1,134 @@
+use std::{
+    fmt::{self, Write},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Assist, game::level_name, replay::Replay};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Won,
+    Lost,
+    // quit or restarted before the game was over
+    Abandoned,
+}
+
+// how one game went
+#[derive(Clone, PartialEq, Debug)]
+pub struct Played {
+    pub level: u8,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    pub seed: Option<u64>,
+    pub assist: Assist,
+}
+
+// every game played since the program started
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    games: Vec<Played>,
+}
+
+impl Session {
+    pub fn add(&mut self, replay: &Replay, outcome: Outcome) {
+        self.games.push(Played {
+            level: replay.level,
+            outcome,
+            duration: replay.duration(),
+            seed: replay.seed,
+            assist: replay.assist,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn won(&self) -> usize {
+        self.games
+            .iter()
+            .filter(|game| game.outcome == Outcome::Won)
+            .count()
+    }
+
+    // the fastest win without any help
+    pub fn best(&self) -> Option<&Played> {
+        self.games
+            .iter()
+            .filter(|game| game.outcome == Outcome::Won && game.assist == Assist::Off)
+            .min_by_key(|game| game.duration)
+    }
+
+    // every game's clock added up
+    pub fn total_time(&self) -> Duration {
+        self.games.iter().map(|game| game.duration).sum()
+    }
+
+    // a few lines of plain text about the session, meant for the scrollback after the game closes.
+    // every game gets its own tab separated line so it's easy to pick apart with cut or awk
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        self.write_summary(&mut summary)
+            .expect("writing to a String can't fail");
+        summary
+    }
+
+    fn write_summary(&self, summary: &mut String) -> fmt::Result {
+        let seconds = |duration: Duration| format!("{:.1}s", duration.as_secs_f64());
+        let count = |outcome| {
+            self.games
+                .iter()
+                .filter(|game| game.outcome == outcome)
+                .count()
+        };
+
+        writeln!(
+            summary,
+            "{} game{}: {} won, {} lost, {} abandoned",
+            self.games.len(),
+            if self.games.len() == 1 { "" } else { "s" },
+            count(Outcome::Won),
+            count(Outcome::Lost),
+            count(Outcome::Abandoned),
+        )?;
+
+        if let Some(best) = self.best() {
+            writeln!(
+                summary,
+                "best time: {} on {}",
+                seconds(best.duration),
+                level_name(best.level)
+            )?;
+        }
+
+        for (n, game) in self.games.iter().enumerate() {
+            let outcome = match game.outcome {
+                Outcome::Won => "won",
+                Outcome::Lost => "lost",
+                Outcome::Abandoned => "abandoned",
+            };
+            let seed = game
+                .seed
+                .map(|seed| seed.to_string())
+                .unwrap_or_else(|| "-".to_string());
+
+            writeln!(
+                summary,
+                "{}\t{}\t{outcome}\t{}\tseed {seed}\tassist {}",
+                n + 1,
+                level_name(game.level),
+                seconds(game.duration),
+                game.assist.name(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
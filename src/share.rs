@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::board::Board;
+
+// how a game ended, for the mark next to the level
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Ending {
+    Won,
+    Lost,
+    GaveUp,
+}
+
+// a few lines of emoji to paste into a chat, Wordle style: what was played, how it went, the time and 3BV and then
+// the board with the mines left out unless they went off, so it doesn't give the board away
+pub fn summary(board: &Board, name: &str, ending: Ending, duration: Duration) -> String {
+    let mark = match ending {
+        Ending::Won => "🏆",
+        Ending::Lost => "💥",
+        Ending::GaveUp => "🏳️",
+    };
+    let (solved, total) = board.three_bv();
+
+    let mut text = format!(
+        "Minesweeper {name} {mark}\n⏱️ {:.1}s | 3BV {solved}/{total}\n",
+        duration.as_secs_f64()
+    );
+
+    for y in (0..board.height()).rev() {
+        for x in 0..board.width() {
+            let cell = board.cell(x, y);
+
+            text.push(
+                match (cell.is_mine(), cell.is_uncovered(), cell.is_flagged()) {
+                    (true, true, _) => '💥',
+                    (_, false, true) => '🚩',
+                    (false, true, _) => '🟩',
+                    (_, false, false) => '⬜',
+                },
+            );
+        }
+
+        text.push('\n');
+    }
+
+    text
+}
+
+// put the summary on the clipboard, if this was built with the clipboard feature
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> Result<(), String> {
+    Err("this copy of minesweeper was built without the clipboard feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_hides_the_mines_that_didnt_go_off() {
+        let mut board = Board::with_mines(3, 2, &[0, 5]);
+        board.uncover(2, 0);
+        board.uncover(0, 0);
+        board.toggle_flag(2, 1);
+
+        assert_eq!(
+            summary(
+                &board,
+                "Beginner",
+                Ending::Lost,
+                Duration::from_millis(4321)
+            ),
+            "Minesweeper Beginner 💥\n⏱️ 4.3s | 3BV 1/4\n⬜⬜🚩\n💥⬜🟩\n"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use crate::board::{cell_name, Board};
+
+// why the solver knows something
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Reason {
+    // a number already touches as many known mines as it shows, so the rest of its covered neighbors are safe
+    Satisfied,
+    // a number has exactly as many unknown neighbors as it still needs mines
+    Filled,
+    // the unknown neighbors of one number are all neighbors of another, and the difference between them decides the rest
+    Subset,
+}
+
+// one thing the solver worked out from what's on the board
+#[derive(Clone, PartialEq, Debug)]
+pub struct Deduction {
+    pub reason: Reason,
+    // the numbers the deduction was made from
+    pub from: Vec<(usize, usize)>,
+    pub safe: Vec<(usize, usize)>,
+    pub mines: Vec<(usize, usize)>,
+}
+
+impl Deduction {
+    // a sentence explaining the deduction and what came of it
+    pub fn describe(&self, board: &Board) -> String {
+        let names = |cells: &[(usize, usize)]| {
+            cells
+                .iter()
+                .map(|&(x, y)| cell_name(x, y))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let result = match self.safe.is_empty() {
+            false => format!("{} safe", names(&self.safe)),
+            true => format!("{} mined", names(&self.mines)),
+        };
+
+        match self.reason {
+            Reason::Subset => format!("{}, which leaves {result}", self.why(board)),
+            _ => format!("{}, so {result}", self.why(board)),
+        }
+    }
+
+    // only what the deduction was made from, for when what came of it has already been said
+    pub fn why(&self, board: &Board) -> String {
+        let number = |&(x, y): &(usize, usize)| {
+            format!("the {} at {}", board.adjacent_mines(x, y), cell_name(x, y))
+        };
+
+        match self.reason {
+            Reason::Satisfied => format!("{} already has all its mines", number(&self.from[0])),
+            Reason::Filled => format!("{} needs every cell it touches", number(&self.from[0])),
+            Reason::Subset => format!(
+                "{} and {} share covered cells",
+                number(&self.from[0]),
+                number(&self.from[1]),
+            ),
+        }
+    }
+}
+
+// works out everything that can be proven from the numbers on the board, only ever looking at what the player can see
+#[derive(Debug)]
+pub struct Solver<'a> {
+    board: &'a Board,
+    // covered cells proven to be mines and proven to be safe, by index
+    mines: BTreeSet<usize>,
+    safe: BTreeSet<usize>,
+    deductions: Vec<Deduction>,
+}
+
+// a number on the board and what it says about the cells around it that we don't know yet
+struct Constraint {
+    cell: (usize, usize),
+    unknown: BTreeSet<usize>,
+    // how many of the unknown cells are mines
+    mines: usize,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(board: &'a Board) -> Self {
+        let mut solver = Self {
+            board,
+            mines: BTreeSet::new(),
+            safe: BTreeSet::new(),
+            deductions: Vec::new(),
+        };
+
+        while let Some(deduction) = solver.next_deduction() {
+            for &(x, y) in &deduction.safe {
+                solver.safe.insert(board.index(x, y));
+            }
+
+            for &(x, y) in &deduction.mines {
+                solver.mines.insert(board.index(x, y));
+            }
+
+            solver.deductions.push(deduction);
+        }
+
+        solver
+    }
+
+    pub fn is_safe(&self, x: usize, y: usize) -> bool {
+        self.safe.contains(&self.board.index(x, y))
+    }
+
+    pub fn is_mine(&self, x: usize, y: usize) -> bool {
+        self.mines.contains(&self.board.index(x, y))
+    }
+
+    // every covered cell that's proven safe
+    pub fn safe_cells(&self) -> Vec<(usize, usize)> {
+        self.safe.iter().map(|&i| self.board.position(i)).collect()
+    }
+
+    // the deduction that proved (x, y) safe or mined
+    pub fn deduction_for(&self, x: usize, y: usize) -> Option<&Deduction> {
+        self.deductions
+            .iter()
+            .find(|deduction| deduction.safe.contains(&(x, y)) || deduction.mines.contains(&(x, y)))
+    }
+
+    // the covered cell least likely to be a mine out of the ones that haven't been proven either way, and its odds
+    pub fn best_guess(&self) -> Option<((usize, usize), f64)> {
+        let constraints = self.constraints();
+        let density = self.density();
+
+        self.unknown_cells()
+            .into_iter()
+            .map(|(x, y)| {
+                (
+                    (x, y),
+                    self.probability_with(self.board.index(x, y), &constraints, density),
+                )
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // an estimate of the chance a covered cell is a mine.
+    // cells next to numbers take the worst odds any of their numbers give them,
+    // everything else gets the mine density of the rest of the board
+    pub fn probability(&self, x: usize, y: usize) -> f64 {
+        self.probability_with(self.board.index(x, y), &self.constraints(), self.density())
+    }
+
+    fn probability_with(&self, i: usize, constraints: &[Constraint], density: f64) -> f64 {
+        let (x, y) = self.board.position(i);
+
+        if self.mines.contains(&i) {
+            return 1.0;
+        } else if self.safe.contains(&i) || self.board.is_uncovered(x, y) {
+            return 0.0;
+        }
+
+        constraints
+            .iter()
+            .filter(|constraint| constraint.unknown.contains(&i))
+            .map(|constraint| constraint.mines as f64 / constraint.unknown.len() as f64)
+            .reduce(f64::max)
+            .unwrap_or(density)
+    }
+
+    // mines left per covered cell we don't know anything about
+    fn density(&self) -> f64 {
+        let unknown = self.unknown_cells().len();
+        let mines = self.board.num_mines().saturating_sub(self.mines.len());
+
+        match unknown {
+            0 => 0.0,
+            _ => mines as f64 / unknown as f64,
+        }
+    }
+
+    // covered cells that haven't been proven either way
+    fn unknown_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+
+        for y in 0..self.board.height() {
+            for x in 0..self.board.width() {
+                let i = self.board.index(x, y);
+
+                if !self.board.is_uncovered(x, y)
+                    && !self.mines.contains(&i)
+                    && !self.safe.contains(&i)
+                {
+                    cells.push((x, y));
+                }
+            }
+        }
+
+        cells
+    }
+
+    fn constraints(&self) -> Vec<Constraint> {
+        let board = self.board;
+        let mut constraints = Vec::new();
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                if !board.is_uncovered(x, y) || board.is_mine(x, y) || board.is_wall(x, y) {
+                    continue;
+                }
+
+                let mut unknown = BTreeSet::new();
+                let mut known_mines = 0;
+
+                for (nx, ny) in board.neighbors(x, y) {
+                    let i = board.index(nx, ny);
+
+                    // mines that went off are just as known as the ones we worked out
+                    if self.mines.contains(&i)
+                        || (board.is_uncovered(nx, ny) && board.is_mine(nx, ny))
+                    {
+                        known_mines += 1;
+                    } else if !board.is_uncovered(nx, ny) && !self.safe.contains(&i) {
+                        unknown.insert(i);
+                    }
+                }
+
+                if !unknown.is_empty() {
+                    constraints.push(Constraint {
+                        cell: (x, y),
+                        unknown,
+                        mines: board.adjacent_mines(x, y).saturating_sub(known_mines),
+                    });
+                }
+            }
+        }
+
+        constraints
+    }
+
+    fn next_deduction(&self) -> Option<Deduction> {
+        let constraints = self.constraints();
+        let cells = |set: &BTreeSet<usize>| {
+            set.iter()
+                .map(|&i| self.board.position(i))
+                .collect::<Vec<_>>()
+        };
+
+        // look at each number on its own first
+        for constraint in &constraints {
+            let (reason, safe, mines) = if constraint.mines == 0 {
+                (Reason::Satisfied, cells(&constraint.unknown), Vec::new())
+            } else if constraint.mines == constraint.unknown.len() {
+                (Reason::Filled, Vec::new(), cells(&constraint.unknown))
+            } else {
+                continue;
+            };
+
+            return Some(Deduction {
+                reason,
+                from: vec![constraint.cell],
+                safe,
+                mines,
+            });
+        }
+
+        // then pairs of numbers where one's unknown cells are all shared with the other
+        let mut touching: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+        for (n, constraint) in constraints.iter().enumerate() {
+            for &i in &constraint.unknown {
+                touching.entry(i).or_default().push(n);
+            }
+        }
+
+        for (a, small) in constraints.iter().enumerate() {
+            let others: BTreeSet<usize> = small
+                .unknown
+                .iter()
+                .flat_map(|i| touching[i].iter().copied())
+                .collect();
+
+            for b in others {
+                let large = &constraints[b];
+
+                if a == b
+                    || large.unknown.len() <= small.unknown.len()
+                    || !small.unknown.is_subset(&large.unknown)
+                {
+                    continue;
+                }
+
+                let rest: BTreeSet<usize> =
+                    large.unknown.difference(&small.unknown).copied().collect();
+                let rest_mines = large.mines.saturating_sub(small.mines);
+
+                let (safe, mines) = if large.mines == small.mines {
+                    (cells(&rest), Vec::new())
+                } else if rest_mines == rest.len() {
+                    (Vec::new(), cells(&rest))
+                } else {
+                    continue;
+                };
+
+                return Some(Deduction {
+                    reason: Reason::Subset,
+                    from: vec![small.cell, large.cell],
+                    safe,
+                    mines,
+                });
+            }
+        }
+
+        None
+    }
+}
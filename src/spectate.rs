@@ -0,0 +1,485 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufReader, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crossterm::{
+    event::{Event, KeyCode},
+    Result,
+};
+use ratatui::{
+    text::Spans,
+    widgets::{Paragraph, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, MAX_CELLS},
+    config::Config,
+    input::{self, Events},
+    net,
+    player::Seen,
+    protocol,
+    terminal::set_title,
+    theme::Tile,
+    ui::{self, MinesweeperState, MinesweeperWidget, Screen},
+};
+
+// each spectator's sent frames on a thread of their own, and one that doesn't take a frame within this long is gone
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the most spectators a game takes at once, anyone after that is turned away
+const MAX_SPECTATORS: usize = 32;
+
+// how long to wait for a key before looking for the next frame
+const TICK: Duration = Duration::from_millis(50);
+
+// the longest frame a spectator takes, with room to spare for the biggest board there can be
+const MAX_FRAME: u64 = 32 * 1024 * 1024;
+
+// the whole screen as the player sees it, sent to spectators as one line of JSON every time it's drawn
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Frame {
+    // the board from the top row down, written like the protocol writes it. only what the player can see is sent,
+    // so the mines don't show until the game's over
+    pub rows: Vec<String>,
+    pub selection: (usize, usize),
+    // the status bar and what's said under the board, as text
+    pub status: String,
+    pub footer: Vec<String>,
+}
+
+impl Frame {
+    // the board's width and height, if the rows make a board and the selection's on it
+    fn size(&self) -> Option<(usize, usize)> {
+        let height = self.rows.len();
+        let width = self.rows.first()?.chars().count();
+
+        let fits = width > 0
+            && width.saturating_mul(height) <= MAX_CELLS
+            && self.rows.iter().all(|row| row.chars().count() == width)
+            && self.selection.0 < width
+            && self.selection.1 < height;
+
+        fits.then_some((width, height))
+    }
+}
+
+// the board as a widget draws it, a row of symbols at a time from the top
+pub fn rows(widget: &MinesweeperWidget, (width, height): (usize, usize)) -> Vec<String> {
+    (0..height)
+        .rev()
+        .map(|y| (0..width).map(|x| symbol(widget.tile(x, y))).collect())
+        .collect()
+}
+
+fn symbol(tile: Tile) -> char {
+    protocol::symbol(match tile {
+        Tile::Empty => Seen::Number(0),
+        Tile::Number(mines) => Seen::Number(mines),
+        Tile::Covered => Seen::Covered,
+        Tile::Marked => Seen::Flagged,
+        Tile::Mine => Seen::Mine,
+        Tile::Wall => Seen::Wall,
+    })
+}
+
+fn tile(symbol: char) -> Tile {
+    match symbol {
+        '0' => Tile::Empty,
+        'f' => Tile::Marked,
+        '*' | 'x' => Tile::Mine,
+        '#' => Tile::Wall,
+        _ => match symbol.to_digit(10) {
+            Some(mines) => Tile::Number(mines as usize),
+            None => Tile::Covered,
+        },
+    }
+}
+
+// the newest frame a spectator hasn't been sent yet. their thread only ever sends the newest, so one that's fallen
+// behind skips the frames in between instead of them piling up
+#[derive(Default)]
+struct Mailbox {
+    frame: Mutex<(Option<Arc<String>>, bool)>,
+    arrived: Condvar,
+}
+
+// a spectator, sent frames by a thread of their own so the game never waits on them
+struct Viewer {
+    mailbox: Arc<Mailbox>,
+}
+
+impl Viewer {
+    fn new(mut stream: Box<dyn Write + Send>) -> Self {
+        let mailbox = Arc::new(Mailbox::default());
+        let sending = mailbox.clone();
+
+        thread::spawn(move || loop {
+            let frame = {
+                let mut waiting = sending.frame.lock().unwrap();
+
+                while waiting.0.is_none() && !waiting.1 {
+                    waiting = sending.arrived.wait(waiting).unwrap();
+                }
+
+                match waiting.0.take() {
+                    Some(frame) => frame,
+                    None => return,
+                }
+            };
+
+            if stream.write_all(frame.as_bytes()).is_err() {
+                sending.frame.lock().unwrap().1 = true;
+                return;
+            }
+        });
+
+        Self { mailbox }
+    }
+
+    // hand them a frame, returns whether they're still watching
+    fn give(&self, frame: Arc<String>) -> bool {
+        let mut waiting = self.mailbox.frame.lock().unwrap();
+
+        if !waiting.1 {
+            waiting.0 = Some(frame);
+            self.mailbox.arrived.notify_one();
+        }
+
+        !waiting.1
+    }
+}
+
+// their thread's done once nobody's giving it frames any more
+impl Drop for Viewer {
+    fn drop(&mut self) {
+        self.mailbox.frame.lock().unwrap().1 = true;
+        self.mailbox.arrived.notify_one();
+    }
+}
+
+// the spectators watching a game, and the last frame for whoever turns up next
+#[derive(Default)]
+struct Audience {
+    viewers: Vec<Viewer>,
+    last: Option<Arc<String>>,
+}
+
+impl Audience {
+    fn add(&mut self, stream: Box<dyn Write + Send>) {
+        if self.viewers.len() >= MAX_SPECTATORS {
+            return;
+        }
+
+        let viewer = Viewer::new(stream);
+
+        if self.last.clone().is_none_or(|last| viewer.give(last)) {
+            self.viewers.push(viewer);
+        }
+    }
+}
+
+// lets anyone connect and watch. nothing they send is read, so there's no way for them to play
+pub struct Spectators {
+    audience: Arc<Mutex<Audience>>,
+    // the local socket, which is cleaned up when the game's done with it
+    path: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for Spectators {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Spectators").finish_non_exhaustive()
+    }
+}
+
+// take spectators on `address` from now on: a port number or address for TCP, or a path with a / in it for a local socket
+pub fn serve(address: &str) -> io::Result<Spectators> {
+    let audience = Arc::new(Mutex::new(Audience::default()));
+    let joining = audience.clone();
+
+    if is_path(address) {
+        let listener = bind_local(address)?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                    joining.lock().unwrap().add(Box::new(stream));
+                }
+            }
+        });
+    } else {
+        let listener = match address.parse::<u16>() {
+            Ok(port) => TcpListener::bind(("0.0.0.0", port))?,
+            Err(_) => TcpListener::bind(address)?,
+        };
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                    joining.lock().unwrap().add(Box::new(stream));
+                }
+            }
+        });
+    }
+
+    Ok(Spectators {
+        audience,
+        path: is_path(address).then(|| PathBuf::from(address)),
+    })
+}
+
+fn is_path(address: &str) -> bool {
+    address.contains('/')
+}
+
+#[cfg(unix)]
+fn bind_local(path: &str) -> io::Result<UnixListener> {
+    UnixListener::bind(path)
+}
+
+#[cfg(not(unix))]
+fn bind_local(_path: &str) -> io::Result<TcpListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "local sockets only work on unix, give a port instead",
+    ))
+}
+
+impl Spectators {
+    // show everyone watching the latest frame. anyone who's gone stops watching
+    pub fn send(&self, frame: &Frame) {
+        let mut line = serde_json::to_string(frame).unwrap_or_default();
+        line.push('\n');
+        let line = Arc::new(line);
+
+        let mut audience = self.audience.lock().unwrap();
+        audience.viewers.retain(|viewer| viewer.give(line.clone()));
+        audience.last = Some(line);
+    }
+}
+
+impl Drop for Spectators {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// a line of the screen without its colors
+pub fn plain(spans: &Spans) -> String {
+    spans.0.iter().map(|span| span.content.as_ref()).collect()
+}
+
+// watching someone else's game, drawn the same way they see it
+#[derive(Debug)]
+pub struct Spectator {
+    screen: Screen,
+    config: Config,
+    events: Events,
+    frames: Receiver<Frame>,
+    frame: Option<Frame>,
+    // whether the game's still sending frames
+    live: bool,
+}
+
+impl Spectator {
+    // connect to a game that's taking spectators, the same kind of address it was given
+    pub fn connect(address: &str, config: Config, events: Events) -> Result<Self> {
+        let stream = connect(address)?;
+        let (sender, frames) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in net::lines(BufReader::new(stream), MAX_FRAME) {
+                let frame = match serde_json::from_str::<Frame>(&line).ok() {
+                    Some(frame) if frame.size().is_some() => frame,
+                    _ => break,
+                };
+
+                if sender.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut screen = Screen::new()?;
+
+        if config.set_title {
+            set_title(screen.out(), "Minesweeper - Spectating")?;
+        }
+
+        Ok(Self {
+            screen,
+            config,
+            events,
+            frames,
+            frame: None,
+            live: true,
+        })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.draw()?;
+
+        loop {
+            if self.events.poll(TICK)? {
+                if let Event::Key(key) = self.events.read()? {
+                    if input::is_interrupt(&key) || key.code == KeyCode::Char('q') {
+                        return Ok(());
+                    }
+                }
+
+                continue;
+            }
+
+            // only the newest frame matters, any before it have already been drawn over
+            let mut changed = false;
+
+            loop {
+                match self.frames.try_recv() {
+                    Ok(frame) => self.frame = Some(frame),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        changed |= self.live;
+                        self.live = false;
+                        break;
+                    }
+                }
+
+                changed = true;
+            }
+
+            if changed {
+                self.draw()?;
+            }
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let theme = &self.config.theme;
+        let top = self.config.safe_area().top;
+        let watching = match self.live {
+            true => "spectating, q stops watching",
+            false => "the game's over or it stopped taking spectators, q quits",
+        };
+
+        let frame = match &self.frame {
+            Some(frame) => frame,
+            None => {
+                let waiting = match self.live {
+                    true => "waiting for the game to draw something, q stops watching",
+                    false => watching,
+                };
+                return self
+                    .screen
+                    .page(top, vec![Spans::from(ui::styled(waiting, &theme.overlay))]);
+            }
+        };
+
+        // the frames have already been checked, so this always has a size
+        let (width, height) = frame.size().unwrap_or((1, 1));
+        let blank = Board::new(width, height);
+        let tiles: HashMap<_, _> = frame
+            .rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars().enumerate().map(move |(x, symbol)| {
+                    let glyph = theme.board.glyph(tile(symbol));
+                    ((x, height - 1 - row), ui::span(glyph))
+                })
+            })
+            .collect();
+
+        // the cells are drawn from the rows, the board underneath is only there for the size
+        let widget = MinesweeperWidget::new(&blank, theme).drawn_over(&tiles);
+        let rows = widget.rows();
+        let mut state = MinesweeperState::new(frame.selection);
+
+        let mut lines = vec![Spans::from(ui::styled(
+            frame.status.clone(),
+            &theme.status_bar,
+        ))];
+        lines.extend(frame.footer.iter().map(|line| Spans::from(line.clone())));
+        lines.push(Spans::from(ui::styled(watching, &theme.overlay)));
+
+        self.screen.draw(|screen| {
+            let area = screen.size();
+
+            screen.render_stateful_widget(widget, ui::below(area, top), &mut state);
+            screen.render_widget(
+                Paragraph::new(lines).wrap(Wrap { trim: false }),
+                ui::below(area, top + rows),
+            );
+
+            if let Some((column, row)) = state
+                .cursor()
+                .filter(|&(column, row)| column < area.right() && row < area.bottom())
+            {
+                screen.set_cursor(column, row);
+            }
+        })
+    }
+}
+
+fn connect(address: &str) -> io::Result<Box<dyn Read + Send>> {
+    match is_path(address) {
+        true => connect_local(address),
+        false => Ok(Box::new(net::connect(address)?)),
+    }
+}
+
+#[cfg(unix)]
+fn connect_local(path: &str) -> io::Result<Box<dyn Read + Send>> {
+    Ok(Box::new(UnixStream::connect(path)?))
+}
+
+#[cfg(not(unix))]
+fn connect_local(_path: &str) -> io::Result<Box<dyn Read + Send>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "local sockets only work on unix, give an address instead",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn spectators_only_see_what_the_player_does() {
+        let mut board = Board::with_mines(3, 1, &[0]);
+        board.uncover(2, 0);
+        let theme = Theme::default();
+
+        let playing = MinesweeperWidget::new(&board, &theme);
+        assert_eq!(rows(&playing, (3, 1)), ["-10"]);
+
+        let over = MinesweeperWidget::new(&board, &theme).show_everything(true);
+        assert_eq!(rows(&over, (3, 1)), ["*10"]);
+
+        let frame = |rows: &[&str], selection| Frame {
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+            selection,
+            status: String::new(),
+            footer: Vec::new(),
+        };
+        assert_eq!(frame(&["-10", "---"], (2, 1)).size(), Some((3, 2)));
+        assert_eq!(frame(&["-10", "--"], (0, 0)).size(), None);
+        assert_eq!(frame(&["-10"], (0, 5)).size(), None);
+    }
+}
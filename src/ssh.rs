@@ -0,0 +1,1163 @@
+use std::path::Path;
+
+// the port serve-ssh listens on unless it's told otherwise
+pub const PORT: u16 = 2222;
+
+// how many can be connected at once unless it's told otherwise. every one of them is a game running on the server
+pub const MAX_PLAYERS: usize = 16;
+
+// let people play over SSH, if this was built with the ssh feature. every connection gets a game of its own in a
+// terminal of its own, started with `args`, and nobody's asked for a password or a key to get in. the name someone
+// connects as picks their profile if it can be one, so everyone's stats and saves are their own
+#[cfg(feature = "ssh")]
+pub fn serve(
+    port: u16,
+    host_key: Option<&Path>,
+    max_players: usize,
+    args: Vec<String>,
+) -> Result<(), String> {
+    server::serve(port, host_key, max_players, args)
+}
+
+#[cfg(not(feature = "ssh"))]
+pub fn serve(
+    _port: u16,
+    _host_key: Option<&Path>,
+    _max_players: usize,
+    _args: Vec<String>,
+) -> Result<(), String> {
+    Err("this copy of minesweeper was built without the ssh feature".to_string())
+}
+
+// just enough of SSH (RFC 4253, 4252 and 4254) for a terminal session: curve25519-sha256 to agree on keys, an
+// ssh-ed25519 host key, aes256-ctr with hmac-sha2-256 on the wire and one session channel with a pty. OpenSSH has
+// supported all of it for years. anything else is turned down, and so is a second key exchange part way through
+#[cfg(feature = "ssh")]
+mod server {
+    use std::{
+        env, fmt,
+        fs::{self, OpenOptions},
+        io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+        net::{Shutdown, TcpListener, TcpStream},
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Condvar, Mutex,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use aes::{
+        cipher::{KeyIvInit, StreamCipher},
+        Aes256,
+    };
+    use ed25519_dalek::{Signer, SigningKey};
+    use hmac::{Hmac, Mac};
+    use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+    use rand::{rngs::OsRng, RngCore};
+    use sha2::{Digest, Sha256};
+    use x25519_dalek::{EphemeralSecret, PublicKey};
+
+    use crate::paths;
+
+    type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+    type HmacSha256 = Hmac<Sha256>;
+
+    const VERSION: &str = concat!("SSH-2.0-minesweeper_", env!("CARGO_PKG_VERSION"));
+
+    // where the host key's kept when --host-key doesn't say, in the data directory
+    const HOST_KEY: &str = "ssh_host_ed25519_key";
+
+    // the longest packet a client can send. the biggest thing a terminal session sends is the client's list of
+    // algorithms, a few kilobytes at most
+    const MAX_PACKET: usize = 64 * 1024;
+
+    // how much typing the client can send before it has to wait for us to say there's room for more, and the most
+    // it can put in one packet
+    const WINDOW: u32 = 1024 * 1024;
+    const MAX_DATA: u32 = 32 * 1024;
+
+    // the most output that goes in one packet, however much the client would take
+    const MAX_OUTPUT: usize = 16 * 1024;
+
+    // someone who connects and doesn't get as far as a game is let go after this
+    const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    // a client that stops reading gets this long before it's dropped, rather than holding its game up for good
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    const MAC_LENGTH: usize = 32;
+
+    // the message numbers, from RFC 4250
+    const DISCONNECT: u8 = 1;
+    const IGNORE: u8 = 2;
+    const UNIMPLEMENTED: u8 = 3;
+    const DEBUG: u8 = 4;
+    const SERVICE_REQUEST: u8 = 5;
+    const SERVICE_ACCEPT: u8 = 6;
+    const KEXINIT: u8 = 20;
+    const NEWKEYS: u8 = 21;
+    const KEX_ECDH_INIT: u8 = 30;
+    const KEX_ECDH_REPLY: u8 = 31;
+    const USERAUTH_REQUEST: u8 = 50;
+    const USERAUTH_SUCCESS: u8 = 52;
+    const GLOBAL_REQUEST: u8 = 80;
+    const REQUEST_FAILURE: u8 = 82;
+    const CHANNEL_OPEN: u8 = 90;
+    const CHANNEL_OPEN_CONFIRMATION: u8 = 91;
+    const CHANNEL_OPEN_FAILURE: u8 = 92;
+    const CHANNEL_WINDOW_ADJUST: u8 = 93;
+    const CHANNEL_DATA: u8 = 94;
+    const CHANNEL_EOF: u8 = 96;
+    const CHANNEL_CLOSE: u8 = 97;
+    const CHANNEL_REQUEST: u8 = 98;
+    const CHANNEL_SUCCESS: u8 = 99;
+    const CHANNEL_FAILURE: u8 = 100;
+
+    // why a connection's being dropped, also from RFC 4250
+    const PROTOCOL_ERROR: u32 = 2;
+    const KEY_EXCHANGE_FAILED: u32 = 3;
+    const SERVICE_NOT_AVAILABLE: u32 = 7;
+    const TOO_MANY_CONNECTIONS: u32 = 12;
+
+    // what's on offer. there's one of each, so agreeing on them is only checking the client has them too
+    const KEX: [&str; 2] = ["curve25519-sha256", "curve25519-sha256@libssh.org"];
+    const HOST_KEY_ALGORITHM: &str = "ssh-ed25519";
+    const CIPHER: &str = "aes256-ctr";
+    const MAC: &str = "hmac-sha2-256";
+
+    // both ends offering these means sequence numbers start again after each key exchange and nothing else can be
+    // slipped in during it, which is OpenSSH's fix for the Terrapin attack
+    const STRICT_CLIENT: &str = "kex-strict-c-v00@openssh.com";
+    const STRICT_SERVER: &str = "kex-strict-s-v00@openssh.com";
+
+    pub fn serve(
+        port: u16,
+        host_key: Option<&Path>,
+        max_players: usize,
+        args: Vec<String>,
+    ) -> Result<(), String> {
+        let path = match host_key {
+            Some(path) => path.to_path_buf(),
+            None => paths::data_dir()
+                .ok_or("couldn't find a data directory to keep the host key in, pass --host-key")?
+                .join(HOST_KEY),
+        };
+        let host_key =
+            load_host_key(&path).map_err(|error| format!("{}: {error}", path.display()))?;
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|error| error.to_string())?;
+
+        println!(
+            "Serving minesweeper over SSH, play with: ssh -p {port} <this computer's address>"
+        );
+        println!("The host key's fingerprint is {}", fingerprint(&host_key));
+
+        let host_key = Arc::new(host_key);
+        let args = Arc::new(args);
+        let connected = Arc::new(AtomicUsize::new(0));
+
+        for stream in listener.incoming().flatten() {
+            let (host_key, args, connected) = (host_key.clone(), args.clone(), connected.clone());
+
+            thread::spawn(move || {
+                let address = stream
+                    .peer_addr()
+                    .map_or("someone".to_string(), |address| address.to_string());
+
+                if connected.fetch_add(1, Ordering::SeqCst) >= max_players {
+                    let _ = refuse(stream);
+                } else {
+                    match connect(stream, &host_key, &args) {
+                        Ok(Some(user)) => println!("{address} played as {user}"),
+                        Ok(None) => {}
+                        Err(error) => println!("{address} was dropped: {error}"),
+                    }
+                }
+
+                connected.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+
+        Ok(())
+    }
+
+    // the server's key, made the first time it's needed and kept so players see the same one every time. it's the
+    // key's 32 bytes in hex, readable only by whoever runs the server
+    fn load_host_key(path: &Path) -> io::Result<SigningKey> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let text = text.trim();
+                let seed: Option<Vec<u8>> = (text.len() == 64 && text.is_ascii())
+                    .then(|| {
+                        (0..32)
+                            .map(|i| u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok())
+                            .collect()
+                    })
+                    .flatten();
+
+                match seed.and_then(|seed| <[u8; 32]>::try_from(seed).ok()) {
+                    Some(seed) => Ok(SigningKey::from_bytes(&seed)),
+                    None => Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "that isn't a host key, it should be 64 hex digits",
+                    )),
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                let mut seed = [0; 32];
+                OsRng.fill_bytes(&mut seed);
+
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                let mut options = OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+                let hex: String = seed.iter().map(|byte| format!("{byte:02x}")).collect();
+                writeln!(options.open(path)?, "{hex}")?;
+                Ok(SigningKey::from_bytes(&seed))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // the host key the way it's sent to clients
+    fn host_key_blob(host_key: &SigningKey) -> Vec<u8> {
+        Message::empty()
+            .string(HOST_KEY_ALGORITHM.as_bytes())
+            .string(host_key.verifying_key().as_bytes())
+            .0
+    }
+
+    // the key's fingerprint the way ssh shows it the first time it connects, so the two can be checked against
+    // each other
+    fn fingerprint(host_key: &SigningKey) -> String {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let digest = Sha256::digest(host_key_blob(host_key));
+        let mut text = "SHA256:".to_string();
+
+        // base64 without the padding
+        for chunk in digest.chunks(3) {
+            let bits = chunk
+                .iter()
+                .fold(0u32, |bits, &byte| (bits << 8) | byte as u32)
+                << (8 * (3 - chunk.len()));
+
+            for i in 0..=chunk.len() {
+                text.push(ALPHABET[((bits >> (18 - 6 * i)) & 63) as usize] as char);
+            }
+        }
+
+        text
+    }
+
+    // there's no room, so they're told that before anything else happens. what they've sent is read until they hang
+    // up, since closing with it still unread throws away what we sent before they see it
+    fn refuse(mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        stream.write_all(format!("{VERSION}\r\n").as_bytes())?;
+        stream.write_all(
+            &Direction::default().seal(
+                &disconnect(
+                    TOO_MANY_CONNECTIONS,
+                    "too many people are playing, try again later",
+                )
+                .0,
+            ),
+        )?;
+        stream.shutdown(Shutdown::Write)?;
+
+        io::copy(&mut stream.take(MAX_PACKET as u64), &mut io::sink()).map(|_| ())
+    }
+
+    // one connection from start to finish, with who they played as if they got as far as logging in
+    fn connect(
+        stream: TcpStream,
+        host_key: &SigningKey,
+        args: &[String],
+    ) -> io::Result<Option<String>> {
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+        let mut connection = Connection::handshake(stream.try_clone()?, host_key)?;
+        let result = connection.serve(args);
+
+        // whatever ended it, the game goes with it
+        drop(connection);
+        let _ = stream.shutdown(Shutdown::Both);
+        result
+    }
+
+    fn disconnect(reason: u32, description: &str) -> Message {
+        Message::new(DISCONNECT)
+            .uint(reason)
+            .string(description.as_bytes())
+            .string(b"")
+    }
+
+    fn malformed() -> io::Error {
+        io::Error::new(ErrorKind::InvalidData, "a malformed packet")
+    }
+
+    // a message being put together, its fields go on the end in order
+    #[derive(Clone, Debug)]
+    struct Message(Vec<u8>);
+
+    impl Message {
+        fn new(kind: u8) -> Self {
+            Self(vec![kind])
+        }
+
+        // fields with no message number in front, for what gets hashed or signed
+        fn empty() -> Self {
+            Self(Vec::new())
+        }
+
+        fn bytes(mut self, bytes: &[u8]) -> Self {
+            self.0.extend_from_slice(bytes);
+            self
+        }
+
+        fn boolean(self, value: bool) -> Self {
+            self.bytes(&[value as u8])
+        }
+
+        fn uint(self, value: u32) -> Self {
+            self.bytes(&value.to_be_bytes())
+        }
+
+        fn string(self, bytes: &[u8]) -> Self {
+            self.uint(bytes.len() as u32).bytes(bytes)
+        }
+
+        // a positive number, most significant byte first, without leading zeros but with a zero in front if the top
+        // bit's set so it doesn't read as negative
+        fn mpint(self, number: &[u8]) -> Self {
+            let start = number.iter().position(|&byte| byte != 0);
+            let number = start.map_or(&[][..], |start| &number[start..]);
+
+            match number.first() {
+                Some(byte) if byte & 0x80 != 0 => {
+                    self.uint(number.len() as u32 + 1).bytes(&[0]).bytes(number)
+                }
+                _ => self.string(number),
+            }
+        }
+    }
+
+    // the fields of a message that's come in, read off the front one at a time
+    struct Fields<'a>(&'a [u8]);
+
+    impl<'a> Fields<'a> {
+        fn bytes(&mut self, length: usize) -> io::Result<&'a [u8]> {
+            if self.0.len() < length {
+                return Err(malformed());
+            }
+
+            let (bytes, rest) = self.0.split_at(length);
+            self.0 = rest;
+            Ok(bytes)
+        }
+
+        fn boolean(&mut self) -> io::Result<bool> {
+            Ok(self.bytes(1)?[0] != 0)
+        }
+
+        fn uint(&mut self) -> io::Result<u32> {
+            let bytes = self.bytes(4)?;
+            Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        }
+
+        fn string(&mut self) -> io::Result<&'a [u8]> {
+            let length = self.uint()? as usize;
+            self.bytes(length)
+        }
+
+        fn text(&mut self) -> io::Result<String> {
+            Ok(String::from_utf8_lossy(self.string()?).into_owned())
+        }
+
+        // a comma separated list of algorithm names
+        fn names(&mut self) -> io::Result<Vec<String>> {
+            let text = self.text()?;
+            Ok(text.split(',').map(String::from).collect())
+        }
+    }
+
+    // one way's keys, made from the key exchange
+    struct Keys {
+        cipher: Aes256Ctr,
+        mac: [u8; 32],
+    }
+
+    impl Keys {
+        fn new(key: [u8; 32], iv: [u8; 32], mac: [u8; 32]) -> Self {
+            Self {
+                cipher: Aes256Ctr::new(&key.into(), iv[..16].into()),
+                mac,
+            }
+        }
+
+        // the MAC goes over the packet before it's encrypted, with its sequence number in front
+        fn mac(&self, sequence: u32, packet: &[u8]) -> HmacSha256 {
+            let mut mac =
+                HmacSha256::new_from_slice(&self.mac).expect("HMAC takes keys of any length");
+            mac.update(&sequence.to_be_bytes());
+            mac.update(packet);
+            mac
+        }
+    }
+
+    // everything about packets going one way: the keys, once there are some, and how many have gone, which goes
+    // into every MAC so packets can't be dropped or replayed
+    #[derive(Default)]
+    struct Direction {
+        keys: Option<Keys>,
+        sequence: u32,
+    }
+
+    impl Direction {
+        // packets are padded out to the cipher's block size, or 8 bytes before there's a cipher
+        fn block(&self) -> usize {
+            match self.keys {
+                Some(_) => 16,
+                None => 8,
+            }
+        }
+
+        fn seal(&mut self, payload: &[u8]) -> Vec<u8> {
+            let block = self.block();
+            let mut padding = block - (5 + payload.len()) % block;
+            if padding < 4 {
+                padding += block;
+            }
+
+            let length = 1 + payload.len() + padding;
+            let mut packet = Vec::with_capacity(4 + length + MAC_LENGTH);
+            packet.extend_from_slice(&(length as u32).to_be_bytes());
+            packet.push(padding as u8);
+            packet.extend_from_slice(payload);
+
+            let mut random = vec![0; padding];
+            OsRng.fill_bytes(&mut random);
+            packet.extend_from_slice(&random);
+
+            if let Some(keys) = &mut self.keys {
+                let mac = keys.mac(self.sequence, &packet).finalize().into_bytes();
+                keys.cipher.apply_keystream(&mut packet);
+                packet.extend_from_slice(&mac);
+            }
+
+            self.sequence = self.sequence.wrapping_add(1);
+            packet
+        }
+
+        // the next packet's payload. the first block's read on its own to find out how long the packet is, and
+        // anything too long or that doesn't add up is refused before the rest is read
+        fn open(&mut self, reader: &mut impl Read) -> io::Result<Vec<u8>> {
+            let block = self.block();
+            let mut packet = vec![0; block];
+            reader.read_exact(&mut packet)?;
+
+            if let Some(keys) = &mut self.keys {
+                keys.cipher.apply_keystream(&mut packet);
+            }
+
+            let length = u32::from_be_bytes([packet[0], packet[1], packet[2], packet[3]]) as usize;
+            if length > MAX_PACKET || length + 4 < block || !(length + 4).is_multiple_of(block) {
+                return Err(malformed());
+            }
+
+            packet.resize(4 + length, 0);
+            reader.read_exact(&mut packet[block..])?;
+
+            if let Some(keys) = &mut self.keys {
+                keys.cipher.apply_keystream(&mut packet[block..]);
+
+                let mut mac = [0; MAC_LENGTH];
+                reader.read_exact(&mut mac)?;
+                keys.mac(self.sequence, &packet)
+                    .verify_slice(&mac)
+                    .map_err(|_| {
+                        io::Error::new(ErrorKind::InvalidData, "a packet's MAC was wrong")
+                    })?;
+            }
+
+            self.sequence = self.sequence.wrapping_add(1);
+
+            let padding = packet[4] as usize;
+            if padding < 4 || padding + 1 >= length {
+                return Err(malformed());
+            }
+
+            Ok(packet[5..4 + length - padding].to_vec())
+        }
+    }
+
+    // the sending half of a connection, shared with the thread passing the game's output on
+    struct Sender {
+        stream: TcpStream,
+        outgoing: Direction,
+    }
+
+    impl Sender {
+        fn send(&mut self, message: Message) -> io::Result<()> {
+            let packet = self.outgoing.seal(&message.0);
+            self.stream.write_all(&packet)
+        }
+    }
+
+    // how much more output the client's said it'll take. the game's output waits for room when there isn't any
+    struct Window {
+        // None once the connection's gone
+        left: Mutex<Option<u32>>,
+        room: Condvar,
+    }
+
+    impl Window {
+        fn new(left: u32) -> Self {
+            Self {
+                left: Mutex::new(Some(left)),
+                room: Condvar::new(),
+            }
+        }
+
+        // up to `wanted` bytes of room, waiting until there's some. None if the connection's gone
+        fn take(&self, wanted: usize) -> Option<usize> {
+            let left = self.left.lock().unwrap();
+            let mut left = self.room.wait_while(left, |left| *left == Some(0)).unwrap();
+            let left = left.as_mut()?;
+
+            let taken = wanted.min(*left as usize);
+            *left -= taken as u32;
+            Some(taken)
+        }
+
+        fn add(&self, room: u32) {
+            if let Some(left) = &mut *self.left.lock().unwrap() {
+                *left = left.saturating_add(room);
+            }
+
+            self.room.notify_all();
+        }
+
+        fn close(&self) {
+            *self.left.lock().unwrap() = None;
+            self.room.notify_all();
+        }
+    }
+
+    // the one session a connection gets, and the game once it's been started in it
+    struct Channel {
+        // the client's number for it, ours is always 0
+        theirs: u32,
+        window: Arc<Window>,
+        max_packet: usize,
+        // how much more typing we've said we'll take
+        room: u32,
+        term: String,
+        size: PtySize,
+        game: Option<Game>,
+        // whether we've said the channel's closed, which only happens once
+        closed: Arc<AtomicBool>,
+    }
+
+    struct Game {
+        master: Box<dyn MasterPty + Send>,
+        input: Box<dyn Write + Send>,
+        killer: Box<dyn ChildKiller + Send + Sync>,
+    }
+
+    impl Drop for Channel {
+        fn drop(&mut self) {
+            self.window.close();
+
+            if let Some(game) = &mut self.game {
+                let _ = game.killer.kill();
+            }
+        }
+    }
+
+    struct Connection {
+        reader: BufReader<TcpStream>,
+        incoming: Direction,
+        sender: Arc<Mutex<Sender>>,
+        // whether both ends are doing the strict key exchange
+        strict: bool,
+    }
+
+    impl Connection {
+        // swap versions and agree on keys, everything after this is encrypted
+        fn handshake(stream: TcpStream, host_key: &SigningKey) -> io::Result<Self> {
+            let mut sender = Sender {
+                stream: stream.try_clone()?,
+                outgoing: Direction::default(),
+            };
+            sender
+                .stream
+                .write_all(format!("{VERSION}\r\n").as_bytes())?;
+
+            // the version line's at most 255 characters, RFC 4253 section 4.2
+            let mut reader = BufReader::new(stream);
+            let mut version = Vec::new();
+            (&mut reader).take(255).read_until(b'\n', &mut version)?;
+            while version
+                .last()
+                .is_some_and(|&byte| byte == b'\n' || byte == b'\r')
+            {
+                version.pop();
+            }
+
+            let mut connection = Self {
+                reader,
+                incoming: Direction::default(),
+                sender: Arc::new(Mutex::new(sender)),
+                strict: false,
+            };
+
+            if !version.starts_with(b"SSH-2.0-") {
+                return Err(connection.disconnect(PROTOCOL_ERROR, "that isn't an SSH 2 client"));
+            }
+
+            let ours = kexinit();
+            connection.send(ours.clone())?;
+
+            let theirs = connection.read()?;
+            if theirs[0] != KEXINIT {
+                return Err(
+                    connection.disconnect(PROTOCOL_ERROR, "the key exchange has to come first")
+                );
+            }
+
+            let mut fields = Fields(&theirs[1..]);
+            fields.bytes(16)?;
+            let kex = fields.names()?;
+            let host_keys = fields.names()?;
+            let lists: Vec<Vec<String>> =
+                (0..6).map(|_| fields.names()).collect::<io::Result<_>>()?;
+            let guessed = fields.boolean()?;
+
+            let offered = |list: &[String], name: &str| list.iter().any(|offered| offered == name);
+            let agreed = KEX.iter().any(|name| offered(&kex, name))
+                && offered(&host_keys, HOST_KEY_ALGORITHM)
+                && offered(&lists[0], CIPHER)
+                && offered(&lists[1], CIPHER)
+                && offered(&lists[2], MAC)
+                && offered(&lists[3], MAC)
+                && offered(&lists[4], "none")
+                && offered(&lists[5], "none");
+
+            if !agreed {
+                return Err(connection.disconnect(
+                    KEY_EXCHANGE_FAILED,
+                    "this server only does curve25519-sha256, ssh-ed25519, aes256-ctr and hmac-sha2-256",
+                ));
+            }
+
+            connection.strict = offered(&kex, STRICT_CLIENT);
+
+            // a client that guessed what we'd agree on sends its half straight away, which is thrown out if it
+            // guessed wrong
+            let right = kex.first().is_some_and(|name| KEX.contains(&name.as_str()))
+                && host_keys
+                    .first()
+                    .is_some_and(|name| name == HOST_KEY_ALGORITHM);
+            if guessed && !right {
+                connection.read_kex()?;
+            }
+
+            let init = connection.read_kex()?;
+            if init[0] != KEX_ECDH_INIT {
+                return Err(connection.disconnect(PROTOCOL_ERROR, "expected the client's key"));
+            }
+
+            let client_key =
+                <[u8; 32]>::try_from(Fields(&init[1..]).string()?).map_err(|_| malformed())?;
+            let secret = EphemeralSecret::random_from_rng(OsRng);
+            let server_key = PublicKey::from(&secret);
+            let shared = secret.diffie_hellman(&PublicKey::from(client_key));
+
+            // a key that makes the secret all zeros would let someone in the middle know it
+            if !shared.was_contributory() {
+                return Err(connection.disconnect(KEY_EXCHANGE_FAILED, "that key can't be used"));
+            }
+
+            let shared = Message::empty().mpint(shared.as_bytes()).0;
+            let blob = host_key_blob(host_key);
+            let hash: [u8; 32] = Sha256::digest(
+                Message::empty()
+                    .string(&version)
+                    .string(VERSION.as_bytes())
+                    .string(&theirs)
+                    .string(&ours.0)
+                    .string(&blob)
+                    .string(&client_key)
+                    .string(server_key.as_bytes())
+                    .bytes(&shared)
+                    .0,
+            )
+            .into();
+
+            let signature = Message::empty()
+                .string(HOST_KEY_ALGORITHM.as_bytes())
+                .string(&host_key.sign(&hash).to_bytes())
+                .0;
+            connection.send(
+                Message::new(KEX_ECDH_REPLY)
+                    .string(&blob)
+                    .string(server_key.as_bytes())
+                    .string(&signature),
+            )?;
+            connection.send(Message::new(NEWKEYS))?;
+
+            // every key comes from the shared secret, the exchange hash and a letter for what it's for. the hash is
+            // the session id too, since this is the only key exchange there is
+            let key = |letter: u8| -> [u8; 32] {
+                Sha256::new()
+                    .chain_update(&shared)
+                    .chain_update(hash)
+                    .chain_update([letter])
+                    .chain_update(hash)
+                    .finalize()
+                    .into()
+            };
+
+            {
+                let mut sender = connection.sender.lock().unwrap();
+                sender.outgoing.keys = Some(Keys::new(key(b'D'), key(b'B'), key(b'F')));
+                if connection.strict {
+                    sender.outgoing.sequence = 0;
+                }
+            }
+
+            if connection.read_kex()? != [NEWKEYS] {
+                return Err(connection.disconnect(PROTOCOL_ERROR, "expected the client's new keys"));
+            }
+
+            connection.incoming.keys = Some(Keys::new(key(b'C'), key(b'A'), key(b'E')));
+            if connection.strict {
+                connection.incoming.sequence = 0;
+            }
+
+            Ok(connection)
+        }
+
+        // log them in and run their game until they go, with who they played as if they got that far
+        fn serve(&mut self, args: &[String]) -> io::Result<Option<String>> {
+            let mut user: Option<String> = None;
+            let mut channel: Option<Channel> = None;
+
+            loop {
+                let message = match self.read() {
+                    Ok(message) => message,
+                    // a connection that's just gone is how most games end
+                    Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(user),
+                    Err(error) => return Err(error),
+                };
+                let mut fields = Fields(&message[1..]);
+
+                match message[0] {
+                    DISCONNECT => return Ok(user),
+                    IGNORE | DEBUG | UNIMPLEMENTED => {}
+                    KEXINIT => {
+                        return Err(self
+                            .disconnect(PROTOCOL_ERROR, "this server can't exchange keys again"))
+                    }
+                    SERVICE_REQUEST => {
+                        let service = fields.string()?;
+                        if service != b"ssh-userauth" || user.is_some() {
+                            return Err(self.disconnect(
+                                SERVICE_NOT_AVAILABLE,
+                                "only ssh-userauth is on offer",
+                            ));
+                        }
+
+                        self.send(Message::new(SERVICE_ACCEPT).string(service))?;
+                    }
+                    // anyone can play, so whatever they try to log in with works
+                    USERAUTH_REQUEST if user.is_none() => {
+                        let name = fields.text()?;
+                        if fields.string()? != b"ssh-connection" {
+                            return Err(self.disconnect(
+                                SERVICE_NOT_AVAILABLE,
+                                "only ssh-connection is on offer",
+                            ));
+                        }
+
+                        self.send(Message::new(USERAUTH_SUCCESS))?;
+                        self.reader.get_ref().set_read_timeout(None)?;
+                        user = Some(name);
+                    }
+                    // anything past logging in has to wait until they have
+                    kind if user.is_none() && kind >= GLOBAL_REQUEST => {
+                        return Err(self.disconnect(PROTOCOL_ERROR, "log in first"));
+                    }
+                    USERAUTH_REQUEST => {}
+                    GLOBAL_REQUEST => {
+                        fields.string()?;
+                        if fields.boolean()? {
+                            self.send(Message::new(REQUEST_FAILURE))?;
+                        }
+                    }
+                    CHANNEL_OPEN => {
+                        let kind = fields.string()?;
+                        let theirs = fields.uint()?;
+                        let window = fields.uint()?;
+                        let max_packet = fields.uint()?;
+
+                        if kind != b"session" || channel.is_some() {
+                            self.send(
+                                Message::new(CHANNEL_OPEN_FAILURE)
+                                    .uint(theirs)
+                                    .uint(1)
+                                    .string(b"there's one game to a connection")
+                                    .string(b""),
+                            )?;
+                            continue;
+                        }
+
+                        channel = Some(Channel {
+                            theirs,
+                            window: Arc::new(Window::new(window)),
+                            max_packet: (max_packet as usize).clamp(1, MAX_OUTPUT),
+                            room: WINDOW,
+                            term: "xterm-256color".to_string(),
+                            size: PtySize::default(),
+                            game: None,
+                            closed: Arc::new(AtomicBool::new(false)),
+                        });
+                        self.send(
+                            Message::new(CHANNEL_OPEN_CONFIRMATION)
+                                .uint(theirs)
+                                .uint(0)
+                                .uint(WINDOW)
+                                .uint(MAX_DATA),
+                        )?;
+                    }
+                    kind @ (CHANNEL_WINDOW_ADJUST
+                    | CHANNEL_DATA
+                    | CHANNEL_EOF
+                    | CHANNEL_CLOSE
+                    | CHANNEL_REQUEST) => {
+                        let (Some(channel), 0) = (&mut channel, fields.uint()?) else {
+                            return Err(self.disconnect(PROTOCOL_ERROR, "there's no such channel"));
+                        };
+
+                        match kind {
+                            CHANNEL_WINDOW_ADJUST => channel.window.add(fields.uint()?),
+                            CHANNEL_DATA => self.typed(channel, fields.string()?)?,
+                            CHANNEL_CLOSE => {
+                                if !channel.closed.swap(true, Ordering::SeqCst) {
+                                    self.send(Message::new(CHANNEL_CLOSE).uint(channel.theirs))?;
+                                }
+
+                                return Ok(user);
+                            }
+                            CHANNEL_REQUEST => {
+                                let user = user.as_deref().unwrap_or_default();
+                                self.request(channel, &mut fields, user, args)?;
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {
+                        let sequence = self.incoming.sequence.wrapping_sub(1);
+                        self.send(Message::new(UNIMPLEMENTED).uint(sequence))?;
+                    }
+                }
+            }
+        }
+
+        // keys pressed in the client go to the game, and once it's taken in half of what it's allowed more room's
+        // made
+        fn typed(&self, channel: &mut Channel, data: &[u8]) -> io::Result<()> {
+            // the game might have just ended, which doesn't end the connection
+            if let Some(game) = &mut channel.game {
+                let _ = game.input.write_all(data).and_then(|_| game.input.flush());
+            }
+
+            channel.room = channel.room.saturating_sub(data.len() as u32);
+            if channel.room < WINDOW / 2 {
+                self.send(
+                    Message::new(CHANNEL_WINDOW_ADJUST)
+                        .uint(channel.theirs)
+                        .uint(WINDOW - channel.room),
+                )?;
+                channel.room = WINDOW;
+            }
+
+            Ok(())
+        }
+
+        fn request(
+            &self,
+            channel: &mut Channel,
+            fields: &mut Fields,
+            user: &str,
+            args: &[String],
+        ) -> io::Result<()> {
+            let kind = fields.string()?;
+            let reply = fields.boolean()?;
+
+            let done = match kind {
+                b"pty-req" => {
+                    let term = fields.text()?;
+                    if !term.is_empty()
+                        && term.len() <= 64
+                        && term.chars().all(|char| char.is_ascii_graphic())
+                    {
+                        channel.term = term;
+                    }
+
+                    channel.size = size(fields.uint()?, fields.uint()?);
+                    true
+                }
+                b"window-change" => {
+                    channel.size = size(fields.uint()?, fields.uint()?);
+
+                    if let Some(game) = &channel.game {
+                        let _ = game.master.resize(channel.size);
+                    }
+
+                    true
+                }
+                b"shell" if channel.game.is_none() => match start(channel, user, args) {
+                    Ok((output, child)) => {
+                        // the reply goes before any of the game's output
+                        if reply {
+                            self.send(Message::new(CHANNEL_SUCCESS).uint(channel.theirs))?;
+                        }
+
+                        let (sender, window, closed) = (
+                            self.sender.clone(),
+                            channel.window.clone(),
+                            channel.closed.clone(),
+                        );
+                        let (theirs, max_packet) = (channel.theirs, channel.max_packet);
+                        thread::spawn(move || {
+                            pass_output(output, child, sender, window, closed, theirs, max_packet)
+                        });
+
+                        return Ok(());
+                    }
+                    Err(error) => {
+                        println!("couldn't start a game: {error}");
+                        false
+                    }
+                },
+                // there's only the game to run, and nothing else it needs from the client
+                _ => false,
+            };
+
+            if reply {
+                let kind = if done {
+                    CHANNEL_SUCCESS
+                } else {
+                    CHANNEL_FAILURE
+                };
+                self.send(Message::new(kind).uint(channel.theirs))?;
+            }
+
+            Ok(())
+        }
+
+        fn read(&mut self) -> io::Result<Vec<u8>> {
+            self.incoming.open(&mut self.reader)
+        }
+
+        // the next message of the key exchange. strictly nothing else is allowed in the middle of it
+        fn read_kex(&mut self) -> io::Result<Vec<u8>> {
+            loop {
+                let message = self.read()?;
+
+                if self.strict || !matches!(message[0], IGNORE | DEBUG) {
+                    return Ok(message);
+                }
+            }
+        }
+
+        fn send(&self, message: Message) -> io::Result<()> {
+            self.sender.lock().unwrap().send(message)
+        }
+
+        // tell the client why it's being dropped, and give back an error saying the same thing
+        fn disconnect(&self, reason: u32, description: &str) -> io::Error {
+            let _ = self.send(disconnect(reason, description));
+            io::Error::other(description)
+        }
+    }
+
+    fn kexinit() -> Message {
+        let mut cookie = [0; 16];
+        OsRng.fill_bytes(&mut cookie);
+
+        let kex = format!("{},{STRICT_SERVER}", KEX.join(","));
+        Message::new(KEXINIT)
+            .bytes(&cookie)
+            .string(kex.as_bytes())
+            .string(HOST_KEY_ALGORITHM.as_bytes())
+            .string(CIPHER.as_bytes())
+            .string(CIPHER.as_bytes())
+            .string(MAC.as_bytes())
+            .string(MAC.as_bytes())
+            .string(b"none")
+            .string(b"none")
+            .string(b"")
+            .string(b"")
+            .boolean(false)
+            .uint(0)
+    }
+
+    // a terminal's size from what the client says, anything silly is taken as the usual 80x24
+    fn size(columns: u32, rows: u32) -> PtySize {
+        let default = PtySize::default();
+
+        PtySize {
+            cols: u16::try_from(columns)
+                .ok()
+                .filter(|&columns| columns > 0)
+                .unwrap_or(default.cols),
+            rows: u16::try_from(rows)
+                .ok()
+                .filter(|&rows| rows > 0)
+                .unwrap_or(default.rows),
+            ..default
+        }
+    }
+
+    // start a game in a terminal of its own: this same program, run as `user`'s profile when that can be a profile
+    fn start(
+        channel: &mut Channel,
+        user: &str,
+        args: &[String],
+    ) -> io::Result<(Box<dyn Read + Send>, Box<dyn Child + Send + Sync>)> {
+        let pty = native_pty_system()
+            .openpty(channel.size)
+            .map_err(pty_error)?;
+        let mut command = CommandBuilder::new(env::current_exe()?);
+
+        if paths::is_profile_name(user) {
+            command.args(["--profile", user]);
+        }
+
+        command.args(args);
+        command.env("TERM", &channel.term);
+
+        let child = pty.slave.spawn_command(command).map_err(pty_error)?;
+        // the game has the other end now, so reading ours stops once it's exited
+        drop(pty.slave);
+
+        let output = pty.master.try_clone_reader().map_err(pty_error)?;
+        channel.game = Some(Game {
+            input: pty.master.take_writer().map_err(pty_error)?,
+            killer: child.clone_killer(),
+            master: pty.master,
+        });
+
+        Ok((output, child))
+    }
+
+    // the pty crate has errors of its own, which only need to be said
+    fn pty_error(error: impl fmt::Display) -> io::Error {
+        io::Error::other(error.to_string())
+    }
+
+    // everything the game draws goes to the client, as much as it's got room for at a time. once the game's over
+    // the client's told how it exited and the channel's closed, which ends the connection
+    fn pass_output(
+        mut output: Box<dyn Read + Send>,
+        mut child: Box<dyn Child + Send + Sync>,
+        sender: Arc<Mutex<Sender>>,
+        window: Arc<Window>,
+        closed: Arc<AtomicBool>,
+        theirs: u32,
+        max_packet: usize,
+    ) {
+        let mut buffer = [0; MAX_OUTPUT];
+
+        loop {
+            let read = match output.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+
+            let mut data = &buffer[..read];
+            while !data.is_empty() {
+                let Some(room) = window.take(data.len().min(max_packet)) else {
+                    return;
+                };
+
+                let message = Message::new(CHANNEL_DATA)
+                    .uint(theirs)
+                    .string(&data[..room]);
+                if sender.lock().unwrap().send(message).is_err() {
+                    return;
+                }
+
+                data = &data[room..];
+            }
+        }
+
+        let status = child.wait().map_or(1, |status| status.exit_code());
+        let mut sender = sender.lock().unwrap();
+
+        let _ = sender.send(
+            Message::new(CHANNEL_REQUEST)
+                .uint(theirs)
+                .string(b"exit-status")
+                .boolean(false)
+                .uint(status),
+        );
+        let _ = sender.send(Message::new(CHANNEL_EOF).uint(theirs));
+
+        if !closed.swap(true, Ordering::SeqCst) {
+            let _ = sender.send(Message::new(CHANNEL_CLOSE).uint(theirs));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn packets_only_open_with_the_right_keys() {
+            let keys = || Keys::new([1; 32], [2; 32], [3; 32]);
+            let mut sending = Direction {
+                keys: Some(keys()),
+                sequence: 7,
+            };
+            let mut receiving = Direction {
+                keys: Some(keys()),
+                sequence: 7,
+            };
+
+            let packet = sending.seal(b"hello");
+            assert_eq!(receiving.open(&mut &packet[..]).unwrap(), b"hello");
+
+            // a flipped bit or a packet out of order is refused
+            let mut packet = sending.seal(b"again");
+            packet[6] ^= 1;
+            assert!(receiving.open(&mut &packet[..]).is_err());
+
+            let mut receiving = Direction {
+                keys: Some(keys()),
+                sequence: 7,
+            };
+            sending.seal(b"skipped");
+            let packet = sending.seal(b"late");
+            assert!(receiving.open(&mut &packet[..]).is_err());
+
+            // and so is one that says it's longer than any packet could be
+            let mut huge = ((MAX_PACKET + 4) as u32).to_be_bytes().to_vec();
+            huge.resize(8, 0);
+            assert!(Direction::default().open(&mut &huge[..]).is_err());
+        }
+    }
+}
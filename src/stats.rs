@@ -0,0 +1,687 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    board::Board,
+    config::Assist,
+    daily, paths,
+    replay::Replay,
+    session::Outcome,
+    versioned::{self, Versioned},
+};
+
+// how many of the latest games the sparkline shows
+const RECENT: usize = 20;
+
+// the columns of an exported CSV, games and marathons both. a game leaves boards and ramp empty, a marathon leaves
+// everything from level to three_bv empty
+const CSV_COLUMNS: [&str; 14] = [
+    "kind",
+    "level",
+    "outcome",
+    "duration",
+    "assist",
+    "no_flags",
+    "daily",
+    "time_limit",
+    "cells",
+    "mines",
+    "three_bv",
+    "boards",
+    "ramp",
+    "finished",
+];
+
+// columns an export from before they were added won't have, they're read as 0 (or no time limit)
+const NEWER_COLUMNS: [&str; 4] = ["time_limit", "cells", "mines", "three_bv"];
+
+// where everyone's skill rating starts, and how far it moves after a game: further for the first few while it finds
+// its level
+const STARTING_SKILL: f64 = 1000.0;
+const PROVISIONAL_GAMES: usize = 10;
+const PROVISIONAL_STEP: f64 = 40.0;
+const STEP: f64 = 20.0;
+
+// one finished game, kept forever in stats.json
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub level: u8,
+    pub outcome: Outcome,
+    // milliseconds from the first reveal to the end
+    pub duration: u64,
+    #[serde(default)]
+    pub assist: Assist,
+    #[serde(default)]
+    pub no_flags: bool,
+    // the day of the daily challenge, daily games are counted on their own
+    #[serde(default)]
+    pub daily: Option<u64>,
+    // the time limit in milliseconds of a timed challenge, which are counted on their own too
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+    // the size of the board and its 3BV, which the skill rating goes by. 0 for games from before they were kept
+    #[serde(default)]
+    pub cells: usize,
+    #[serde(default)]
+    pub mines: usize,
+    #[serde(default)]
+    pub three_bv: usize,
+    // seconds since the unix epoch when the game ended
+    pub finished: u64,
+}
+
+impl Record {
+    // how hard the board was, on the same scale as a skill rating: a beginner board's about 1000 and an advanced one
+    // about 1600. it goes up with the 3BV (doubling it adds 200) and with how crowded the mines are
+    pub fn strength(&self) -> f64 {
+        let density = self.mines as f64 / self.cells.max(1) as f64;
+        1000.0 + 200.0 * (self.three_bv.max(1) as f64 / 20.0).log2() + 2000.0 * (density - 0.12)
+    }
+
+    // how well the game went against its board, from 0 for a loss up to 1 for a win at 2 3BV a second or faster. any
+    // win's at least a half
+    pub fn score(&self) -> f64 {
+        match self.outcome {
+            Outcome::Won => {
+                let speed = self.three_bv as f64 / (self.duration.max(1) as f64 / 1000.0);
+                0.5 + 0.5 * (speed / 2.0).min(1.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+// one marathon, boards cleared one after another on the same clock until a mine went off
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MarathonRecord {
+    pub boards: usize,
+    // milliseconds from the first reveal on the first board to the end
+    pub duration: u64,
+    // whether the boards got harder as it went
+    pub ramp: bool,
+    pub finished: u64,
+}
+
+// a single number for how well someone plays across every level, see Stats::skill()
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Skill {
+    pub rating: f64,
+    // how many games it's from
+    pub games: usize,
+    // how much the latest game moved it
+    pub change: f64,
+}
+
+// every game that's been won or lost and every marathon, oldest first
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub games: Vec<Record>,
+    #[serde(default)]
+    pub marathons: Vec<MarathonRecord>,
+}
+
+// what the dashboard shows for one level
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LevelStats {
+    pub played: usize,
+    pub won: usize,
+    // the fastest and average unassisted wins
+    pub best: Option<Duration>,
+    pub average: Option<Duration>,
+    // wins in a row, up to the latest game and the most ever
+    pub streak: usize,
+    pub longest_streak: usize,
+    // whether each of the latest games was won, oldest first
+    pub recent: Vec<bool>,
+}
+
+impl Versioned for Stats {
+    const VERSION: u32 = 1;
+}
+
+impl Stats {
+    pub fn load() -> io::Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        versioned::from_str(&text)
+    }
+
+    // add a finished game to the stats file
+    pub fn record(replay: &Replay, outcome: Outcome) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut stats = Self::load()?;
+        stats.games.push(Record {
+            level: replay.level,
+            outcome,
+            duration: replay.duration().as_millis() as u64,
+            assist: replay.assist,
+            no_flags: replay.no_flags,
+            daily: replay.daily,
+            time_limit: replay.time_limit,
+            cells: replay.width * replay.height,
+            mines: replay.mines.len(),
+            three_bv: Board::with_mines(replay.width, replay.height, &replay.mines)
+                .three_bv()
+                .1,
+            finished: now(),
+        });
+
+        stats.save(&path)
+    }
+
+    // add a marathon that's just ended to the stats file
+    pub fn record_marathon(boards: usize, duration: Duration, ramp: bool) -> io::Result<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut stats = Self::load()?;
+        stats.marathons.push(MarathonRecord {
+            boards,
+            duration: duration.as_millis() as u64,
+            ramp,
+            finished: now(),
+        });
+
+        stats.save(&path)
+    }
+
+    // the marathon with the most boards, the quickest one if there's a tie
+    pub fn longest_marathon(&self, ramp: bool) -> Option<&MarathonRecord> {
+        self.marathons
+            .iter()
+            .filter(|marathon| marathon.ramp == ramp && marathon.boards > 0)
+            .max_by_key(|marathon| (marathon.boards, Reverse(marathon.duration)))
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, versioned::to_string(self)?)
+    }
+
+    // no-flag games are counted on their own, apart from the regular ones, and daily challenges and timed challenges
+    // aren't counted here at all
+    pub fn level(&self, level: u8, no_flags: bool) -> LevelStats {
+        let games: Vec<&Record> = self
+            .games
+            .iter()
+            .filter(|game| {
+                game.level == level
+                    && game.no_flags == no_flags
+                    && game.daily.is_none()
+                    && game.time_limit.is_none()
+            })
+            .collect();
+
+        Self::summarise(&games)
+    }
+
+    // every level and time limit timed challenges have been played on, in that order
+    pub fn time_limits(&self) -> Vec<(u8, u64)> {
+        let mut limits: Vec<(u8, u64)> = self
+            .games
+            .iter()
+            .filter_map(|game| Some((game.level, game.time_limit?)))
+            .collect();
+        limits.sort();
+        limits.dedup();
+        limits
+    }
+
+    // the timed challenges on a level with one time limit, each limit's a game of its own
+    pub fn timed(&self, level: u8, limit: u64) -> LevelStats {
+        let games: Vec<&Record> = self
+            .games
+            .iter()
+            .filter(|game| game.level == level && game.time_limit == Some(limit))
+            .collect();
+
+        Self::summarise(&games)
+    }
+
+    // the daily challenges, where the streak is days in a row with a win rather than games
+    pub fn daily(&self) -> LevelStats {
+        let games: Vec<&Record> = self
+            .games
+            .iter()
+            .filter(|game| game.daily.is_some())
+            .collect();
+        let mut stats = Self::summarise(&games);
+
+        let mut days: Vec<(u64, bool)> = Vec::new();
+        for game in &games {
+            let (day, won) = (game.daily.unwrap_or_default(), game.outcome == Outcome::Won);
+
+            match days.iter_mut().find(|(seen, _)| *seen == day) {
+                Some((_, day_won)) => *day_won |= won,
+                None => days.push((day, won)),
+            }
+        }
+        days.sort();
+
+        let (mut streak, mut longest, mut last) = (0, 0, None);
+        for (day, won) in days {
+            streak = match (won, last) {
+                (true, Some(last)) if last + 1 == day => streak + 1,
+                (true, _) => 1,
+                (false, _) => 0,
+            };
+            longest = usize::max(longest, streak);
+            last = Some(day);
+        }
+
+        // missing a day ends the streak, even before the next one's played
+        if last.is_some_and(|last| last + 1 < daily::today()) {
+            streak = 0;
+        }
+
+        stats.streak = streak;
+        stats.longest_streak = longest;
+        stats
+    }
+
+    // an elo-style rating worked out over every unassisted game, oldest first. each one's a match against the board:
+    // the rating goes up by more for beating a board that was expected to win and down by more for losing to one that
+    // was expected to lose. None until a game with a 3BV has been played
+    pub fn skill(&self) -> Option<Skill> {
+        let mut skill: Option<Skill> = None;
+
+        for game in self
+            .games
+            .iter()
+            .filter(|game| game.assist == Assist::Off && game.three_bv > 0)
+        {
+            let Skill { rating, games, .. } = skill.unwrap_or(Skill {
+                rating: STARTING_SKILL,
+                games: 0,
+                change: 0.0,
+            });
+            let expected = 1.0 / (1.0 + 10f64.powf((game.strength() - rating) / 400.0));
+            let step = if games < PROVISIONAL_GAMES {
+                PROVISIONAL_STEP
+            } else {
+                STEP
+            };
+            let change = step * (game.score() - expected);
+
+            skill = Some(Skill {
+                rating: rating + change,
+                games: games + 1,
+                change,
+            });
+        }
+
+        skill
+    }
+
+    fn summarise(games: &[&Record]) -> LevelStats {
+        let times: Vec<u64> = games
+            .iter()
+            .filter(|game| game.outcome == Outcome::Won && game.assist == Assist::Off)
+            .map(|game| game.duration)
+            .collect();
+
+        let mut stats = LevelStats {
+            played: games.len(),
+            won: games
+                .iter()
+                .filter(|game| game.outcome == Outcome::Won)
+                .count(),
+            best: times.iter().min().map(|&time| Duration::from_millis(time)),
+            average: (!times.is_empty())
+                .then(|| Duration::from_millis(times.iter().sum::<u64>() / times.len() as u64)),
+            recent: games[games.len().saturating_sub(RECENT)..]
+                .iter()
+                .map(|game| game.outcome == Outcome::Won)
+                .collect(),
+            ..Default::default()
+        };
+
+        for game in games {
+            stats.streak = match game.outcome {
+                Outcome::Won => stats.streak + 1,
+                _ => 0,
+            };
+            stats.longest_streak = stats.longest_streak.max(stats.streak);
+        }
+
+        stats
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("stats.json"))
+    }
+
+    // add every game and marathon from `other` that isn't here already, keeping them oldest first. gives how many
+    // games and marathons were new
+    pub fn merge(&mut self, other: Stats) -> (usize, usize) {
+        let (games, marathons) = (self.games.len(), self.marathons.len());
+
+        for game in other.games {
+            if !self.games.contains(&game) {
+                self.games.push(game);
+            }
+        }
+
+        for marathon in other.marathons {
+            if !self.marathons.contains(&marathon) {
+                self.marathons.push(marathon);
+            }
+        }
+
+        // sorting's stable, so games that ended in the same second stay in the order they were played
+        self.games.sort_by_key(|game| game.finished);
+        self.marathons.sort_by_key(|marathon| marathon.finished);
+
+        (self.games.len() - games, self.marathons.len() - marathons)
+    }
+
+    // merge `other` into the stats file, see merge()
+    pub fn import(other: Stats) -> io::Result<(usize, usize)> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    "couldn't find a data directory for this platform",
+                ))
+            }
+        };
+
+        let mut stats = Self::load()?;
+        let added = stats.merge(other);
+        stats.save(&path)?;
+
+        Ok(added)
+    }
+
+    // every game and then every marathon, a line each under a header of CSV_COLUMNS
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_COLUMNS.join(",");
+        csv.push('\n');
+
+        for game in &self.games {
+            let daily = game.daily.map(|day| day.to_string()).unwrap_or_default();
+            let row = [
+                "game".to_string(),
+                game.level.to_string(),
+                csv_name(game.outcome),
+                game.duration.to_string(),
+                csv_name(game.assist),
+                game.no_flags.to_string(),
+                daily,
+                game.time_limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default(),
+                game.cells.to_string(),
+                game.mines.to_string(),
+                game.three_bv.to_string(),
+                String::new(),
+                String::new(),
+                game.finished.to_string(),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        for marathon in &self.marathons {
+            let row = [
+                "marathon".to_string(),
+                String::new(),
+                String::new(),
+                marathon.duration.to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                marathon.boards.to_string(),
+                marathon.ramp.to_string(),
+                marathon.finished.to_string(),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    // read back what to_csv() wrote. the columns are found by the header, so they can be in any order
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or("the file's empty")?
+            .split(',')
+            .map(str::trim)
+            .collect();
+
+        if let Some(missing) = CSV_COLUMNS
+            .iter()
+            .find(|column| !header.contains(column) && !NEWER_COLUMNS.contains(column))
+        {
+            return Err(format!("there's no {missing} column"));
+        }
+
+        let mut stats = Self::default();
+
+        for (row, line) in lines.enumerate() {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            let cell = |column: &str| {
+                header
+                    .iter()
+                    .position(|&name| name == column)
+                    .and_then(|i| cells.get(i))
+                    .copied()
+                    .unwrap_or_default()
+            };
+            let number = |column: &str| match cell(column) {
+                "" if NEWER_COLUMNS.contains(&column) => Ok(0),
+                text => text.parse::<u64>().map_err(|_| {
+                    format!("row {}: {column} should be a number, not {text:?}", row + 1)
+                }),
+            };
+            let flag = |column: &str| {
+                cell(column).parse::<bool>().map_err(|_| {
+                    format!(
+                        "row {}: {column} should be true or false, not {:?}",
+                        row + 1,
+                        cell(column)
+                    )
+                })
+            };
+
+            match cell("kind") {
+                "game" => stats.games.push(Record {
+                    level: number("level")? as u8,
+                    outcome: csv_value(cell("outcome"))
+                        .map_err(|error| format!("row {}: {error}", row + 1))?,
+                    duration: number("duration")?,
+                    assist: csv_value(cell("assist"))
+                        .map_err(|error| format!("row {}: {error}", row + 1))?,
+                    no_flags: flag("no_flags")?,
+                    daily: match cell("daily") {
+                        "" => None,
+                        _ => Some(number("daily")?),
+                    },
+                    time_limit: match cell("time_limit") {
+                        "" => None,
+                        _ => Some(number("time_limit")?),
+                    },
+                    cells: number("cells")? as usize,
+                    mines: number("mines")? as usize,
+                    three_bv: number("three_bv")? as usize,
+                    finished: number("finished")?,
+                }),
+                "marathon" => stats.marathons.push(MarathonRecord {
+                    boards: number("boards")? as usize,
+                    duration: number("duration")?,
+                    ramp: flag("ramp")?,
+                    finished: number("finished")?,
+                }),
+                kind => {
+                    return Err(format!(
+                        "row {}: the kind should be game or marathon, not {kind:?}",
+                        row + 1
+                    ))
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+// an outcome or assist level spelled the way it is in the JSON
+fn csv_name<T: Serialize>(value: T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn csv_value<T: DeserializeOwned>(name: &str) -> Result<T, String> {
+    serde_json::from_value(Value::String(name.to_string()))
+        .map_err(|_| format!("{name:?} isn't one of the values that can go there"))
+}
+
+// seconds since the unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_come_back_in_without_doubling_up() {
+        let game = |finished, outcome| Record {
+            level: 2,
+            outcome,
+            duration: 41_500,
+            assist: Assist::Chord,
+            no_flags: false,
+            daily: (finished == 30).then_some(20_000),
+            time_limit: (finished == 20).then_some(60_000),
+            cells: 256,
+            mines: 40,
+            three_bv: 60,
+            finished,
+        };
+
+        let mut here = Stats {
+            games: vec![game(10, Outcome::Won), game(30, Outcome::Lost)],
+            marathons: Vec::new(),
+        };
+        let there = Stats {
+            games: vec![game(20, Outcome::Abandoned), game(30, Outcome::Lost)],
+            marathons: vec![MarathonRecord {
+                boards: 4,
+                duration: 90_000,
+                ramp: true,
+                finished: 25,
+            }],
+        };
+
+        let read = Stats::from_csv(&there.to_csv()).unwrap();
+        assert_eq!(
+            (read.games.clone(), read.marathons.clone()),
+            (there.games, there.marathons)
+        );
+
+        // the game they've both got is only counted once, and the one from elsewhere goes in where it was played
+        assert_eq!(here.merge(read), (1, 1));
+        assert_eq!(
+            here.games
+                .iter()
+                .map(|game| game.finished)
+                .collect::<Vec<_>>(),
+            [10, 20, 30]
+        );
+        // the daily and the timed challenge each go in their own rows
+        assert_eq!(here.level(2, false).played, 1);
+        assert_eq!(here.time_limits(), [(2, 60_000)]);
+        assert_eq!(here.timed(2, 60_000).played, 1);
+
+        assert!(Stats::from_csv("kind,level\ngame,1\n").is_err());
+    }
+
+    #[test]
+    fn skill_goes_by_how_hard_the_board_was() {
+        let game = |cells, mines, three_bv, outcome, duration| Record {
+            level: 1,
+            outcome,
+            duration,
+            assist: Assist::Off,
+            no_flags: false,
+            daily: None,
+            time_limit: None,
+            cells,
+            mines,
+            three_bv,
+            finished: 0,
+        };
+        let skill = |games| {
+            Stats {
+                games,
+                marathons: Vec::new(),
+            }
+            .skill()
+            .unwrap()
+        };
+
+        let beginner = game(81, 10, 20, Outcome::Won, 20_000);
+        let advanced = game(576, 99, 120, Outcome::Won, 120_000);
+        assert!(advanced.strength() > beginner.strength() + 500.0);
+
+        // beating a harder board counts for more, and so does beating it quicker
+        assert!(skill(vec![advanced.clone()]).rating > skill(vec![beginner.clone()]).rating);
+        assert!(
+            skill(vec![game(81, 10, 20, Outcome::Won, 8_000)]).rating
+                > skill(vec![beginner.clone()]).rating
+        );
+        assert!(skill(vec![game(81, 10, 20, Outcome::Lost, 5_000)]).rating < STARTING_SKILL);
+
+        // assisted games and ones from before the 3BV was kept are left out
+        let mut assisted = advanced;
+        assisted.assist = Assist::Chord;
+        let rated = skill(vec![
+            beginner,
+            assisted,
+            game(0, 0, 0, Outcome::Lost, 1_000),
+        ]);
+        assert_eq!(rated.games, 1);
+        assert_eq!(rated.rating, STARTING_SKILL + rated.change);
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use ratatui::text::Spans;
+use serde::Deserialize;
+
+use crate::{rating::Band, theme::Theme, ui};
+
+// where the status bar goes relative to the board
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+// everything the status bar shows
+#[derive(Clone, PartialEq, Debug)]
+pub struct Status {
+    pub difficulty: &'static str,
+    pub elapsed: Duration,
+    // the clock counts down to this instead of up, in a timed challenge
+    pub time_limit: Option<Duration>,
+    // lives left, when playing with them
+    pub lives: Option<u8>,
+    // how hard the board is, once that's been worked out
+    pub rating: Option<Band>,
+    // milliseconds we're behind (positive) or ahead (negative) of our best run
+    pub pace: Option<i64>,
+    // the clock and pace to the millisecond instead of the tenth, for speedruns
+    pub precise: bool,
+    pub mines: usize,
+    pub flags: usize,
+    // how the player we're racing over the network is getting on
+    pub opponent: Option<String>,
+    // where the cursor is, as shown to the player
+    pub cursor: String,
+}
+
+impl Status {
+    // the single line of information drawn above or below the board
+    pub fn line(&self, theme: &Theme) -> Spans<'static> {
+        let lives = match self.lives {
+            Some(lives) => format!(" | {lives} {}", if lives == 1 { "life" } else { "lives" }),
+            None => String::new(),
+        };
+
+        let rating = match self.rating {
+            Some(band) => format!(" | rated {band}"),
+            None => String::new(),
+        };
+
+        let opponent = match &self.opponent {
+            Some(opponent) => format!(" | {opponent}"),
+            None => String::new(),
+        };
+
+        let places = if self.precise { 3 } else { 1 };
+        let clock = match self.time_limit {
+            Some(limit) => format!(
+                "{:.places$}s left",
+                limit.saturating_sub(self.elapsed).as_secs_f64()
+            ),
+            None => format!("{:.places$}s", self.elapsed.as_secs_f64()),
+        };
+
+        let text = format!(
+            "{}{rating}{lives}{opponent} | {clock} | {} mines left | {} flags | {} ",
+            self.difficulty,
+            self.mines.saturating_sub(self.flags),
+            self.flags,
+            self.cursor,
+        );
+
+        let mut spans = vec![ui::styled(text, &theme.status_bar)];
+
+        if let Some(pace) = self.pace {
+            let text = format!(
+                "{}{:.places$}s",
+                if pace > 0 { '+' } else { '-' },
+                pace.abs() as f64 / 1000.0
+            );
+            let style = if pace > 0 {
+                &theme.pace_behind
+            } else {
+                &theme.pace_ahead
+            };
+
+            spans.push(ui::styled(text, style));
+        }
+
+        Spans::from(spans)
+    }
+}
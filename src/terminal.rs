@@ -0,0 +1,168 @@
+use std::{
+    env, fs,
+    io::{stdout, Write},
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableFocusChange, EnableFocusChange},
+    style::Print,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand, QueueableCommand, Result,
+};
+
+use crate::paths;
+
+// whether the terminal is set up for the game and needs putting back
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// sets the terminal up for the game (raw mode on the alternate screen, so the shell's scrollback is left alone, and
+// telling us when it goes in and out of focus) and puts it back the way it was when it's dropped
+#[derive(Debug)]
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn enter() -> Result<Self> {
+        ACTIVE.store(true, Ordering::Relaxed);
+        terminal::enable_raw_mode()?;
+        stdout()
+            .execute(EnterAlternateScreen)?
+            .execute(EnableFocusChange)?;
+
+        Ok(Self { _private: () })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+// make sure a crash puts the terminal back before the panic message gets printed,
+// otherwise it ends up mangled by raw mode on a screen that's about to disappear
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        log_crash(info);
+        default_hook(info);
+    }));
+}
+
+// where the last crash is written down, so --bug-report can pick it up after the message has scrolled away
+pub fn crash_log() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("crash.log"))
+}
+
+// a crash that can't be written down still gets printed, so anything going wrong here is left at that
+fn log_crash(info: &PanicHookInfo) {
+    let Some(path) = crash_log() else {
+        return;
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let _ = fs::write(
+        path,
+        format!("minesweeper {}\n{info}\n", env!("CARGO_PKG_VERSION")),
+    );
+}
+
+// put the terminal back the way we found it. it's fine to call this more than once,
+// only the first call after TerminalGuard::enter() does anything
+pub fn restore() -> Result<()> {
+    if !ACTIVE.swap(false, Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let mut out = stdout();
+    restore_title(&mut out)?;
+    terminal::disable_raw_mode()?;
+    out.execute(DisableFocusChange)?
+        .execute(LeaveAlternateScreen)?
+        .execute(Show)?;
+
+    Ok(())
+}
+
+// programs that run the game inside another terminal
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Multiplexer {
+    Tmux,
+    Zellij,
+    Screen,
+}
+
+impl Multiplexer {
+    // the multiplexer we're running in, going by the variables each one sets for its panes
+    pub fn detect() -> Option<Self> {
+        let set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+
+        if set("TMUX") {
+            Some(Multiplexer::Tmux)
+        } else if set("ZELLIJ") {
+            Some(Multiplexer::Zellij)
+        } else if set("STY") {
+            Some(Multiplexer::Screen)
+        } else {
+            None
+        }
+    }
+
+    // wrap an escape sequence so it goes straight through to the terminal outside the multiplexer
+    // instead of being swallowed by it. tmux only lets these through with `set -g allow-passthrough on`,
+    // and zellij doesn't have a way through at all
+    pub fn passthrough(&self, sequence: &str) -> Option<String> {
+        match self {
+            Multiplexer::Tmux => Some(format!(
+                "\x1bPtmux;{}\x1b\\",
+                sequence.replace('\x1b', "\x1b\x1b")
+            )),
+            Multiplexer::Screen => Some(format!("\x1bP{sequence}\x1b\\")),
+            Multiplexer::Zellij => None,
+        }
+    }
+}
+
+// send an escape sequence to the terminal we're drawing in, and to the one outside the multiplexer if we can
+fn send<W: Write>(out: &mut W, sequence: &str) -> Result<()> {
+    out.queue(Print(sequence))?;
+
+    if let Some(wrapped) =
+        Multiplexer::detect().and_then(|multiplexer| multiplexer.passthrough(sequence))
+    {
+        out.queue(Print(wrapped))?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+// whether we saved the terminal's title and need to put it back
+static TITLE_SAVED: AtomicBool = AtomicBool::new(false);
+
+// set the terminal window's title, saving the old one the first time so restore_title() can put it back
+pub fn set_title<W: Write>(out: &mut W, title: &str) -> Result<()> {
+    let save = match TITLE_SAVED.swap(true, Ordering::Relaxed) {
+        false => "\x1b[22;0t",
+        true => "",
+    };
+
+    send(out, &format!("{save}\x1b]2;{title}\x07"))
+}
+
+fn restore_title<W: Write>(out: &mut W) -> Result<()> {
+    match TITLE_SAVED.swap(false, Ordering::Relaxed) {
+        true => send(out, "\x1b[23;0t"),
+        false => Ok(()),
+    }
+}
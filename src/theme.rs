@@ -0,0 +1,437 @@
+use std::{fmt::Display, str::FromStr};
+
+use crossterm::style::{self, Color, StyledContent, Stylize};
+use serde::Deserialize;
+
+use crate::color::{self, ColorSupport};
+
+// the look of a single piece of the ui
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Style {
+    // text color, the terminal's default if unset
+    #[serde(deserialize_with = "color::deserialize")]
+    pub fg: Option<Color>,
+    // background color, the terminal's default if unset
+    #[serde(deserialize_with = "color::deserialize")]
+    pub bg: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    pub fn apply<D: Display>(&self, content: D) -> StyledContent<D> {
+        let mut styled = style::style(content);
+
+        if let Some(fg) = self.fg {
+            styled = styled.with(fg);
+        }
+
+        if let Some(bg) = self.bg {
+            styled = styled.on(bg);
+        }
+
+        if self.bold {
+            styled = styled.bold();
+        }
+
+        styled
+    }
+
+    const fn fg(color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            bg: None,
+            bold: true,
+        }
+    }
+
+    const fn bold() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: true,
+        }
+    }
+}
+
+// everything that decides how the game looks, both the board and the screens around it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub board: BoardStyle,
+    // regular menu text
+    pub menu: Style,
+    // the level that's currently selected in the menu
+    pub menu_highlight: Style,
+    // messages drawn over the board, like the win/lose message
+    pub overlay: Style,
+    // the box drawn around the board
+    pub border: Style,
+    // the column letters and row numbers
+    pub labels: Style,
+    // the line of information above or below the board
+    pub status_bar: Style,
+    // the pace delta when we're faster than our best run
+    pub pace_ahead: Style,
+    // the pace delta when we're slower than our best run
+    pub pace_behind: Style,
+    // which events ring the terminal bell
+    pub bell: Bell,
+    // the colors confetti comes in when you win
+    pub confetti: Vec<Style>,
+}
+
+// things that can happen in a game that you might want to hear about
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Sound {
+    Reveal,
+    Flag,
+    Explosion,
+    Win,
+}
+
+// ring the terminal bell on any of these, they're all off unless turned on
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Bell {
+    pub reveal: bool,
+    pub flag: bool,
+    pub explosion: bool,
+    pub win: bool,
+}
+
+impl Bell {
+    pub fn rings(&self, sound: Sound) -> bool {
+        match sound {
+            Sound::Reveal => self.reveal,
+            Sound::Flag => self.flag,
+            Sound::Explosion => self.explosion,
+            Sound::Win => self.win,
+        }
+    }
+}
+
+// the built-in themes, picked with `preset` under theme in the config file or with --theme.
+// names can be written with dashes too (retro-dos), since that's how they look on the command line
+#[derive(Copy, Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    #[default]
+    Default,
+    Classic,
+    Dark,
+    Light,
+    #[serde(alias = "retro-dos")]
+    RetroDos,
+    // colors that colorblind players can tell apart, with shapes doing the work colors usually do
+    Colorblind,
+    #[serde(alias = "high-contrast")]
+    HighContrast,
+}
+
+impl Preset {
+    // the preset written the same way as the theme section of a config file
+    pub fn yaml(&self) -> &'static str {
+        match self {
+            Preset::Default => "{}",
+            Preset::Classic => include_str!("themes/classic.yaml"),
+            Preset::Dark => include_str!("themes/dark.yaml"),
+            Preset::Light => include_str!("themes/light.yaml"),
+            Preset::RetroDos => include_str!("themes/retro_dos.yaml"),
+            Preset::Colorblind => include_str!("themes/colorblind.yaml"),
+            Preset::HighContrast => include_str!("themes/high_contrast.yaml"),
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = serde_yaml::Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_value(serde_yaml::Value::from(name))
+    }
+}
+
+impl Theme {
+    // swap every color the terminal can't show for the closest one it can
+    pub fn downgrade(&mut self, support: ColorSupport) {
+        let board = &mut self.board;
+        let styles = [
+            &mut board.empty_style,
+            &mut board.mine_style,
+            &mut board.covered_style,
+            &mut board.marked_style,
+            &mut board.wall_style,
+            &mut board.solution_style,
+            &mut self.menu,
+            &mut self.menu_highlight,
+            &mut self.overlay,
+            &mut self.border,
+            &mut self.labels,
+            &mut self.status_bar,
+            &mut self.pace_ahead,
+            &mut self.pace_behind,
+        ];
+
+        for style in styles
+            .into_iter()
+            .chain(&mut board.numbers)
+            .chain(&mut self.confetti)
+        {
+            style.fg = style.fg.map(|fg| support.downgrade(fg));
+            style.bg = style.bg.map(|bg| support.downgrade(bg));
+        }
+    }
+
+    // strip out every color and anything that isn't plain ASCII, for serial consoles, dumb terminals and NO_COLOR
+    pub fn make_plain(&mut self) {
+        let plain = Style::default();
+        let board = &mut self.board;
+
+        board.emoji = false;
+        board.ascii = true;
+
+        for (glyph, fallback) in [
+            (&mut board.empty, ' '),
+            (&mut board.mine, '*'),
+            (&mut board.covered, '.'),
+            (&mut board.marked, '?'),
+            (&mut board.wall, '#'),
+        ] {
+            if !glyph.is_ascii() {
+                *glyph = fallback;
+            }
+        }
+
+        for (num, digit) in ('1'..='8').zip(&mut board.digits) {
+            if !digit.is_ascii() {
+                *digit = num;
+            }
+        }
+
+        board.empty_style = plain;
+        board.mine_style = plain;
+        board.covered_style = plain;
+        board.marked_style = plain;
+        board.wall_style = plain;
+        board.solution_style = plain;
+        board.numbers = [plain; 8];
+
+        self.menu = plain;
+        self.menu_highlight = plain;
+        self.overlay = plain;
+        self.border = plain;
+        self.labels = plain;
+        self.status_bar = plain;
+        self.pace_ahead = plain;
+        self.pace_behind = plain;
+        self.confetti = vec![plain];
+    }
+}
+
+// whether the terminal can't be trusted with colors or anything beyond ASCII:
+// NO_COLOR is set (https://no-color.org) or TERM says it's a dumb terminal
+pub fn plain_terminal() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+
+    no_color || dumb
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            board: BoardStyle::default(),
+            menu: Style::default(),
+            menu_highlight: Style::bold(),
+            overlay: Style::bold(),
+            border: Style::default(),
+            labels: Style::default(),
+            status_bar: Style::default(),
+            pace_ahead: Style::fg(Color::Green),
+            pace_behind: Style::fg(Color::Red),
+            bell: Bell::default(),
+            confetti: vec![
+                Style::fg(Color::Red),
+                Style::fg(Color::Yellow),
+                Style::fg(Color::Green),
+                Style::fg(Color::Cyan),
+                Style::fg(Color::Blue),
+                Style::fg(Color::Magenta),
+            ],
+        }
+    }
+}
+
+// the characters and colors of the cells on the board
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BoardStyle {
+    pub empty: char,
+    pub mine: char,
+    pub covered: char,
+    pub marked: char,
+    // walls on puzzle boards, which aren't cells at all
+    pub wall: char,
+    // characters (spaces) between cells
+    pub gap_x: usize,
+    // number of newlines inbetween lines
+    pub gap_y: usize,
+    // show letters above the columns and numbers beside the rows
+    pub labels: bool,
+    // draw a box around the board
+    pub border: bool,
+    // draw every cell in its own box (┌─┬─┐), gap_x becomes the space either side of a cell and gap_y is ignored
+    pub grid: bool,
+    // draw emoji tiles (🟩 🚩 💣 1️⃣) instead of the characters and styles below
+    pub emoji: bool,
+    // draw the border and grid with +, - and | instead of box-drawing characters
+    pub ascii: bool,
+    pub empty_style: Style,
+    pub mine_style: Style,
+    pub covered_style: Style,
+    pub marked_style: Style,
+    pub wall_style: Style,
+    // the cells the solver would have clicked, shown after giving up
+    pub solution_style: Style,
+    // the style of the numbers 1 through 8
+    pub numbers: [Style; 8],
+    // the characters drawn for the numbers 1 through 8, so they can be letters, dice or anything else
+    pub digits: [char; 8],
+}
+
+impl Default for BoardStyle {
+    fn default() -> Self {
+        Self {
+            empty: ' ',
+            mine: '!',
+            covered: '·',
+            marked: '?',
+            wall: '█',
+            gap_x: 1,
+            gap_y: 0,
+            labels: false,
+            border: false,
+            grid: false,
+            emoji: false,
+            ascii: false,
+            empty_style: Style::default(),
+            mine_style: Style::fg(Color::Red),
+            covered_style: Style::default(),
+            marked_style: Style::fg(Color::Cyan),
+            wall_style: Style::default(),
+            solution_style: Style {
+                fg: None,
+                bg: Some(Color::DarkGreen),
+                bold: false,
+            },
+            numbers: [
+                Style::fg(Color::Blue),
+                Style::fg(Color::Green),
+                Style::fg(Color::Yellow),
+                Style::fg(Color::DarkBlue),
+                Style::fg(Color::DarkGreen),
+                Style::fg(Color::DarkMagenta),
+                Style::fg(Color::Red),
+                Style::fg(Color::DarkRed),
+            ],
+            digits: ['1', '2', '3', '4', '5', '6', '7', '8'],
+        }
+    }
+}
+
+// what a cell looks like to the player right now
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Tile {
+    Empty,
+    Covered,
+    Marked,
+    Mine,
+    Wall,
+    // an uncovered cell with mines around it
+    Number(usize),
+}
+
+impl BoardStyle {
+    // the text drawn for a tile
+    pub fn glyph(&self, tile: Tile) -> StyledContent<String> {
+        if self.emoji {
+            return style::style(match tile {
+                Tile::Empty => "⬜".to_string(),
+                Tile::Covered => "🟩".to_string(),
+                Tile::Marked => "🚩".to_string(),
+                Tile::Mine => "💣".to_string(),
+                Tile::Wall => "⬛".to_string(),
+                // a digit followed by the emoji variation selector and the combining keycap
+                Tile::Number(num) => format!("{num}\u{fe0f}\u{20e3}"),
+            });
+        }
+
+        match tile {
+            Tile::Empty => self.empty_style.apply(self.empty.to_string()),
+            Tile::Covered => self.covered_style.apply(self.covered.to_string()),
+            Tile::Marked => self.marked_style.apply(self.marked.to_string()),
+            Tile::Mine => self.mine_style.apply(self.mine.to_string()),
+            Tile::Wall => self.wall_style.apply(self.wall.to_string()),
+            Tile::Number(num @ 1..=8) => {
+                self.numbers[num - 1].apply(self.digits[num - 1].to_string())
+            }
+            Tile::Number(num) => style::style(num.to_string()),
+        }
+    }
+
+    // how many terminal columns a tile takes up, emoji are drawn double width
+    pub fn cell_width(&self) -> usize {
+        match self.emoji {
+            true => 2,
+            false => 1,
+        }
+    }
+
+    // the whitespace printed after every cell
+    pub fn gap(&self) -> String {
+        " ".repeat(self.gap_x)
+    }
+
+    // whether there's anything drawn around the board, either the border or the grid
+    pub fn framed(&self) -> bool {
+        self.border || self.grid
+    }
+
+    // how many columns and rows apart cells are drawn
+    pub fn pitch(&self) -> (usize, usize) {
+        match self.grid {
+            true => ((self.gap_x * 2) + self.cell_width() + 1, 2),
+            false => (self.gap_x + self.cell_width(), self.gap_y + 1),
+        }
+    }
+
+    // a horizontal line of the frame across a board `width` cells wide, like ┌───┬───┐.
+    // the joins only show up in grid mode, the plain border is one unbroken line
+    pub fn frame_line(&self, width: usize, left: char, join: char, right: char) -> String {
+        let line = match self.grid {
+            true => vec!["─".repeat((self.gap_x * 2) + self.cell_width()); width]
+                .join(&join.to_string()),
+            false => "─".repeat(self.gap_x + (width * self.pitch().0)),
+        };
+
+        format!("{left}{line}{right}")
+            .chars()
+            .map(|c| self.frame(c))
+            .collect()
+    }
+
+    // a box-drawing character, or the closest thing in plain ASCII if we're sticking to that
+    pub fn frame(&self, c: char) -> char {
+        if !self.ascii {
+            return c;
+        }
+
+        match c {
+            '─' => '-',
+            '│' => '|',
+            '┌' | '┬' | '┐' | '├' | '┼' | '┤' | '└' | '┴' | '┘' => '+',
+            c => c,
+        }
+    }
+}
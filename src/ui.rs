@@ -0,0 +1,530 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io::{stdout, Stdout},
+};
+
+use crossterm::{
+    style::{Attribute, Color as TermColor, StyledContent},
+    Result,
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    board::{column_label, Board},
+    theme::{self, Theme, Tile},
+};
+
+pub type Backend = CrosstermBackend<Stdout>;
+pub type Frame<'a> = ratatui::Frame<'a, Backend>;
+
+// the terminal as ratatui sees it. it keeps the last frame around and only sends the cells that changed in the next one,
+// so nothing gets cleared and drawn again in front of the player
+pub struct Screen {
+    terminal: ratatui::Terminal<Backend>,
+}
+
+impl Screen {
+    // start from a blank screen, whatever was there before
+    pub fn new() -> Result<Self> {
+        let mut terminal = ratatui::Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        Ok(Self { terminal })
+    }
+
+    // draw a whole frame. the cursor's hidden unless the frame puts it somewhere
+    pub fn draw(&mut self, render: impl FnOnce(&mut Frame)) -> Result<()> {
+        self.terminal.draw(render)?;
+        Ok(())
+    }
+
+    // a whole screen of text from `top` down, like a menu or the help. lines too long for the terminal carry on below
+    pub fn page(&mut self, top: u16, lines: Vec<Spans<'static>>) -> Result<()> {
+        self.draw(|frame| {
+            let area = below(frame.size(), top);
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+        })
+    }
+
+    // the terminal itself, for what isn't drawn like the bell and the window title
+    pub fn out(&mut self) -> &mut Backend {
+        self.terminal.backend_mut()
+    }
+}
+
+impl fmt::Debug for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Screen").finish_non_exhaustive()
+    }
+}
+
+// the part of `area` from `row` down
+pub fn below(area: Rect, row: u16) -> Rect {
+    let row = row.min(area.bottom());
+
+    Rect {
+        y: row,
+        height: area.bottom() - row,
+        ..area
+    }
+}
+
+// ratatui names the colors the other way round from crossterm, its red is crossterm's dark red
+pub fn color(color: TermColor) -> Color {
+    match color {
+        TermColor::Reset => Color::Reset,
+        TermColor::Black => Color::Black,
+        TermColor::DarkGrey => Color::DarkGray,
+        TermColor::Red => Color::LightRed,
+        TermColor::DarkRed => Color::Red,
+        TermColor::Green => Color::LightGreen,
+        TermColor::DarkGreen => Color::Green,
+        TermColor::Yellow => Color::LightYellow,
+        TermColor::DarkYellow => Color::Yellow,
+        TermColor::Blue => Color::LightBlue,
+        TermColor::DarkBlue => Color::Blue,
+        TermColor::Magenta => Color::LightMagenta,
+        TermColor::DarkMagenta => Color::Magenta,
+        TermColor::Cyan => Color::LightCyan,
+        TermColor::DarkCyan => Color::Cyan,
+        TermColor::White => Color::White,
+        TermColor::Grey => Color::Gray,
+        TermColor::Rgb { r, g, b } => Color::Rgb(r, g, b),
+        TermColor::AnsiValue(value) => Color::Indexed(value),
+    }
+}
+
+// a piece of the theme as ratatui draws it
+pub fn style(style: &theme::Style) -> Style {
+    let mut converted = Style::default();
+
+    if let Some(fg) = style.fg {
+        converted = converted.fg(color(fg));
+    }
+
+    if let Some(bg) = style.bg {
+        converted = converted.bg(color(bg));
+    }
+
+    if style.bold {
+        converted = converted.add_modifier(Modifier::BOLD);
+    }
+
+    converted
+}
+
+// `text` in a piece of the theme
+pub fn styled<D: Display>(text: D, theme_style: &theme::Style) -> Span<'static> {
+    Span::styled(text.to_string(), style(theme_style))
+}
+
+// text styled the crossterm way, like a glyph from the theme, as ratatui draws it
+pub fn span<D: Display>(styled: StyledContent<D>) -> Span<'static> {
+    let content = styled.style();
+    let mut style = Style::default();
+
+    if let Some(fg) = content.foreground_color {
+        style = style.fg(color(fg));
+    }
+
+    if let Some(bg) = content.background_color {
+        style = style.bg(color(bg));
+    }
+
+    let attributes = [
+        (Attribute::Bold, Modifier::BOLD),
+        (Attribute::Dim, Modifier::DIM),
+        (Attribute::Italic, Modifier::ITALIC),
+        (Attribute::Underlined, Modifier::UNDERLINED),
+        (Attribute::SlowBlink, Modifier::SLOW_BLINK),
+        (Attribute::RapidBlink, Modifier::RAPID_BLINK),
+        (Attribute::Reverse, Modifier::REVERSED),
+        (Attribute::Hidden, Modifier::HIDDEN),
+        (Attribute::CrossedOut, Modifier::CROSSED_OUT),
+    ];
+
+    for (attribute, modifier) in attributes {
+        if content.attributes.has(attribute) {
+            style = style.add_modifier(modifier);
+        }
+    }
+
+    Span::styled(styled.content().to_string(), style)
+}
+
+// draw `span` at (x, y), cut off at the edge of `area` instead of running past it
+fn put(buf: &mut Buffer, area: Rect, (x, y): (u16, u16), span: &Span) {
+    if x < area.right() && y < area.bottom() {
+        buf.set_span(x, y, span, area.right() - x);
+    }
+}
+
+// a minesweeper board with its labels and frame, laid out the way the theme says, for drawing in any ratatui app.
+// by default it shows what a player would see. rendered with a MinesweeperState it also works out where the selected
+// cell ended up, so the terminal cursor can be put on it
+pub struct MinesweeperWidget<'a> {
+    board: &'a Board,
+    theme: &'a Theme,
+    show_everything: bool,
+    show_mines: bool,
+    exploded: &'a [(usize, usize)],
+    highlighted: &'a [(usize, usize)],
+    drawn_over: Option<&'a HashMap<(usize, usize), Span<'a>>>,
+}
+
+// which cell is selected, and where it was drawn the last time the widget was rendered
+#[derive(Debug, Clone, Default)]
+pub struct MinesweeperState {
+    pub selected: (usize, usize),
+    cursor: Option<(u16, u16)>,
+}
+
+impl MinesweeperState {
+    pub fn new(selected: (usize, usize)) -> Self {
+        Self {
+            selected,
+            cursor: None,
+        }
+    }
+
+    // where the selected cell was drawn, or None if it didn't fit in the area
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor
+    }
+}
+
+impl<'a> MinesweeperWidget<'a> {
+    pub fn new(board: &'a Board, theme: &'a Theme) -> Self {
+        Self {
+            board,
+            theme,
+            show_everything: false,
+            show_mines: false,
+            exploded: &[],
+            highlighted: &[],
+            drawn_over: None,
+        }
+    }
+
+    // uncover every cell, like after the game's over
+    pub fn show_everything(mut self, show: bool) -> Self {
+        self.show_everything = show;
+        self
+    }
+
+    // show where the mines are but leave the rest covered, like for a puzzle that's been failed or in the editor
+    pub fn show_mines(mut self, show: bool) -> Self {
+        self.show_mines = show;
+        self
+    }
+
+    // mines that have gone off, shown even if they've been covered or flagged since
+    pub fn exploded(mut self, cells: &'a [(usize, usize)]) -> Self {
+        self.exploded = cells;
+        self
+    }
+
+    // covered cells drawn in the theme's solution style, like the safe way through after giving up
+    pub fn highlighted(mut self, cells: &'a [(usize, usize)]) -> Self {
+        self.highlighted = cells;
+        self
+    }
+
+    // cells drawn as something else entirely, like the frames of an animation
+    pub fn drawn_over(mut self, cells: &'a HashMap<(usize, usize), Span<'a>>) -> Self {
+        self.drawn_over = Some(cells);
+        self
+    }
+
+    // what the player gets to see of a cell
+    pub fn tile(&self, x: usize, y: usize) -> Tile {
+        let board = self.board;
+        let hidden = !self.show_everything;
+
+        if board.is_wall(x, y) {
+            Tile::Wall
+        } else if self.exploded.contains(&(x, y)) || (self.show_mines && board.is_mine(x, y)) {
+            Tile::Mine
+        } else if board.is_flagged(x, y) && hidden {
+            Tile::Marked
+        } else if !board.is_uncovered(x, y) && hidden {
+            Tile::Covered
+        } else if board.is_mine(x, y) {
+            Tile::Mine
+        } else {
+            match board.adjacent_mines(x, y) {
+                0 => Tile::Empty,
+                num => Tile::Number(num),
+            }
+        }
+    }
+
+    // where the top left cell is drawn, from the top left of the widget
+    pub fn origin(&self) -> (u16, u16) {
+        let board = &self.theme.board;
+        let border = match board.framed() {
+            true => 1 + board.gap_x as u16,
+            false => 0,
+        };
+
+        (
+            self.label_width() + border,
+            board.labels as u16 + board.framed() as u16,
+        )
+    }
+
+    // where a cell is drawn, from the top left of the widget
+    pub fn position(&self, (x, y): (usize, usize)) -> (u16, u16) {
+        let (pitch_x, pitch_y) = self.theme.board.pitch();
+        let (left, top) = self.origin();
+
+        (
+            left + (x * pitch_x) as u16,
+            top + ((self.board.height() - (y + 1)) * pitch_y) as u16,
+        )
+    }
+
+    // how many rows it takes up, labels and frame included
+    pub fn rows(&self) -> u16 {
+        let board = &self.theme.board;
+        let pitch_y = board.pitch().1;
+
+        self.origin().1
+            + ((pitch_y * self.board.height()) - (pitch_y - 1)) as u16
+            + board.framed() as u16
+    }
+
+    // how many columns it takes up, labels and frame included
+    pub fn columns(&self) -> u16 {
+        let board = &self.theme.board;
+        let frame = match (board.grid, board.border) {
+            (true, _) => 1,
+            (false, true) => 2 + board.gap_x,
+            (false, false) => 0,
+        };
+
+        self.label_width() + ((self.board.width() * board.pitch().0) + frame) as u16
+    }
+
+    // how wide the row numbers and the space after them are
+    fn label_width(&self) -> u16 {
+        match self.theme.board.labels {
+            true => self.board.height().to_string().len() as u16 + 1,
+            false => 0,
+        }
+    }
+
+    fn glyph(&self, x: usize, y: usize) -> Span<'a> {
+        if let Some(drawn) = self.drawn_over.and_then(|cells| cells.get(&(x, y))) {
+            return drawn.clone();
+        }
+
+        let board = &self.theme.board;
+        let mut glyph = span(board.glyph(self.tile(x, y)));
+
+        if !self.board.is_uncovered(x, y) && self.highlighted.contains(&(x, y)) {
+            glyph.style = glyph.style.patch(style(&board.solution_style));
+        }
+
+        glyph
+    }
+}
+
+impl Widget for MinesweeperWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme;
+        let board = &theme.board;
+        let (width, height) = (self.board.width(), self.board.height());
+        let pitch_x = board.pitch().0;
+        let label_width = self.label_width();
+        let border = style(&theme.border);
+        let wall = board.frame('│').to_string();
+        let line =
+            |left, join, right| Span::styled(board.frame_line(width, left, join, right), border);
+
+        // the letters above each column
+        if board.labels {
+            let labels: String = (0..width)
+                .map(|x| format!("{:1$}", column_label(x), pitch_x))
+                .collect();
+            put(
+                buf,
+                area,
+                (area.x + self.origin().0, area.y),
+                &styled(labels, &theme.labels),
+            );
+        }
+
+        if board.framed() {
+            put(
+                buf,
+                area,
+                (area.x + label_width, area.y + self.origin().1 - 1),
+                &line('┌', '┬', '┐'),
+            );
+        }
+
+        // we go through the rows backwards so that we'll show the data from the bottom up (1st quadrant of a Cartesian plane)
+        // while still drawing from the top down
+        for y in (0..height).rev() {
+            let (left, row) = self.position((0, y));
+            let (left, row) = (area.x + left, area.y + row);
+
+            // the row number, counting from 1 at the bottom
+            if board.labels {
+                let label = format!("{:>1$} ", y + 1, label_width as usize - 1);
+                put(buf, area, (area.x, row), &styled(label, &theme.labels));
+            }
+
+            if board.framed() {
+                put(
+                    buf,
+                    area,
+                    (area.x + label_width, row),
+                    &Span::styled(wall.clone(), border),
+                );
+            }
+
+            for x in 0..width {
+                let column = left + (x * pitch_x) as u16;
+                put(buf, area, (column, row), &self.glyph(x, y));
+
+                // in grid mode every cell has its own walls
+                if board.grid {
+                    let column = column + (board.cell_width() + board.gap_x) as u16;
+                    put(
+                        buf,
+                        area,
+                        (column, row),
+                        &Span::styled(wall.clone(), border),
+                    );
+                }
+            }
+
+            if board.border && !board.grid {
+                put(
+                    buf,
+                    area,
+                    (left + (width * pitch_x) as u16, row),
+                    &Span::styled(wall.clone(), border),
+                );
+            }
+
+            // the lines between rows: a lattice in grid mode, or the sides of the border carrying on through the gaps
+            if y > 0 && board.grid {
+                put(
+                    buf,
+                    area,
+                    (area.x + label_width, row + 1),
+                    &line('├', '┼', '┤'),
+                );
+            } else if y > 0 && board.border {
+                let inner_width = board.gap_x + (width * pitch_x);
+
+                for gap_row in 1..=board.gap_y as u16 {
+                    let sides = format!("{wall}{}{wall}", " ".repeat(inner_width));
+                    put(
+                        buf,
+                        area,
+                        (area.x + label_width, row + gap_row),
+                        &Span::styled(sides, border),
+                    );
+                }
+            }
+        }
+
+        if board.framed() {
+            put(
+                buf,
+                area,
+                (area.x + label_width, area.y + self.rows() - 1),
+                &line('└', '┴', '┘'),
+            );
+        }
+    }
+}
+
+impl StatefulWidget for MinesweeperWidget<'_> {
+    type State = MinesweeperState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut MinesweeperState) {
+        let (column, row) = self.position(state.selected);
+        let (column, row) = (area.x + column, area.y + row);
+
+        state.cursor = (column < area.right() && row < area.bottom()).then_some((column, row));
+        Widget::render(self, area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_board_lays_out_like_the_theme_says() {
+        let mut theme = Theme::default();
+        theme.board.labels = true;
+        theme.board.border = true;
+
+        let board = Board::new(2, 2);
+        let glyphs = HashMap::from(
+            [((0, 0), "a"), ((1, 0), "b"), ((0, 1), "c"), ((1, 1), "d")]
+                .map(|(cell, glyph)| (cell, Span::raw(glyph))),
+        );
+        let widget = MinesweeperWidget::new(&board, &theme).drawn_over(&glyphs);
+        assert_eq!(
+            (widget.origin(), widget.rows(), widget.columns()),
+            ((4, 2), 5, 9)
+        );
+
+        let area = Rect::new(0, 0, 9, 5);
+        let mut buf = Buffer::empty(area);
+        let mut state = MinesweeperState::new((1, 0));
+        StatefulWidget::render(widget, area, &mut buf, &mut state);
+
+        let rows: Vec<String> = (0..5)
+            .map(|y| (0..9).map(|x| buf.get(x, y).symbol.clone()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "    A B  ",
+                "  ┌─────┐",
+                "2 │ c d │",
+                "1 │ a b │",
+                "  └─────┘"
+            ]
+        );
+        assert_eq!(state.cursor(), Some((6, 3)));
+    }
+
+    #[test]
+    fn mines_that_went_off_stay_shown() {
+        let mut board = Board::with_mines(2, 1, &[0]);
+        board.toggle_flag(0, 0);
+        let theme = Theme::default();
+
+        let widget = MinesweeperWidget::new(&board, &theme);
+        assert_eq!(
+            (widget.tile(0, 0), widget.tile(1, 0)),
+            (Tile::Marked, Tile::Covered)
+        );
+
+        let widget = MinesweeperWidget::new(&board, &theme).exploded(&[(0, 0)]);
+        assert_eq!(widget.tile(0, 0), Tile::Mine);
+
+        let widget = MinesweeperWidget::new(&board, &theme).show_everything(true);
+        assert_eq!(
+            (widget.tile(0, 0), widget.tile(1, 0)),
+            (Tile::Mine, Tile::Number(1))
+        );
+    }
+}
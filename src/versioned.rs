@@ -0,0 +1,125 @@
+use std::io::{self, ErrorKind};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+// something kept in a file that's written by one release and read by later ones. it's written wrapped up with the
+// version of its format, `{"version": 1, "data": ...}`, so a later release knows how to bring it up to date
+pub trait Versioned: Serialize + DeserializeOwned {
+    // the version of the format it's written in now, one more every time the format changes in a way serde's
+    // defaults can't cover
+    const VERSION: u32;
+
+    // bring the JSON of a file written in `version` up to `version + 1`. it's called once for every version between
+    // the file's and VERSION, oldest first. version 0 is a file from before the envelope, which is the data on its own
+    fn migrate(version: u32, data: Value) -> Result<Value, String> {
+        let _ = version;
+        Ok(data)
+    }
+}
+
+// how it's written, the version first so it's the first thing anyone reading the file sees
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+// `value` as it's written to its file
+pub fn to_string<T: Versioned>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&Envelope {
+        version: T::VERSION,
+        data: value,
+    })
+}
+
+// the same, laid out to be read by people
+pub fn to_string_pretty<T: Versioned>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Envelope {
+        version: T::VERSION,
+        data: value,
+    })
+}
+
+// read a file written by this release or any before it. one from a newer release is an error instead of being read
+// wrong and written back over
+pub fn from_str<T: Versioned>(text: &str) -> io::Result<T> {
+    let invalid = |error: String| io::Error::new(ErrorKind::InvalidData, error);
+    let value: Value = serde_json::from_str(text).map_err(|error| invalid(error.to_string()))?;
+
+    let (version, mut data) = match value {
+        Value::Object(mut envelope) if envelope.len() == 2 && envelope.contains_key("data") => {
+            let version = envelope
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| invalid("the version should be a number".to_string()))?;
+            (version as u32, envelope.remove("data").unwrap_or_default())
+        }
+        bare => (0, bare),
+    };
+
+    if version > T::VERSION {
+        return Err(invalid(format!(
+            "this was written by a newer minesweeper (format version {version}, this one reads up to {})",
+            T::VERSION
+        )));
+    }
+
+    for from in version..T::VERSION {
+        data = T::migrate(from, data).map_err(|error| {
+            invalid(format!(
+                "couldn't bring it up from format version {from}: {error}"
+            ))
+        })?;
+    }
+
+    serde_json::from_value(data).map_err(|error| invalid(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    // a format that's changed twice: `time` was in seconds before version 2 and called `secs` before version 3
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Lap {
+        millis: u64,
+    }
+
+    impl Versioned for Lap {
+        const VERSION: u32 = 3;
+
+        fn migrate(version: u32, mut data: Value) -> Result<Value, String> {
+            match version {
+                1 => data["secs"] = json!(data["time"].as_u64().ok_or("there's no time")?),
+                2 => data["millis"] = json!(data["secs"].as_u64().ok_or("there's no secs")? * 1000),
+                _ => (),
+            }
+
+            Ok(data)
+        }
+    }
+
+    #[test]
+    fn old_files_are_brought_up_to_date() {
+        let lap = Lap { millis: 2000 };
+
+        assert_eq!(from_str::<Lap>(&to_string(&lap).unwrap()).unwrap(), lap);
+        assert_eq!(
+            from_str::<Lap>(r#"{"version": 2, "data": {"secs": 2}}"#).unwrap(),
+            lap
+        );
+        // every migration runs in order, from before the envelope too
+        assert_eq!(
+            from_str::<Lap>(r#"{"version": 1, "data": {"time": 2}}"#).unwrap(),
+            lap
+        );
+        assert_eq!(from_str::<Lap>(r#"{"time": 2}"#).unwrap(), lap);
+
+        assert!(from_str::<Lap>(r#"{"version": 4, "data": {"millis": 2000}}"#).is_err());
+        assert!(from_str::<Lap>(r#"{"version": 1, "data": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    board::{Board, MAX_CELLS},
+    generator::Settings,
+    player::{Action, Round},
+    protocol,
+};
+
+// a game for a browser frontend to play, with the same rules as the bots and the terminal game. cells are (x, y) from
+// the bottom left, and the board reads the same as it does for --protocol
+#[wasm_bindgen]
+pub struct WasmGame {
+    round: Round,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    // a generated board, with the mines placed around the first reveal. the same seed gives the same board
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize, mines: usize, seed: u64) -> Result<WasmGame, String> {
+        let cells = match width.checked_mul(height) {
+            Some(0) => return Err("the board needs at least one cell".to_string()),
+            Some(cells) if cells <= MAX_CELLS => cells,
+            // turned away before anything's allocated, running out of memory would take the whole module down
+            _ => {
+                return Err(format!(
+                    "a {width}x{height} board is bigger than minesweeper can play"
+                ))
+            }
+        };
+
+        let settings = Settings {
+            width,
+            height,
+            mines,
+            placement: Default::default(),
+        };
+
+        // the mines have to fit around the safe area the first click opens up
+        let start = settings.start();
+        let opening = Board::new(width, height)
+            .neighbors(start.0, start.1)
+            .count()
+            + 1;
+        if mines > cells.saturating_sub(opening) {
+            return Err(format!(
+                "{mines} mines don't fit on a {width}x{height} board"
+            ));
+        }
+
+        Ok(Self {
+            round: Round::new(settings, seed),
+        })
+    }
+
+    // a board written as a text grid, like the ones --board reads
+    #[wasm_bindgen(js_name = fromText)]
+    pub fn from_text(text: &str) -> Result<WasmGame, String> {
+        let board = Board::from_text(text).map_err(|error| error.to_string())?;
+        Ok(Self {
+            round: Round::with_board(board),
+        })
+    }
+
+    pub fn reveal(&mut self, x: usize, y: usize) -> Result<(), String> {
+        self.round.apply(Action::Reveal(x, y)).map(drop)
+    }
+
+    // flag a covered cell, or take the flag off if it's already flagged
+    pub fn flag(&mut self, x: usize, y: usize) -> Result<(), String> {
+        self.round.apply(Action::Flag(x, y)).map(drop)
+    }
+
+    // open everything around a number that has all its flags
+    pub fn chord(&mut self, x: usize, y: usize) -> Result<(), String> {
+        self.round.apply(Action::Chord(x, y)).map(drop)
+    }
+
+    // playing, won or lost
+    pub fn status(&self) -> String {
+        protocol::status(self.round.standing()).to_string()
+    }
+
+    // a cell as the player sees it: - covered, f flagged, # wall, 0-8 uncovered, and once the game's lost * for a mine
+    // and x for the one that went off. nothing for cells off the board
+    pub fn cell(&self, x: usize, y: usize) -> Option<char> {
+        let view = self.round.view();
+        (x < view.width() && y < view.height()).then(|| protocol::symbol(view.seen(x, y)))
+    }
+
+    pub fn width(&self) -> usize {
+        self.round.view().width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.round.view().height()
+    }
+
+    pub fn mines(&self) -> usize {
+        self.round.view().mines()
+    }
+
+    pub fn flags(&self) -> usize {
+        self.round.view().flags()
+    }
+
+    // everything at once as JSON, the same state --protocol sends
+    pub fn state(&self) -> String {
+        protocol::state(&self.round.view())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_that_cant_be_played_are_refused() {
+        assert!(WasmGame::new(3_000_000, 3_000_000, 10, 0).is_err());
+        assert!(WasmGame::new(usize::MAX, 2, 10, 0).is_err());
+        assert!(WasmGame::new(0, 9, 10, 0).is_err());
+        assert!(WasmGame::new(9, 9, 80, 0).is_err());
+        assert!(WasmGame::new(9, 9, 72, 0).is_ok());
+    }
+}